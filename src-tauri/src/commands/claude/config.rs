@@ -842,6 +842,12 @@ pub async fn update_claude_execution_config(
     Ok(())
 }
 
+/// 获取Claude执行配置的 JSON Schema，供前端渲染设置表单
+#[tauri::command]
+pub async fn get_claude_execution_config_schema() -> Result<serde_json::Value, String> {
+    Ok(crate::utils::config_utils::export_config_schema::<ClaudeExecutionConfig>())
+}
+
 /// 重置Claude执行配置为默认值
 #[tauri::command]
 pub async fn reset_claude_execution_config(app: AppHandle) -> Result<(), String> {
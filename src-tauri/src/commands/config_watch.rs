@@ -0,0 +1,86 @@
+//! 配置文件监听命令
+//!
+//! 外部编辑器修改配置后，前端通过 `config-changed`（`{ path, value }`）或
+//! `config-invalid`（`{ path, error }`）事件得知变化，无需轮询或手动刷新。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{command, AppHandle, Emitter, State};
+
+use crate::commands::claude::ClaudeSettings;
+use crate::mcp::validate_mcp_servers;
+use crate::utils::config_utils::{watch_config_events, ConfigWatcher};
+
+/// 轮询间隔
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 正在监听的配置文件
+#[derive(Default)]
+pub struct ConfigWatchState(pub Mutex<HashMap<PathBuf, ConfigWatcher>>);
+
+/// 开始监听配置文件，变化时向前端推送事件
+///
+/// 文件按 Claude 设置加载，必须为 JSON 对象，其中的 mcpServers 须通过
+/// [`validate_mcp_servers`]；否则推送 `config-invalid`。
+/// 重复监听同一文件不会创建新的监听线程
+#[command]
+pub fn watch_config_file(
+    app: AppHandle,
+    state: State<'_, ConfigWatchState>,
+    path: String,
+) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    let mut watchers = state.0.lock().map_err(|e| e.to_string())?;
+    if watchers.contains_key(&path) {
+        return Ok(());
+    }
+
+    let watcher = watch_config_events::<ClaudeSettings, _, _>(
+        &path,
+        WATCH_INTERVAL,
+        validate_settings,
+        move |event| {
+            if let Err(e) = app.emit(event.name(), &event) {
+                log::error!("Failed to emit {} event: {}", event.name(), e);
+            }
+        },
+    );
+    log::info!("Watching config file {:?}", path);
+    watchers.insert(path, watcher);
+    Ok(())
+}
+
+/// 停止监听配置文件
+///
+/// # 返回值
+/// - `Ok(true)`: 已停止
+/// - `Ok(false)`: 该文件未被监听
+#[command]
+pub fn unwatch_config_file(
+    state: State<'_, ConfigWatchState>,
+    path: String,
+) -> Result<bool, String> {
+    let watcher = state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&PathBuf::from(path));
+    // 在锁外等待监听线程退出
+    Ok(watcher.map(ConfigWatcher::stop).is_some())
+}
+
+/// 校验监听到的设置（非对象的文件在加载为 [`ClaudeSettings`] 时已被拒绝）
+fn validate_settings(settings: &ClaudeSettings) -> Result<(), Vec<String>> {
+    let problems: Vec<String> = validate_mcp_servers(&settings.data)
+        .into_iter()
+        .map(|(name, error)| format!("mcpServers.{}: {}", name, error))
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
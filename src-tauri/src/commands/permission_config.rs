@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Claude权限管理配置结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClaudePermissionConfig {
     pub allowed_tools: Vec<String>,
     pub disallowed_tools: Vec<String>,
@@ -10,7 +11,7 @@ pub struct ClaudePermissionConfig {
     pub enable_dangerous_skip: bool, // 向后兼容选项
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum PermissionMode {
     Interactive,
     AcceptEdits,
@@ -60,7 +61,7 @@ pub const ALL_TOOLS: &[&str] = &[
 ];
 
 /// Claude执行配置结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClaudeExecutionConfig {
     pub output_format: OutputFormat,
     pub timeout_seconds: Option<u32>,
@@ -72,7 +73,7 @@ pub struct ClaudeExecutionConfig {
     pub disable_rewind_git_operations: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum OutputFormat {
     StreamJson,
     Json,
@@ -24,6 +24,7 @@ use commands::claude::{
     cancel_claude_execution, check_claude_version, clear_custom_claude_path, continue_claude_code,
     delete_project, delete_project_permanently, delete_session, delete_sessions_batch,
     execute_claude_code, find_claude_md_files, get_available_tools, get_claude_execution_config,
+    get_claude_execution_config_schema,
     get_claude_path, get_claude_permission_config, get_claude_session_output, get_claude_settings,
     get_codex_system_prompt, get_hooks_config, get_permission_presets, get_project_sessions,
     get_system_prompt, list_directory_contents, list_hidden_projects, list_projects,
@@ -51,6 +52,7 @@ use commands::mcp::{
 use commands::storage::{init_database, AgentDb};
 
 use commands::clipboard::{read_from_clipboard, save_clipboard_image, write_to_clipboard};
+use commands::config_watch::{unwatch_config_file, watch_config_file, ConfigWatchState};
 use commands::prompt_tracker::{
     check_rewind_capabilities, get_prompt_list, get_unified_prompt_list, mark_prompt_completed,
     record_prompt_sent, revert_to_prompt,
@@ -210,6 +212,9 @@ fn main() {
             // Initialize Gemini process state
             app.manage(GeminiProcessState::default());
 
+            // Initialize config file watchers
+            app.manage(ConfigWatchState::default());
+
             // Initialize auto-compact manager for context management
             let auto_compact_manager =
                 Arc::new(commands::context_manager::AutoCompactManager::new());
@@ -323,6 +328,7 @@ fn main() {
             validate_hook_command,
             // 权限管理命令
             get_claude_execution_config,
+            get_claude_execution_config_schema,
             update_claude_execution_config,
             reset_claude_execution_config,
             get_claude_permission_config,
@@ -396,6 +402,9 @@ fn main() {
             save_clipboard_image,
             write_to_clipboard,
             read_from_clipboard,
+            // Config Watch
+            watch_config_file,
+            unwatch_config_file,
             // Provider Management
             get_provider_presets,
             get_current_provider_config,
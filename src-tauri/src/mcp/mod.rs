@@ -39,7 +39,9 @@ pub use gemini::{
     import_from_gemini, remove_server_from_gemini, sync_servers_to_gemini,
     sync_single_server_to_gemini,
 };
-pub use validation::{extract_server_spec, validate_server_spec};
+pub use validation::{
+    extract_server_spec, normalize_mcp_servers, validate_mcp_servers, validate_server_spec,
+};
 
 /// 应用类型
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -42,6 +42,86 @@ pub fn validate_server_spec(spec: &Value) -> Result<(), String> {
     Ok(())
 }
 
+/// 验证配置中 mcpServers 下的所有服务器定义
+///
+/// 在 [`validate_server_spec`] 的基础上，额外检查 args 必须为字符串数组、env 必须为字符串映射
+///
+/// # 返回值
+/// 每个有问题的服务器一条 `(服务器名称, 错误信息)`；mcpServers 不存在时返回空列表
+pub fn validate_mcp_servers(config: &Value) -> Vec<(String, String)> {
+    let Some(servers) = config.get("mcpServers") else {
+        return Vec::new();
+    };
+    let Some(servers) = servers.as_object() else {
+        return vec![(
+            "mcpServers".to_string(),
+            "mcpServers 必须为 JSON 对象".to_string(),
+        )];
+    };
+
+    servers
+        .iter()
+        .filter_map(|(name, spec)| {
+            validate_server_spec(spec)
+                .and_then(|_| validate_server_fields(spec))
+                .err()
+                .map(|e| (name.clone(), e))
+        })
+        .collect()
+}
+
+/// 补全 mcpServers 中服务器定义的默认值
+///
+/// 目前为缺少 args 的 stdio 服务器补上空数组
+///
+/// # 返回值
+/// 被修改的服务器数量
+pub fn normalize_mcp_servers(config: &mut Value) -> usize {
+    let Some(servers) = config.get_mut("mcpServers").and_then(|x| x.as_object_mut()) else {
+        return 0;
+    };
+
+    let mut count = 0;
+    for spec in servers.values_mut() {
+        let Some(obj) = spec.as_object_mut() else {
+            continue;
+        };
+        let is_stdio = obj
+            .get("type")
+            .and_then(|x| x.as_str())
+            .map(|t| t == "stdio")
+            .unwrap_or(true);
+        if is_stdio && !obj.contains_key("args") {
+            obj.insert("args".to_string(), Value::Array(Vec::new()));
+            count += 1;
+        }
+    }
+    count
+}
+
+/// 检查 args、env 字段的类型
+fn validate_server_fields(spec: &Value) -> Result<(), String> {
+    if let Some(args) = spec.get("args") {
+        let valid = args
+            .as_array()
+            .is_some_and(|items| items.iter().all(|x| x.is_string()));
+        if !valid {
+            return Err("args 字段必须为字符串数组".into());
+        }
+    }
+
+    if let Some(env) = spec.get("env") {
+        let valid = env
+            .as_object()
+            .is_some_and(|vars| vars.values().all(|x| x.is_string()));
+        if !valid {
+            return Err("env 字段必须为字符串键值对象".into());
+        }
+    }
+
+    Ok(())
+}
+
 /// 提取服务器规范（移除 UI 辅助字段）
 pub fn extract_server_spec(entry: &Value) -> Result<Value, String> {
     let mut spec = entry.clone();
@@ -68,3 +148,69 @@ pub fn extract_server_spec(entry: &Value) -> Result<Value, String> {
 
     Ok(spec)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_mcp_servers_missing_command() {
+        let config = json!({ "mcpServers": { "fs": { "args": ["/tmp"] } } });
+        let problems = validate_mcp_servers(&config);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].0, "fs");
+        assert!(problems[0].1.contains("command"), "{}", problems[0].1);
+    }
+
+    #[test]
+    fn test_validate_mcp_servers_non_array_args() {
+        let config = json!({
+            "mcpServers": {
+                "fs": { "command": "npx", "args": "-y server" },
+                "git": { "command": "uvx", "args": ["mcp-git", 1] },
+            }
+        });
+        let problems = validate_mcp_servers(&config);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|(_, e)| e.contains("args")));
+    }
+
+    #[test]
+    fn test_validate_mcp_servers_non_string_env() {
+        let config = json!({
+            "mcpServers": {
+                "fs": { "command": "npx", "env": { "DEBUG": true } },
+                "ok": { "command": "npx", "env": { "DEBUG": "1" } },
+            }
+        });
+        let problems = validate_mcp_servers(&config);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].0, "fs");
+        assert!(problems[0].1.contains("env"), "{}", problems[0].1);
+    }
+
+    #[test]
+    fn test_validate_mcp_servers_without_servers() {
+        assert!(validate_mcp_servers(&json!({ "model": "opus" })).is_empty());
+        let problems = validate_mcp_servers(&json!({ "mcpServers": [] }));
+        assert_eq!(problems[0].0, "mcpServers");
+    }
+
+    #[test]
+    fn test_normalize_mcp_servers_fills_args() {
+        let mut config = json!({
+            "mcpServers": {
+                "fs": { "command": "npx" },
+                "git": { "type": "stdio", "command": "uvx", "args": ["mcp-git"] },
+                "remote": { "type": "http", "url": "https://example.com/mcp" },
+            }
+        });
+        assert_eq!(normalize_mcp_servers(&mut config), 1);
+        assert_eq!(config["mcpServers"]["fs"]["args"], json!([]));
+        assert_eq!(config["mcpServers"]["git"]["args"], json!(["mcp-git"]));
+        assert!(config["mcpServers"]["remote"].get("args").is_none());
+        assert!(validate_mcp_servers(&config).is_empty());
+        assert_eq!(normalize_mcp_servers(&mut config), 0);
+    }
+}
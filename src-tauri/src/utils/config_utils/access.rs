@@ -0,0 +1,155 @@
+//! 配置文件可写性检查
+//!
+//! 前端可以在用户开始编辑前判断配置位置是否可写，提前禁用"保存"按钮，
+//! 而不是等到保存失败才提示。
+
+use std::fs;
+use std::path::Path;
+
+/// 检查配置文件是否可写（不实际写入）
+///
+/// # 返回值
+/// - `Ok(())`: 文件存在且可写，或文件不存在、父目录存在/可创建且可写
+/// - `Err(String)`: 不可写的原因
+///
+/// # 检查项
+/// - 目标存在时：必须是文件，且当前用户可写（所在目录不可写时原子写入会经系统临时目录复制写回，见 [`atomic_write`]）
+/// - 目标不存在时：最近的已存在上级目录必须是目录，且当前用户可写、可进入
+///
+/// [`atomic_write`]: super::atomic_write
+///
+/// Unix 上按 `access(2)` 的语义以当前用户的身份判断，而不只看权限位；
+/// 其他平台只检查只读属性
+pub fn check_config_writable(config_path: impl AsRef<Path>) -> Result<(), String> {
+    let path = config_path.as_ref();
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if metadata.is_dir() {
+            return Err(format!("Config path {:?} is a directory", path));
+        }
+        if !file_writable(path, &metadata) {
+            return Err(format!("Config file {:?} is read-only", path));
+        }
+        return Ok(());
+    }
+
+    // 向上查找最近的已存在目录（缺失的目录会在保存时创建）
+    let mut ancestor = path.parent();
+    while let Some(dir) = ancestor {
+        // 相对路径的父目录为空字符串时检查当前目录
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        match fs::metadata(dir) {
+            Ok(metadata) if !metadata.is_dir() => {
+                return Err(format!("{:?} exists but is not a directory", dir));
+            }
+            Ok(metadata) if !dir_writable(dir, &metadata) => {
+                return Err(format!("Config directory {:?} is read-only", dir));
+            }
+            Ok(_) => return Ok(()),
+            Err(_) => ancestor = dir.parent().filter(|_| dir != Path::new(".")),
+        }
+    }
+
+    Err(format!("No existing parent directory for {:?}", path))
+}
+
+#[cfg(unix)]
+fn file_writable(path: &Path, _metadata: &fs::Metadata) -> bool {
+    has_access(path, rustix::fs::Access::WRITE_OK)
+}
+
+/// 目录可写且可进入
+#[cfg(unix)]
+fn dir_writable(dir: &Path, _metadata: &fs::Metadata) -> bool {
+    has_access(
+        dir,
+        rustix::fs::Access::WRITE_OK | rustix::fs::Access::EXEC_OK,
+    )
+}
+
+/// 以当前进程的有效用户身份检查权限
+#[cfg(unix)]
+fn has_access(path: &Path, access: rustix::fs::Access) -> bool {
+    rustix::fs::accessat(rustix::fs::CWD, path, access, rustix::fs::AtFlags::EACCESS).is_ok()
+}
+
+#[cfg(not(unix))]
+fn file_writable(_path: &Path, metadata: &fs::Metadata) -> bool {
+    !metadata.permissions().readonly()
+}
+
+/// Windows 上目录的只读属性不限制在其中创建文件
+#[cfg(not(unix))]
+fn dir_writable(_dir: &Path, _metadata: &fs::Metadata) -> bool {
+    true
+}
+
+/// 配置文件是否可写，见 [`check_config_writable`]
+pub fn can_write_config(config_path: impl AsRef<Path>) -> bool {
+    check_config_writable(config_path).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config_writable() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("nested/dir/settings.json");
+        assert!(can_write_config(&path));
+
+        let file = temp.path().join("settings.json");
+        fs::write(&file, "{}").unwrap();
+        assert!(can_write_config(&file));
+
+        fs::create_dir_all(temp.path().join("blocker")).unwrap();
+        assert!(!can_write_config(temp.path().join("blocker")));
+    }
+
+    #[test]
+    fn test_check_config_writable_rejects_read_only_file() {
+        // root 不受权限位限制，测试没有意义
+        #[cfg(unix)]
+        if rustix::process::geteuid().is_root() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        let file = temp.path().join("settings.json");
+        fs::write(&file, "{}").unwrap();
+
+        let mut permissions = fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions).unwrap();
+        assert!(check_config_writable(&file)
+            .unwrap_err()
+            .contains("read-only"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_config_writable_checks_parent_dir_for_new_files() {
+        use std::os::unix::fs::PermissionsExt;
+        // root 不受目录权限限制，测试没有意义
+        if rustix::process::geteuid().is_root() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("locked");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("settings.json");
+        fs::write(&file, "{}").unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // 已存在的文件可以经系统临时目录写回，新文件需要在目录中创建
+        let existing = check_config_writable(&file);
+        let missing = check_config_writable(dir.join("new.json"));
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(existing.is_ok());
+        assert!(missing.unwrap_err().contains("directory"));
+    }
+}
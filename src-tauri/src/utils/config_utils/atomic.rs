@@ -0,0 +1,167 @@
+//! 原子写入
+//!
+//! 优先在目标文件同目录创建临时文件再重命名（同一文件系统内 rename 是原子的）。
+//! 有些系统的配置目录位于只读或配额受限的挂载点，无法在同目录创建临时文件，
+//! 而跨设备 rename 又会失败，此时退回到"在备用临时目录写入，再复制到目标位置"，并输出警告。
+//!
+//! 目标是符号链接时（如由 dotfiles 仓库管理的 `settings.json`）写入链接指向的文件，
+//! 链接本身保持不变；已有文件的权限也会沿用到新文件上。
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use tempfile::NamedTempFile;
+
+use super::paths::canonical_config_path;
+
+/// 原子写入文件，备用临时目录为系统临时目录
+pub fn atomic_write(path: &Path, content: &[u8]) -> Result<(), String> {
+    atomic_write_via(path, content, &std::env::temp_dir())
+}
+
+/// 原子写入文件，可指定同目录临时文件不可用时使用的备用临时目录
+///
+/// # 参数
+/// - `path`: 目标文件路径
+/// - `content`: 要写入的内容
+/// - `fallback_dir`: 备用临时目录
+///
+/// # 特性
+/// - ✅ 同目录临时文件 + rename，写入过程中崩溃不会留下半截文件
+/// - ✅ 同目录无法创建临时文件时退回到备用目录写入后复制（非原子，会记录警告）
+/// - ✅ 跟随符号链接写入其目标，并保留已有文件的权限
+pub fn atomic_write_via(path: &Path, content: &[u8], fallback_dir: &Path) -> Result<(), String> {
+    let target = canonical_config_path(path);
+    let path = target.as_path();
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    match write_same_dir(path, parent, content) {
+        Ok(()) => Ok(()),
+        Err(SameDirError::Temp(e)) => {
+            log::warn!(
+                "Cannot create temp file next to {:?} ({}), falling back to {:?}; this write is not atomic",
+                path,
+                e,
+                fallback_dir
+            );
+            write_via_fallback(path, content, fallback_dir)
+        }
+        Err(SameDirError::Persist(e)) => {
+            Err(format!("Failed to write config to {:?}: {}", path, e))
+        }
+    }
+}
+
+enum SameDirError {
+    /// 临时文件创建或写入失败（可退回备用目录）
+    Temp(io::Error),
+    /// 重命名到目标位置失败
+    Persist(io::Error),
+}
+
+fn write_same_dir(path: &Path, parent: &Path, content: &[u8]) -> Result<(), SameDirError> {
+    let mut temp = NamedTempFile::new_in(parent).map_err(SameDirError::Temp)?;
+    temp.write_all(content)
+        .and_then(|_| temp.as_file().sync_all())
+        .and_then(|_| copy_permissions(&temp, path))
+        .map_err(SameDirError::Temp)?;
+    temp.persist(path)
+        .map(|_| ())
+        .map_err(|e| SameDirError::Persist(e.error))
+}
+
+fn write_via_fallback(path: &Path, content: &[u8], fallback_dir: &Path) -> Result<(), String> {
+    let mut temp = NamedTempFile::new_in(fallback_dir)
+        .map_err(|e| format!("Failed to create temp file in {:?}: {}", fallback_dir, e))?;
+    temp.write_all(content)
+        .and_then(|_| temp.as_file().sync_all())
+        .map_err(|e| format!("Failed to write temp file in {:?}: {}", fallback_dir, e))?;
+    // fs::copy 会把临时文件的权限带到目标文件上
+    copy_permissions(&temp, path)
+        .map_err(|e| format!("Failed to set permissions on temp file: {}", e))?;
+
+    fs::copy(temp.path(), path)
+        .map_err(|e| format!("Failed to copy config to {:?}: {}", path, e))?;
+    Ok(())
+}
+
+/// 把目标文件现有的权限复制到临时文件（目标不存在时保持临时文件的默认权限）
+fn copy_permissions(temp: &NamedTempFile, path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) => temp.as_file().set_permissions(metadata.permissions()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write_replaces_content_without_leftovers() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        atomic_write(&path, b"{\"a\": 1}").unwrap();
+        atomic_write(&path, b"{\"a\": 2}").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\": 2}");
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_keeps_symlink_and_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("dotfiles.json");
+        let link = temp.path().join("settings.json");
+        fs::write(&real, "{}").unwrap();
+        fs::set_permissions(&real, fs::Permissions::from_mode(0o644)).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        atomic_write(&link, b"{\"a\": 1}").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "{\"a\": 1}");
+        let mode = fs::metadata(&real).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_falls_back_when_dir_not_writable() {
+        use std::os::unix::fs::PermissionsExt;
+        // root 不受目录权限限制，不会走到备用路径，测试没有意义
+        if rustix::process::geteuid().is_root() {
+            return;
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let config_dir = temp.path().join("config");
+        let fallback_dir = temp.path().join("fallback");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::create_dir_all(&fallback_dir).unwrap();
+
+        let path = config_dir.join("settings.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(&config_dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let result = atomic_write_via(&path, b"{\"a\": 1}", &fallback_dir);
+        fs::set_permissions(&config_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        result.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\": 1}");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+}
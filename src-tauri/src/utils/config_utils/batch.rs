@@ -0,0 +1,478 @@
+//! 多文件批量保存
+//!
+//! 应用配置档等操作会同时修改 settings.json、agents.json、mcp.json 等多个文件。
+//! [`ConfigBatch`] 先暂存所有修改，[`ConfigBatch::commit`] 时一次性写出：
+//! 同一路径只写最后一次暂存的内容，内容未变化的文件不重写，减少磁盘写入和文件事件。
+//!
+//! 提交分两步：先为每个文件写出 `<filename>.<事务 id>.pending`，再逐个重命名到目标位置。
+//! 涉及多个文件时，重命名前会在批次所属的配置目录下写入事务日志
+//! `<base_dir>/.transactions/<事务 id>.json`（目标文件可以在该目录之外，如 `~/.claude.json`）。
+//! 提交中途崩溃后，启动时由 [`recover_pending_writes`] 扫描同一目录，按日志完成剩余的重命名；
+//! 没有日志的 `.pending` 文件说明崩溃发生在准备阶段，直接删除。
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::atomic::atomic_write;
+use super::lock::{lock_config, with_config_dir_lock, ConfigLock};
+use super::rate_limit::admit_writes;
+use super::{load_json_config, serialize_config};
+
+/// 事务日志目录名
+pub const TRANSACTIONS_DIR: &str = ".transactions";
+
+/// 批量保存的临时文件后缀
+pub const PENDING_SUFFIX: &str = ".pending";
+
+/// 中断的批量保存的恢复结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    /// 按事务日志完成写入的文件数
+    pub promoted: usize,
+    /// 删除的未提交临时文件数
+    pub discarded: usize,
+}
+
+/// 事务日志，路径均为绝对路径
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionJournal {
+    id: String,
+    entries: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    target: PathBuf,
+    pending: PathBuf,
+}
+
+/// 暂存多个配置文件的修改，提交时统一写出
+#[derive(Debug)]
+pub struct ConfigBatch {
+    /// 事务日志所在的配置目录，与启动时传给 [`recover_pending_writes`] 的目录一致
+    base_dir: PathBuf,
+    pending: BTreeMap<PathBuf, String>,
+}
+
+impl ConfigBatch {
+    /// 创建批量保存
+    ///
+    /// # 参数
+    /// - `base_dir`: 配置目录（如 `~/.claude`），事务日志写在 `<base_dir>/.transactions/` 下
+    pub fn new(base_dir: impl AsRef<Path>) -> Self {
+        Self {
+            base_dir: base_dir.as_ref().to_path_buf(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// 暂存一个配置文件的新内容
+    ///
+    /// 配置在暂存时即被序列化；同一路径重复暂存时以最后一次为准
+    pub fn stage<T>(&mut self, config: &T, config_path: impl AsRef<Path>) -> Result<(), String>
+    where
+        T: Serialize,
+    {
+        let content = serialize_config(config)?;
+        self.pending
+            .insert(config_path.as_ref().to_path_buf(), content);
+        Ok(())
+    }
+
+    /// 已暂存的文件数量
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// 写出所有暂存的修改
+    ///
+    /// # 返回值
+    /// - `Ok(count)`: 实际写入的文件数量（内容未变化的文件被跳过）
+    /// - `Err(String)`: 任一文件写入失败；已替换的文件会恢复为原内容
+    ///
+    /// # 特性
+    /// - ✅ 按路径顺序获取所有文件的写锁后再写入，并发批量提交不会死锁
+    /// - ✅ 先为所有文件准备好同目录临时文件，全部成功后才开始替换
+    /// - ✅ 替换中途失败时回滚已替换的文件
+    /// - ✅ 受写入频率上限约束（见 [`set_config_write_limit`](super::rate_limit::set_config_write_limit)），任一文件超限时整批不写
+    /// - ✅ 涉及多个文件时先写事务日志，进程崩溃后可由 [`recover_pending_writes`] 补完
+    pub fn commit(self) -> Result<usize, String> {
+        for path in self.pending.keys() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    format!("Failed to create config directory {:?}: {}", parent, e)
+                })?;
+            }
+        }
+
+        let _locks: Vec<ConfigLock> = self
+            .pending
+            .keys()
+            .map(|path| lock_config(path))
+            .collect::<Result<_, _>>()?;
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut prepared: Vec<(&PathBuf, PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for (path, content) in &self.pending {
+            let previous = fs::read(path).ok();
+            if previous.as_deref() == Some(content.as_bytes()) {
+                log::debug!("Config {:?} unchanged, skipping write", path);
+                continue;
+            }
+            let pending = pending_path(path, &id);
+            if let Err(e) = write_pending(&pending, content) {
+                fs::remove_file(&pending).ok();
+                discard_pending(&prepared);
+                return Err(e);
+            }
+            prepared.push((path, pending, previous));
+        }
+
+        let changed = prepared.iter().map(|(path, _, _)| path.as_path());
+        if let Err(e) = admit_writes(changed) {
+            discard_pending(&prepared);
+            return Err(e);
+        }
+
+        // 单个文件的重命名本身是原子的，不需要日志
+        let journal = if prepared.len() > 1 {
+            let targets: Vec<(&Path, &Path)> = prepared
+                .iter()
+                .map(|(path, pending, _)| (path.as_path(), pending.as_path()))
+                .collect();
+            match write_journal(&self.base_dir, &id, &targets) {
+                Ok(journal) => Some(journal),
+                Err(e) => {
+                    discard_pending(&prepared);
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut replaced: Vec<(&PathBuf, Option<Vec<u8>>)> = Vec::new();
+        let count = prepared.len();
+        let mut remaining = prepared.into_iter();
+        while let Some((path, pending, previous)) = remaining.next() {
+            if let Err(e) = fs::rename(&pending, path) {
+                fs::remove_file(&pending).ok();
+                discard_pending(&remaining.collect::<Vec<_>>());
+                rollback(replaced);
+                if let Some(journal) = &journal {
+                    fs::remove_file(journal).ok();
+                }
+                return Err(format!(
+                    "Failed to write config to {:?}: {}; earlier files in the batch were restored",
+                    path, e
+                ));
+            }
+            replaced.push((path, previous));
+        }
+
+        if let Some(journal) = &journal {
+            if let Err(e) = fs::remove_file(journal) {
+                log::warn!("Failed to remove transaction journal {:?}: {}", journal, e);
+            }
+        }
+        log::debug!("Committed config batch: {} file(s) written", count);
+        Ok(count)
+    }
+}
+
+/// 恢复中断的批量保存
+///
+/// 启动时调用：读取 `<base_dir>/.transactions/` 下残留的事务日志，并扫描 `base_dir` 下的
+/// `.pending` 临时文件
+/// - 有事务日志的批次已全部准备好，完成剩余的重命名（包括 `base_dir` 之外的目标文件）
+/// - 没有事务日志的临时文件属于未完成准备的批次，删除
+///
+/// 恢复期间持有配置目录锁（见 [`with_config_dir_lock`]）
+pub fn recover_pending_writes(base_dir: &Path) -> Result<RecoveryReport, String> {
+    if !base_dir.is_dir() {
+        return Ok(RecoveryReport::default());
+    }
+
+    with_config_dir_lock(base_dir, || {
+        let mut report = RecoveryReport::default();
+        let journal_dir = base_dir.join(TRANSACTIONS_DIR);
+        let mut journals: Vec<PathBuf> = match fs::read_dir(&journal_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("Failed to read {:?}: {}", journal_dir, e)),
+        };
+        journals.sort();
+        let pending_files: Vec<PathBuf> = walkdir::WalkDir::new(base_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| is_pending_file(path))
+            .collect();
+
+        for journal_path in journals {
+            match load_json_config::<Option<TransactionJournal>>(&journal_path) {
+                Ok(Some(journal)) => {
+                    for entry in journal.entries {
+                        if !entry.pending.is_file() {
+                            continue;
+                        }
+                        let target = &entry.target;
+                        fs::rename(&entry.pending, target).map_err(|e| {
+                            format!(
+                                "Failed to complete interrupted write to {:?}: {}",
+                                target, e
+                            )
+                        })?;
+                        report.promoted += 1;
+                    }
+                    log::info!("Completed interrupted config transaction {}", journal.id);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!(
+                    "Ignoring unreadable transaction journal {:?}: {}",
+                    journal_path,
+                    e
+                ),
+            }
+            fs::remove_file(&journal_path)
+                .map_err(|e| format!("Failed to remove {:?}: {}", journal_path, e))?;
+        }
+
+        for pending in pending_files.iter().filter(|path| path.is_file()) {
+            fs::remove_file(pending)
+                .map_err(|e| format!("Failed to remove {:?}: {}", pending, e))?;
+            report.discarded += 1;
+        }
+
+        if report != RecoveryReport::default() {
+            log::info!(
+                "Recovered interrupted config writes in {:?}: {} promoted, {} discarded",
+                base_dir,
+                report.promoted,
+                report.discarded
+            );
+        }
+        Ok(report)
+    })
+}
+
+fn pending_path(path: &Path, id: &str) -> PathBuf {
+    let mut name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(format!(".{}{}", id, PENDING_SUFFIX));
+    path.with_file_name(name)
+}
+
+/// `<filename>.<32 位十六进制事务 id>.pending`
+fn is_pending_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(PENDING_SUFFIX))
+        .and_then(|stem| stem.rsplit_once('.'))
+        .is_some_and(|(_, id)| id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn write_pending(pending: &Path, content: &str) -> Result<(), String> {
+    let mut file = File::create(pending)
+        .map_err(|e| format!("Failed to create temp file {:?}: {}", pending, e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write temp file {:?}: {}", pending, e))
+}
+
+/// 在 `<base_dir>/.transactions/` 下写入事务日志，返回日志路径
+fn write_journal(base_dir: &Path, id: &str, targets: &[(&Path, &Path)]) -> Result<PathBuf, String> {
+    let absolute = |path: &Path| {
+        std::path::absolute(path).map_err(|e| format!("Failed to resolve {:?}: {}", path, e))
+    };
+    let entries = targets
+        .iter()
+        .map(|(target, pending)| {
+            Ok(JournalEntry {
+                target: absolute(target)?,
+                pending: absolute(pending)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    let journal = TransactionJournal {
+        id: id.to_string(),
+        entries,
+    };
+
+    let dir = base_dir.join(TRANSACTIONS_DIR);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create transaction directory {:?}: {}", dir, e))?;
+    let path = dir.join(format!("{}.json", id));
+    let content = serde_json::to_string_pretty(&journal)
+        .map_err(|e| format!("Failed to serialize transaction journal: {}", e))?;
+    atomic_write(&path, content.as_bytes())?;
+    Ok(path)
+}
+
+fn discard_pending(prepared: &[(&PathBuf, PathBuf, Option<Vec<u8>>)]) {
+    for (_, pending, _) in prepared {
+        fs::remove_file(pending).ok();
+    }
+}
+
+fn rollback(replaced: Vec<(&PathBuf, Option<Vec<u8>>)>) {
+    for (path, previous) in replaced.into_iter().rev() {
+        let result = match previous {
+            Some(content) => atomic_write(path, &content),
+            None => fs::remove_file(path).map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            log::error!("Failed to restore {:?} after batch failure: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_config_batch_dedupes_and_skips_unchanged() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = temp.path().join("settings.json");
+        let agents = temp.path().join("agents.json");
+        let mcp = temp.path().join("nested").join("mcp.json");
+        super::super::save_json_config(&json!({ "agents": [] }), &agents).unwrap();
+
+        let mut batch = ConfigBatch::new(temp.path());
+        assert!(batch.is_empty());
+        batch
+            .stage(&json!({ "model": "sonnet" }), &settings)
+            .unwrap();
+        batch.stage(&json!({ "model": "opus" }), &settings).unwrap();
+        batch.stage(&json!({ "agents": [] }), &agents).unwrap();
+        batch.stage(&json!({ "servers": {} }), &mcp).unwrap();
+        assert_eq!(batch.len(), 3);
+
+        assert_eq!(batch.commit().unwrap(), 2);
+        assert_eq!(
+            super::super::load_json_config::<serde_json::Value>(&settings).unwrap(),
+            json!({ "model": "opus" })
+        );
+        assert!(mcp.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_batch_rolls_back_on_failure() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = temp.path().join("a-settings.json");
+        // 目标是目录，rename 会失败
+        let blocked = temp.path().join("b-blocked.json");
+        fs::create_dir(&blocked).unwrap();
+        fs::write(blocked.join("keep"), "").unwrap();
+        fs::write(&settings, "{}").unwrap();
+
+        let mut batch = ConfigBatch::new(temp.path());
+        batch.stage(&json!({ "model": "opus" }), &settings).unwrap();
+        batch.stage(&json!({}), &blocked).unwrap();
+
+        let err = batch.commit().unwrap_err();
+        assert!(err.contains("b-blocked.json"), "{}", err);
+        assert_eq!(fs::read_to_string(&settings).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_recover_pending_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = temp.path().join("settings.json");
+        let mcp = temp.path().join("nested").join("mcp.json");
+        fs::create_dir_all(mcp.parent().unwrap()).unwrap();
+        fs::write(&settings, "{}").unwrap();
+
+        // 日志已写入、尚未重命名时崩溃
+        let id = "0123456789abcdef0123456789abcdef";
+        let pending = [pending_path(&settings, id), pending_path(&mcp, id)];
+        write_pending(&pending[0], r#"{"model": "opus"}"#).unwrap();
+        write_pending(&pending[1], r#"{"servers": {}}"#).unwrap();
+        write_journal(
+            temp.path(),
+            id,
+            &[(&settings, &pending[0]), (&mcp, &pending[1])],
+        )
+        .unwrap();
+        // 准备阶段崩溃留下的临时文件
+        let orphan = pending_path(&settings, "fedcba9876543210fedcba9876543210");
+        write_pending(&orphan, "{").unwrap();
+
+        let report = recover_pending_writes(temp.path()).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                promoted: 2,
+                discarded: 1
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(&settings).unwrap(),
+            r#"{"model": "opus"}"#
+        );
+        assert_eq!(fs::read_to_string(&mcp).unwrap(), r#"{"servers": {}}"#);
+        assert!(!orphan.exists());
+        assert_eq!(
+            fs::read_dir(temp.path().join(TRANSACTIONS_DIR))
+                .unwrap()
+                .count(),
+            0
+        );
+
+        let mut batch = ConfigBatch::new(temp.path());
+        batch.stage(&json!({ "a": 1 }), &settings).unwrap();
+        batch.stage(&json!({ "b": 2 }), &mcp).unwrap();
+        assert_eq!(batch.commit().unwrap(), 2);
+        assert_eq!(
+            recover_pending_writes(temp.path()).unwrap(),
+            RecoveryReport::default()
+        );
+    }
+
+    #[test]
+    fn test_recover_pending_writes_outside_base_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join(".claude");
+        let settings = base.join("settings.json");
+        let global = temp.path().join(".claude.json");
+        fs::create_dir_all(&base).unwrap();
+
+        let id = "00112233445566778899aabbccddeeff";
+        let pending = [pending_path(&settings, id), pending_path(&global, id)];
+        write_pending(&pending[0], r#"{"model": "opus"}"#).unwrap();
+        write_pending(&pending[1], r#"{"projects": {}}"#).unwrap();
+        let journal = write_journal(
+            &base,
+            id,
+            &[(&settings, &pending[0]), (&global, &pending[1])],
+        )
+        .unwrap();
+        assert!(journal.starts_with(base.join(TRANSACTIONS_DIR)));
+
+        let report = recover_pending_writes(&base).unwrap();
+        assert_eq!(report.promoted, 2);
+        assert_eq!(fs::read_to_string(&global).unwrap(), r#"{"projects": {}}"#);
+        assert!(!temp.path().join(TRANSACTIONS_DIR).exists());
+    }
+}
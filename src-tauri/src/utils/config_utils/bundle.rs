@@ -0,0 +1,782 @@
+//! 配置包（zip）校验
+//!
+//! 导入配置包会覆盖 `~/.claude` 中的真实文件。导入前先把整个包解压到临时目录，
+//! 逐个检查 JSON 文件，任何一项失败都应中止导入，避免只应用了一半的损坏配置。
+//!
+//! [`export_config_bundle`] 导出的包带有 `manifest.json`，记录每个文件的 SHA-256；
+//! 校验时逐一比对，在机器之间传输时被篡改或截断的包会被拒绝导入。
+//!
+//! [`export_config_with_assets`] 导出单个配置及其引用的脚本等本地文件，
+//! 包内路径改写为相对形式，由 [`import_config_with_assets`] 在另一台机器上还原。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::atomic::atomic_write;
+use super::digest::sha256_hex;
+use super::lint::find_duplicate_keys;
+use super::lock::{lock_config, with_config_dir_lock};
+use super::rate_limit::admit_writes;
+use super::snapshot::config_files;
+use super::{load_json_config, serialize_config};
+
+/// 配置包清单的条目名
+pub const BUNDLE_MANIFEST: &str = "manifest.json";
+
+/// 当前的清单格式版本
+const MANIFEST_VERSION: u32 = 1;
+
+/// 资源文件在配置包中的目录
+pub const BUNDLE_ASSETS_DIR: &str = "assets";
+
+/// 配置包清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub version: u32,
+    /// 包内相对路径 -> SHA-256（十六进制）
+    pub files: BTreeMap<String, String>,
+    /// 配置文件的条目名（仅 [`export_config_with_assets`] 导出的包）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// 配置中引用资源文件的 JSON Pointer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asset_pointers: Vec<String>,
+}
+
+/// 配置包中的单个问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleProblem {
+    /// 包内条目名称（相对路径）
+    pub entry: String,
+    /// 问题描述
+    pub message: String,
+}
+
+/// 配置包校验报告
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleReport {
+    /// 已检查的 JSON 文件（相对路径）
+    pub checked_files: Vec<String>,
+    /// 发现的问题
+    pub problems: Vec<BundleProblem>,
+}
+
+impl BundleReport {
+    /// 没有任何问题时可以安全导入
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// 校验配置包（仅检查 JSON 语法和重复键）
+///
+/// 等价于 `verify_config_bundle_with(in_zip, |_, _| Ok(()))`。
+pub fn verify_config_bundle(in_zip: impl AsRef<Path>) -> Result<BundleReport, String> {
+    verify_config_bundle_with(in_zip, |_, _| Ok(()))
+}
+
+/// 校验配置包，并对每个文件执行额外的校验
+///
+/// # 参数
+/// - `in_zip`: 配置包路径
+/// - `validate`: 额外校验（参数为包内相对路径和解析后的值），返回 `Err` 记为问题
+///
+/// # 返回值
+/// - `Ok(BundleReport)`: 校验报告（包内文件有问题时仍返回 `Ok`，问题记录在报告中）
+/// - `Err(String)`: 包本身无法打开或解压
+///
+/// # 特性
+/// - ✅ 解压到临时目录，不触碰真实配置目录
+/// - ✅ 拒绝试图写出解压目录的条目（路径穿越）
+/// - ✅ 检查 JSON 语法与重复键
+/// - ✅ 包含清单时校验每个文件的 SHA-256，并检查缺失或多出的文件
+pub fn verify_config_bundle_with<F>(
+    in_zip: impl AsRef<Path>,
+    validate: F,
+) -> Result<BundleReport, String>
+where
+    F: Fn(&Path, &Value) -> Result<(), String>,
+{
+    let in_zip = in_zip.as_ref();
+    let file =
+        fs::File::open(in_zip).map_err(|e| format!("Failed to open bundle {:?}: {}", in_zip, e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read bundle {:?}: {}", in_zip, e))?;
+
+    let mut report = BundleReport::default();
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read bundle {:?}: {}", in_zip, e))?;
+        if entry.enclosed_name().is_none() {
+            report.problems.push(BundleProblem {
+                entry: entry.name().to_string(),
+                message: "Entry path escapes the bundle directory".to_string(),
+            });
+        }
+    }
+    if !report.is_ok() {
+        return Ok(report);
+    }
+
+    report
+        .problems
+        .extend(manifest_problems(&mut archive, in_zip)?);
+    if !report.is_ok() {
+        return Ok(report);
+    }
+
+    let temp = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    archive
+        .extract(temp.path())
+        .map_err(|e| format!("Failed to extract bundle {:?}: {}", in_zip, e))?;
+
+    for relative in config_files(temp.path())? {
+        if relative == Path::new(BUNDLE_MANIFEST) {
+            continue;
+        }
+        let name = display_entry(&relative);
+        if let Err(message) = verify_entry(&temp.path().join(&relative), &relative, &validate) {
+            report.problems.push(BundleProblem {
+                entry: name.clone(),
+                message,
+            });
+        }
+        report.checked_files.push(name);
+    }
+
+    Ok(report)
+}
+
+/// 导出配置目录为配置包
+///
+/// # 参数
+/// - `base_dir`: 配置目录（如 `~/.claude`），收集其中的 `.json` 文件
+/// - `out_zip`: 输出的配置包路径
+///
+/// # 返回值
+/// - `Ok(count)`: 导出的配置文件数量（不含清单）
+pub fn export_config_bundle(
+    base_dir: impl AsRef<Path>,
+    out_zip: impl AsRef<Path>,
+) -> Result<usize, String> {
+    let base_dir = base_dir.as_ref();
+    let mut entries = Vec::new();
+    for relative in config_files(base_dir)? {
+        let path = base_dir.join(&relative);
+        let content = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        entries.push((display_entry(&relative), content, None));
+    }
+
+    let count = entries.len();
+    write_bundle(out_zip.as_ref(), entries, None, Vec::new())?;
+    Ok(count)
+}
+
+/// 校验并导入配置包
+///
+/// # 参数
+/// - `in_zip`: 由 [`export_config_bundle`] 导出的配置包
+/// - `base_dir`: 导入的目标配置目录
+///
+/// # 返回值
+/// - `Ok(count)`: 导入的文件数量
+/// - `Err(String)`: 包缺少清单、校验失败（包含失败的文件名）或写入失败；校验失败时不写入任何文件
+///
+/// # 特性
+/// - ✅ 导入期间持有配置目录锁（见 [`with_config_dir_lock`]）
+pub fn import_config_bundle(
+    in_zip: impl AsRef<Path>,
+    base_dir: impl AsRef<Path>,
+) -> Result<usize, String> {
+    let in_zip = in_zip.as_ref();
+    let base_dir = base_dir.as_ref();
+
+    let report = verify_config_bundle(in_zip)?;
+    if let Some(problem) = report.problems.first() {
+        return Err(format!(
+            "Bundle {:?} failed verification: {}: {}",
+            in_zip, problem.entry, problem.message
+        ));
+    }
+
+    let file =
+        fs::File::open(in_zip).map_err(|e| format!("Failed to open bundle {:?}: {}", in_zip, e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read bundle {:?}: {}", in_zip, e))?;
+    let manifest = read_manifest(&mut archive, in_zip)?
+        .ok_or_else(|| format!("Bundle {:?} has no {}", in_zip, BUNDLE_MANIFEST))?;
+
+    with_config_dir_lock(base_dir, || {
+        let targets: Vec<PathBuf> = manifest
+            .files
+            .keys()
+            .map(|name| base_dir.join(name))
+            .collect();
+        admit_writes(targets.iter().map(PathBuf::as_path))?;
+        for name in manifest.files.keys() {
+            let content = read_entry(&mut archive, name, in_zip)?;
+            let target = base_dir.join(name);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    format!("Failed to create config directory {:?}: {}", parent, e)
+                })?;
+            }
+            let _lock = lock_config(&target)?;
+            atomic_write(&target, &content)?;
+        }
+        Ok(manifest.files.len())
+    })
+}
+
+/// 导出配置及其引用的本地文件
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `asset_pointers`: 值为文件路径（字符串或字符串数组）的 JSON Pointer，如 `/statusLine/script`；
+///   不存在的指针被跳过，相对路径相对于配置文件所在目录
+/// - `out_zip`: 输出的配置包路径
+///
+/// # 返回值
+/// - `Ok(count)`: 打包的资源文件数量
+/// - `Err(String)`: 配置不存在、指针的值不是路径、引用的文件不存在或写入失败
+///
+/// # 特性
+/// - ✅ 资源文件存放在包内 `assets/` 下，配置中的路径改写为 `assets/<文件名>`
+/// - ✅ 同一文件被多处引用时只打包一次；不同文件重名时自动加序号
+/// - ✅ 保留资源文件的 Unix 权限（如脚本的可执行位）
+/// - ✅ 清单记录每个文件的 SHA-256，导入前校验
+pub fn export_config_with_assets(
+    config_path: impl AsRef<Path>,
+    asset_pointers: &[&str],
+    out_zip: impl AsRef<Path>,
+) -> Result<usize, String> {
+    let config_path = config_path.as_ref();
+    if !config_path.is_file() {
+        return Err(format!("Config file not found at {:?}", config_path));
+    }
+    let config_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let config_name = config_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("Invalid config path {:?}", config_path))?;
+    let mut config: Value = load_json_config(config_path)?;
+
+    // 源文件 -> 包内条目名
+    let mut assets: BTreeMap<PathBuf, String> = BTreeMap::new();
+    let mut entries = Vec::new();
+    for pointer in asset_pointers {
+        let Some(target) = config.pointer_mut(pointer) else {
+            continue;
+        };
+        let mut references: Vec<&mut String> = match target {
+            Value::String(reference) => vec![reference],
+            Value::Array(items) => items
+                .iter_mut()
+                .map(|item| match item {
+                    Value::String(reference) => Ok(reference),
+                    _ => Err(format!("Asset list at {} must contain only paths", pointer)),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => {
+                return Err(format!(
+                    "Asset at {} must be a path or a list of paths",
+                    pointer
+                ))
+            }
+        };
+
+        for reference in references.iter_mut() {
+            let source = config_dir.join(reference.as_str());
+            let source = fs::canonicalize(&source).map_err(|e| {
+                format!(
+                    "Asset {:?} referenced at {} not found: {}",
+                    source, pointer, e
+                )
+            })?;
+            if !source.is_file() {
+                return Err(format!(
+                    "Asset {:?} referenced at {} is not a file",
+                    source, pointer
+                ));
+            }
+
+            let name = match assets.get(&source) {
+                Some(name) => name.clone(),
+                None => {
+                    let name = asset_entry_name(&source, &assets);
+                    let content = fs::read(&source)
+                        .map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+                    entries.push((name.clone(), content, unix_mode(&source)));
+                    assets.insert(source, name.clone());
+                    name
+                }
+            };
+            **reference = name;
+        }
+    }
+
+    let content = serialize_config(&config)?;
+    entries.push((config_name.clone(), content.into_bytes(), None));
+    let pointers = asset_pointers
+        .iter()
+        .map(|pointer| pointer.to_string())
+        .collect();
+    write_bundle(out_zip.as_ref(), entries, Some(config_name), pointers)?;
+    Ok(assets.len())
+}
+
+/// 导入由 [`export_config_with_assets`] 导出的配置包
+///
+/// # 参数
+/// - `in_zip`: 配置包路径
+/// - `target_dir`: 目标目录；配置写入其中，资源文件写入其中的 `assets/`
+///
+/// # 返回值
+/// - `Ok(PathBuf)`: 导入的配置文件路径；其中的资源路径已改写为导入后的绝对路径
+/// - `Err(String)`: 包校验失败、不是带资源的配置包或写入失败；校验失败时不写入任何文件
+pub fn import_config_with_assets(
+    in_zip: impl AsRef<Path>,
+    target_dir: impl AsRef<Path>,
+) -> Result<PathBuf, String> {
+    let in_zip = in_zip.as_ref();
+    let target_dir = target_dir.as_ref();
+
+    let report = verify_config_bundle(in_zip)?;
+    if let Some(problem) = report.problems.first() {
+        return Err(format!(
+            "Bundle {:?} failed verification: {}: {}",
+            in_zip, problem.entry, problem.message
+        ));
+    }
+
+    let file =
+        fs::File::open(in_zip).map_err(|e| format!("Failed to open bundle {:?}: {}", in_zip, e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read bundle {:?}: {}", in_zip, e))?;
+    let manifest = read_manifest(&mut archive, in_zip)?
+        .ok_or_else(|| format!("Bundle {:?} has no {}", in_zip, BUNDLE_MANIFEST))?;
+    let config_name = manifest
+        .config
+        .clone()
+        .ok_or_else(|| format!("Bundle {:?} does not contain a config with assets", in_zip))?;
+
+    let mut config: Value =
+        serde_json::from_slice(&read_entry(&mut archive, &config_name, in_zip)?)
+            .map_err(|e| format!("Invalid {} in bundle {:?}: {}", config_name, in_zip, e))?;
+    let asset_prefix = format!("{}/", BUNDLE_ASSETS_DIR);
+    for pointer in &manifest.asset_pointers {
+        let references: Vec<&mut Value> = match config.pointer_mut(pointer) {
+            Some(Value::Array(items)) => items.iter_mut().collect(),
+            Some(value) => vec![value],
+            None => continue,
+        };
+        for reference in references {
+            if let Some(name) = reference
+                .as_str()
+                .filter(|name| name.starts_with(&asset_prefix))
+            {
+                *reference = Value::String(target_dir.join(name).to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    with_config_dir_lock(target_dir, || {
+        let targets: Vec<PathBuf> = manifest
+            .files
+            .keys()
+            .map(|name| target_dir.join(name))
+            .collect();
+        admit_writes(targets.iter().map(PathBuf::as_path))?;
+        for name in manifest.files.keys().filter(|name| **name != config_name) {
+            let content = read_entry(&mut archive, name, in_zip)?;
+            let target = target_dir.join(name);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+            }
+            atomic_write(&target, &content)?;
+            let mode = archive
+                .by_name(name)
+                .ok()
+                .and_then(|entry| entry.unix_mode());
+            set_unix_mode(&target, mode)?;
+        }
+
+        let config_path = target_dir.join(&config_name);
+        let _lock = lock_config(&config_path)?;
+        atomic_write(&config_path, serialize_config(&config)?.as_bytes())?;
+        Ok(config_path)
+    })
+}
+
+/// 写出配置包，并为所有条目生成清单
+fn write_bundle(
+    out_zip: &Path,
+    entries: Vec<(String, Vec<u8>, Option<u32>)>,
+    config: Option<String>,
+    asset_pointers: Vec<String>,
+) -> Result<(), String> {
+    let file = fs::File::create(out_zip)
+        .map_err(|e| format!("Failed to create bundle {:?}: {}", out_zip, e))?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    let write_error =
+        |e: &dyn std::fmt::Display| format!("Failed to write bundle {:?}: {}", out_zip, e);
+
+    let mut manifest = BundleManifest {
+        version: MANIFEST_VERSION,
+        files: BTreeMap::new(),
+        config,
+        asset_pointers,
+    };
+    for (name, content, mode) in entries {
+        let options = match mode {
+            Some(mode) => options.unix_permissions(mode),
+            None => options,
+        };
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| write_error(&e))?;
+        writer.write_all(&content).map_err(|e| write_error(&e))?;
+        manifest.files.insert(name, sha256_hex(&content));
+    }
+
+    let manifest = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+    writer
+        .start_file(BUNDLE_MANIFEST, options)
+        .map_err(|e| write_error(&e))?;
+    writer
+        .write_all(manifest.as_bytes())
+        .map_err(|e| write_error(&e))?;
+    writer.finish().map_err(|e| write_error(&e))?;
+    Ok(())
+}
+
+/// `assets/<文件名>`，与已有条目重名时为 `assets/<序号>-<文件名>`
+fn asset_entry_name(source: &Path, assets: &BTreeMap<PathBuf, String>) -> String {
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "asset".to_string());
+    let taken = |name: &String| assets.values().any(|existing| existing == name);
+
+    let mut name = format!("{}/{}", BUNDLE_ASSETS_DIR, file_name);
+    let mut counter = 2;
+    while taken(&name) {
+        name = format!("{}/{}-{}", BUNDLE_ASSETS_DIR, counter, file_name);
+        counter += 1;
+    }
+    name
+}
+
+#[cfg(unix)]
+fn unix_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .ok()
+        .map(|metadata| metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_unix_mode(path: &Path, mode: Option<u32>) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let Some(mode) = mode else {
+        return Ok(());
+    };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+        .map_err(|e| format!("Failed to set permissions on {:?}: {}", path, e))
+}
+
+#[cfg(not(unix))]
+fn set_unix_mode(_path: &Path, _mode: Option<u32>) -> Result<(), String> {
+    Ok(())
+}
+
+fn read_manifest<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    in_zip: &Path,
+) -> Result<Option<BundleManifest>, String> {
+    if archive.index_for_name(BUNDLE_MANIFEST).is_none() {
+        return Ok(None);
+    }
+    let content = read_entry(archive, BUNDLE_MANIFEST, in_zip)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {} in bundle {:?}: {}", BUNDLE_MANIFEST, in_zip, e))
+}
+
+/// 按清单校验包内文件；包内没有清单时不做检查
+fn manifest_problems<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    in_zip: &Path,
+) -> Result<Vec<BundleProblem>, String> {
+    let Some(manifest) = read_manifest(archive, in_zip)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut problems = Vec::new();
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| *name != BUNDLE_MANIFEST && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    for name in &names {
+        let message = match manifest.files.get(name) {
+            None => "File is not listed in the manifest".to_string(),
+            Some(expected) => {
+                let actual = sha256_hex(&read_entry(archive, name, in_zip)?);
+                if actual == *expected {
+                    continue;
+                }
+                format!("SHA-256 mismatch (expected {}, found {})", expected, actual)
+            }
+        };
+        problems.push(BundleProblem {
+            entry: name.clone(),
+            message,
+        });
+    }
+    for name in manifest.files.keys() {
+        if !names.contains(name) {
+            problems.push(BundleProblem {
+                entry: name.clone(),
+                message: "File listed in the manifest is missing from the bundle".to_string(),
+            });
+        }
+    }
+
+    problems.sort_by(|a, b| a.entry.cmp(&b.entry));
+    Ok(problems)
+}
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+    in_zip: &Path,
+) -> Result<Vec<u8>, String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Failed to read {} from bundle {:?}: {}", name, in_zip, e))?;
+    let mut content = Vec::new();
+    entry
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read {} from bundle {:?}: {}", name, in_zip, e))?;
+    Ok(content)
+}
+
+fn verify_entry<F>(path: &Path, relative: &Path, validate: &F) -> Result<(), String>
+where
+    F: Fn(&Path, &Value) -> Result<(), String>,
+{
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let value: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let duplicates = find_duplicate_keys(&content)?;
+    if let Some(first) = duplicates.first() {
+        return Err(format!(
+            "Duplicate key {} (lines {:?})",
+            first.pointer, first.lines
+        ));
+    }
+
+    validate(relative, &value)
+}
+
+fn display_entry(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 供测试使用：由 `(条目名, 内容)` 写出一个 zip 包
+    fn write_test_bundle(path: &Path, entries: &[(&str, &str)]) -> std::path::PathBuf {
+        use std::io::Write;
+
+        let file = fs::File::create(path).unwrap();
+        let mut writer = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn test_verify_config_bundle() {
+        let temp = tempfile::tempdir().unwrap();
+        let good = write_test_bundle(
+            &temp.path().join("good.zip"),
+            &[
+                ("settings.json", r#"{"model": "a"}"#),
+                ("profiles/work/settings.json", "{}"),
+            ],
+        );
+        let report = verify_config_bundle(&good).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(
+            report.checked_files,
+            vec!["profiles/work/settings.json", "settings.json"]
+        );
+
+        let bad = write_test_bundle(
+            &temp.path().join("bad.zip"),
+            &[
+                ("settings.json", r#"{"model": "a", "model": "b"}"#),
+                ("broken.json", "{"),
+            ],
+        );
+        let report = verify_config_bundle(&bad).unwrap();
+        let entries: Vec<_> = report.problems.iter().map(|p| p.entry.as_str()).collect();
+        assert_eq!(entries, vec!["broken.json", "settings.json"]);
+    }
+
+    #[test]
+    fn test_verify_config_bundle_rejects_traversal_and_custom_failures() {
+        let temp = tempfile::tempdir().unwrap();
+        let escaping =
+            write_test_bundle(&temp.path().join("escape.zip"), &[("../evil.json", "{}")]);
+        let report = verify_config_bundle(&escaping).unwrap();
+        assert_eq!(report.problems[0].entry, "../evil.json");
+        assert!(!temp.path().parent().unwrap().join("evil.json").exists());
+
+        let bundle = write_test_bundle(&temp.path().join("b.zip"), &[("settings.json", "[]")]);
+        let report = verify_config_bundle_with(&bundle, |_, value| {
+            value
+                .is_object()
+                .then_some(())
+                .ok_or_else(|| "expected an object".to_string())
+        })
+        .unwrap();
+        assert_eq!(report.problems[0].message, "expected an object");
+    }
+
+    #[test]
+    fn test_export_and_import_config_bundle() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("source");
+        fs::create_dir_all(source.join("agents")).unwrap();
+        fs::write(source.join("settings.json"), r#"{"model": "opus"}"#).unwrap();
+        fs::write(source.join("agents/reviewer.json"), "{}").unwrap();
+
+        let bundle = temp.path().join("export.zip");
+        assert_eq!(export_config_bundle(&source, &bundle).unwrap(), 2);
+        let report = verify_config_bundle(&bundle).unwrap();
+        assert!(report.is_ok(), "{:?}", report.problems);
+        assert_eq!(
+            report.checked_files,
+            vec!["agents/reviewer.json", "settings.json"]
+        );
+
+        let target = temp.path().join("target");
+        assert_eq!(import_config_bundle(&bundle, &target).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(target.join("settings.json")).unwrap(),
+            r#"{"model": "opus"}"#
+        );
+    }
+
+    #[test]
+    fn test_import_config_bundle_rejects_tampered_bundle() {
+        let temp = tempfile::tempdir().unwrap();
+        let manifest = serde_json::json!({
+            "version": 1,
+            "files": {
+                "settings.json": sha256_hex(br#"{"model": "opus"}"#),
+                "mcp.json": sha256_hex(b"{}")
+            }
+        })
+        .to_string();
+        let tampered = write_test_bundle(
+            &temp.path().join("tampered.zip"),
+            &[
+                ("settings.json", r#"{"model": "evil"}"#),
+                (BUNDLE_MANIFEST, &manifest),
+            ],
+        );
+
+        let report = verify_config_bundle(&tampered).unwrap();
+        let entries: Vec<_> = report.problems.iter().map(|p| p.entry.as_str()).collect();
+        assert_eq!(entries, vec!["mcp.json", "settings.json"]);
+        assert!(report.problems[1].message.starts_with("SHA-256 mismatch"));
+
+        let target = temp.path().join("target");
+        let err = import_config_bundle(&tampered, &target).unwrap_err();
+        assert!(err.contains("mcp.json"), "{}", err);
+        assert!(!target.join("settings.json").exists());
+
+        let unsigned = write_test_bundle(&temp.path().join("plain.zip"), &[("a.json", "{}")]);
+        let err = import_config_bundle(&unsigned, &target).unwrap_err();
+        assert!(err.contains("has no manifest.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_export_and_import_config_with_assets() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("source");
+        fs::create_dir_all(source.join("hooks")).unwrap();
+        fs::create_dir_all(source.join("other")).unwrap();
+        fs::write(source.join("hooks/lint.sh"), "#!/bin/sh\necho lint\n").unwrap();
+        fs::write(source.join("other/lint.sh"), "#!/bin/sh\necho other\n").unwrap();
+        fs::write(source.join("status.sh"), "#!/bin/sh\n").unwrap();
+        let config = source.join("settings.json");
+        fs::write(
+            &config,
+            r#"{"statusLine": {"script": "status.sh"}, "hooks": ["hooks/lint.sh", "other/lint.sh", "./status.sh"], "model": "opus"}"#,
+        )
+        .unwrap();
+
+        let bundle = temp.path().join("setup.zip");
+        let pointers = ["/statusLine/script", "/hooks", "/missing"];
+        assert_eq!(
+            export_config_with_assets(&config, &pointers, &bundle).unwrap(),
+            3
+        );
+        assert!(verify_config_bundle(&bundle).unwrap().is_ok());
+
+        let target = temp.path().join("target");
+        let imported = import_config_with_assets(&bundle, &target).unwrap();
+        let value: Value = load_json_config(&imported).unwrap();
+        let asset = |name: &str| target.join(name).to_string_lossy().into_owned();
+        assert_eq!(value["statusLine"]["script"], asset("assets/status.sh"));
+        assert_eq!(
+            value["hooks"],
+            serde_json::json!([
+                asset("assets/lint.sh"),
+                asset("assets/2-lint.sh"),
+                asset("assets/status.sh")
+            ])
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("assets/2-lint.sh")).unwrap(),
+            "#!/bin/sh\necho other\n"
+        );
+
+        fs::write(&config, r#"{"hooks": ["hooks/missing.sh"]}"#).unwrap();
+        let err = export_config_with_assets(&config, &pointers, &bundle).unwrap_err();
+        assert!(err.contains("not found"), "{}", err);
+    }
+}
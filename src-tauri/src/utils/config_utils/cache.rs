@@ -0,0 +1,210 @@
+//! 有容量上限的配置缓存
+//!
+//! 扫描数百个项目配置时，不加限制的缓存会让所有配置常驻内存。
+//! [`CachedConfigStore`] 按条目数和近似字节数（文件大小）限制容量，超出时淘汰最久未使用的条目；
+//! 命中时仍会比较文件的修改时间和大小，被修改或被淘汰后重新加载的条目都是最新内容。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde_json::Value;
+
+use super::load_json_config;
+use super::paths::canonical_config_path;
+
+/// 缓存容量上限；都为 `None` 时不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// 最多缓存的配置数
+    pub max_entries: Option<usize>,
+    /// 缓存配置的文件总大小上限
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    value: Arc<Value>,
+    stamp: (SystemTime, u64),
+    last_used: u64,
+}
+
+/// 按 LRU 淘汰的配置缓存
+///
+/// 不是线程安全的，多线程共享时由调用方加锁（如 `Mutex<CachedConfigStore>`）
+#[derive(Debug, Default)]
+pub struct CachedConfigStore {
+    limits: CacheLimits,
+    entries: HashMap<PathBuf, CacheEntry>,
+    bytes: u64,
+    clock: u64,
+}
+
+impl CachedConfigStore {
+    pub fn new(limits: CacheLimits) -> Self {
+        CachedConfigStore {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// 读取配置，缓存有效时不访问文件内容
+    ///
+    /// # 返回值
+    /// - `Ok(value)`: 配置内容；文件不存在时为 `null`。不存在的文件和单个超出字节上限的配置不缓存
+    /// - `Err(String)`: 读取或解析失败
+    pub fn get(&mut self, config_path: impl AsRef<Path>) -> Result<Arc<Value>, String> {
+        let path = canonical_config_path(config_path.as_ref());
+        self.clock += 1;
+
+        let Some(stamp) = file_stamp(&path) else {
+            self.invalidate(&path);
+            return Ok(Arc::new(Value::Null));
+        };
+        if let Some(entry) = self.entries.get_mut(&path) {
+            if entry.stamp == stamp {
+                entry.last_used = self.clock;
+                return Ok(Arc::clone(&entry.value));
+            }
+        }
+
+        self.invalidate(&path);
+        let value = Arc::new(load_json_config::<Value>(&path)?);
+        if self.limits.max_bytes.is_some_and(|max| stamp.1 > max) {
+            return Ok(value);
+        }
+        self.bytes += stamp.1;
+        self.entries.insert(
+            path,
+            CacheEntry {
+                value: Arc::clone(&value),
+                stamp,
+                last_used: self.clock,
+            },
+        );
+        self.evict();
+        Ok(value)
+    }
+
+    /// 移除某个配置的缓存
+    pub fn invalidate(&mut self, config_path: impl AsRef<Path>) {
+        let path = canonical_config_path(config_path.as_ref());
+        if let Some(entry) = self.entries.remove(&path) {
+            self.bytes -= entry.stamp.1;
+        }
+    }
+
+    /// 清空缓存
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    /// 缓存的配置数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 缓存配置的文件总大小
+    pub fn cached_bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// 淘汰最久未使用的条目直到不超过上限
+    fn evict(&mut self) {
+        let over_limit = |store: &Self| {
+            store
+                .limits
+                .max_entries
+                .is_some_and(|max| store.entries.len() > max)
+                || store.limits.max_bytes.is_some_and(|max| store.bytes > max)
+        };
+        while over_limit(self) {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            log::debug!("Evicting cached config {:?}", oldest);
+            self.invalidate(&oldest);
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_config_store_evicts_least_recently_used() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = temp.path().join(format!("project-{}.json", i));
+                fs::write(&path, format!(r#"{{"id": {}}}"#, i)).unwrap();
+                path
+            })
+            .collect();
+
+        let mut store = CachedConfigStore::new(CacheLimits {
+            max_entries: Some(2),
+            max_bytes: None,
+        });
+        store.get(&paths[0]).unwrap();
+        store.get(&paths[1]).unwrap();
+        store.get(&paths[0]).unwrap();
+        store.get(&paths[2]).unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(!store
+            .entries
+            .contains_key(&canonical_config_path(&paths[1])));
+
+        // 命中时检查文件是否被修改
+        fs::write(&paths[0], r#"{"id": 100}"#).unwrap();
+        assert_eq!(store.get(&paths[0]).unwrap()["id"], 100);
+        assert_eq!(store.get(&paths[1]).unwrap()["id"], 1);
+        let expected = r#"{"id": 100}"#.len() + r#"{"id": 1}"#.len();
+        assert_eq!(store.cached_bytes(), expected as u64);
+
+        store.clear();
+        assert!(store.is_empty());
+        assert_eq!(store.cached_bytes(), 0);
+    }
+
+    #[test]
+    fn test_cached_config_store_byte_budget() {
+        let temp = tempfile::tempdir().unwrap();
+        let small = temp.path().join("small.json");
+        let large = temp.path().join("large.json");
+        fs::write(&small, r#"{"a": 1}"#).unwrap();
+        fs::write(&large, format!(r#"{{"a": "{}"}}"#, "x".repeat(100))).unwrap();
+
+        let mut store = CachedConfigStore::new(CacheLimits {
+            max_entries: None,
+            max_bytes: Some(50),
+        });
+        store.get(&small).unwrap();
+        assert_eq!(store.get(&large).unwrap()["a"].as_str().unwrap().len(), 100);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.cached_bytes(), r#"{"a": 1}"#.len() as u64);
+
+        assert!(store
+            .get(temp.path().join("missing.json"))
+            .unwrap()
+            .is_null());
+    }
+}
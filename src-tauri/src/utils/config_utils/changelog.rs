@@ -0,0 +1,179 @@
+//! 面向用户的配置变更说明
+//!
+//! 在 [`diff_json_configs`] 的基础上按已知的配置结构生成可读的句子，
+//! 用于发布说明、审计，以及向用户展示自动迁移或导入具体修改了什么。
+
+use serde_json::Value;
+
+use super::diff::{diff_json_configs, ConfigChange, ConfigChangeKind};
+use super::pointer::split_pointer;
+
+/// 按元素增减描述的工具列表：`(JSON Pointer, 元素名称)`
+const TOOL_LISTS: &[(&str, &str)] = &[
+    ("/permissions/allow", "allowed tool"),
+    ("/permissions/deny", "denied tool"),
+];
+
+/// 名称包含这些片段的键不显示值
+const SECRET_MARKERS: &[&str] = &["TOKEN", "KEY", "SECRET", "PASSWORD"];
+
+/// 生成两个配置版本之间的变更说明
+///
+/// # 返回值
+/// 每项变更一句话，如：
+/// - `Changed model from sonnet to opus`
+/// - `Added MCP server 'github'`
+/// - `Removed 2 allowed tools`
+///
+/// # 特性
+/// - ✅ MCP 服务器按名称描述，同一服务器的多处修改合并为一句
+/// - ✅ 工具列表按增减的元素数量描述
+/// - ✅ 令牌、密钥等敏感设置只说明发生了变化，不显示值
+pub fn changelog_between(old: &Value, new: &Value) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for (pointer, noun) in TOOL_LISTS {
+        let old_items = string_items(old.pointer(pointer));
+        let new_items = string_items(new.pointer(pointer));
+        let added = new_items.iter().filter(|item| !old_items.contains(item));
+        let removed = old_items.iter().filter(|item| !new_items.contains(item));
+        if let Some(line) = count_line("Added", added.count(), noun) {
+            lines.push(line);
+        }
+        if let Some(line) = count_line("Removed", removed.count(), noun) {
+            lines.push(line);
+        }
+    }
+
+    for change in diff_json_configs(old, new) {
+        if TOOL_LISTS
+            .iter()
+            .any(|(pointer, _)| *pointer == change.pointer)
+        {
+            continue;
+        }
+        let Ok(tokens) = split_pointer(&change.pointer) else {
+            continue;
+        };
+
+        let line = match tokens.as_slice() {
+            [root] if root == "mcpServers" => {
+                // 整个 mcpServers 对象被新增或删除
+                let (verb, servers) = match &change.kind {
+                    ConfigChangeKind::Removed => ("Removed", change.old_value.as_ref()),
+                    _ => ("Added", change.new_value.as_ref()),
+                };
+                if let Some(Value::Object(servers)) = servers {
+                    lines.extend(
+                        servers
+                            .keys()
+                            .map(|name| format!("{} MCP server '{}'", verb, name)),
+                    );
+                    continue;
+                }
+                generic_line(&tokens, &change)
+            }
+            [root, name] if root == "mcpServers" => match change.kind {
+                ConfigChangeKind::Added => format!("Added MCP server '{}'", name),
+                ConfigChangeKind::Removed => format!("Removed MCP server '{}'", name),
+                ConfigChangeKind::Changed => format!("Updated MCP server '{}'", name),
+            },
+            [root, name, ..] if root == "mcpServers" => format!("Updated MCP server '{}'", name),
+            _ => generic_line(&tokens, &change),
+        };
+
+        if lines.last() != Some(&line) {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn generic_line(tokens: &[String], change: &ConfigChange) -> String {
+    let label = tokens.join(".");
+    let secret = tokens.last().is_some_and(|key| {
+        let key = key.to_ascii_uppercase();
+        SECRET_MARKERS.iter().any(|marker| key.contains(marker))
+    });
+    let show = |value: &Option<Value>| value.as_ref().map(display_value).unwrap_or_default();
+
+    match change.kind {
+        ConfigChangeKind::Added if secret => format!("Set {} (value hidden)", label),
+        ConfigChangeKind::Changed if secret => format!("Changed {} (value hidden)", label),
+        ConfigChangeKind::Added => format!("Set {} to {}", label, show(&change.new_value)),
+        ConfigChangeKind::Removed => format!("Removed {}", label),
+        ConfigChangeKind::Changed => format!(
+            "Changed {} from {} to {}",
+            label,
+            show(&change.old_value),
+            show(&change.new_value)
+        ),
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn string_items(value: Option<&Value>) -> Vec<&str> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn count_line(verb: &str, count: usize, noun: &str) -> Option<String> {
+    match count {
+        0 => None,
+        1 => Some(format!("{} 1 {}", verb, noun)),
+        _ => Some(format!("{} {} {}s", verb, count, noun)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_changelog_between() {
+        let old = json!({
+            "model": "sonnet",
+            "permissions": { "allow": ["Read", "Edit", "Bash"], "deny": [] },
+            "mcpServers": {
+                "fs": { "command": "fs-server" },
+                "legacy": { "command": "old" }
+            },
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-old", "HTTPS_PROXY": "http://proxy" }
+        });
+        let new = json!({
+            "model": "opus",
+            "permissions": { "allow": ["Read"], "deny": ["WebFetch"] },
+            "mcpServers": {
+                "fs": { "command": "fs-server", "args": ["--root", "/"], "env": { "A": "1" } },
+                "github": { "command": "gh-mcp" }
+            },
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-new" },
+            "theme": "dark"
+        });
+
+        assert_eq!(
+            changelog_between(&old, &new),
+            vec![
+                "Removed 2 allowed tools",
+                "Added 1 denied tool",
+                "Changed env.ANTHROPIC_AUTH_TOKEN (value hidden)",
+                "Removed env.HTTPS_PROXY",
+                "Updated MCP server 'fs'",
+                "Added MCP server 'github'",
+                "Removed MCP server 'legacy'",
+                "Changed model from sonnet to opus",
+                "Set theme to dark",
+            ]
+        );
+        assert!(changelog_between(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! 配置与应用版本的兼容性检查
+//!
+//! 新版应用写入的配置可能包含旧版不认识的键，被旧版加载后会被静默忽略甚至丢失。
+//! 配置中可以记录两个标记：
+//! - `_writtenByVersion`: 最后写入该配置的应用版本，旧版加载时记录警告
+//! - `_minAppVersion`: 加载该配置所需的最低应用版本，旧版加载时直接报错
+//!
+//! Claude CLI 自己的配置文件不应写入额外的键，因此标记只在显式使用
+//! [`save_json_config_versioned`] / [`load_json_config_versioned`] 时读写。
+
+use std::cmp::Ordering;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{load_json_config, save_json_config};
+
+/// 当前应用版本
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 最后写入配置的应用版本
+pub const WRITTEN_BY_VERSION_KEY: &str = "_writtenByVersion";
+
+/// 加载配置所需的最低应用版本
+pub const MIN_APP_VERSION_KEY: &str = "_minAppVersion";
+
+/// 检查配置是否能被指定版本的应用安全加载
+///
+/// # 返回值
+/// - `Ok(None)`: 兼容
+/// - `Ok(Some(warning))`: 配置由更新的版本写入，可能包含当前版本不认识的设置
+/// - `Err(String)`: 当前版本低于配置要求的最低版本
+pub fn check_config_compatibility(
+    value: &Value,
+    app_version: &str,
+) -> Result<Option<String>, String> {
+    if let Some(required) = value.get(MIN_APP_VERSION_KEY).and_then(Value::as_str) {
+        if compare_versions(app_version, required) == Ordering::Less {
+            return Err(format!(
+                "This config requires app version {} or newer (running {})",
+                required, app_version
+            ));
+        }
+    }
+
+    let warning = value
+        .get(WRITTEN_BY_VERSION_KEY)
+        .and_then(Value::as_str)
+        .filter(|written_by| compare_versions(app_version, written_by) == Ordering::Less)
+        .map(|written_by| {
+            format!(
+                "This config was written by app version {} (running {}); some settings may be ignored",
+                written_by, app_version
+            )
+        });
+    Ok(warning)
+}
+
+/// 加载配置并检查版本兼容性
+///
+/// 由更新版本写入时记录警告并继续加载；低于最低版本要求时报错。
+/// 版本标记在反序列化前移除，不会出现在 `T` 中
+pub fn load_json_config_versioned<T>(config_path: impl AsRef<Path>) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let mut raw: Value = load_json_config(path)?;
+    if raw.is_null() {
+        return Ok(T::default());
+    }
+
+    match check_config_compatibility(&raw, APP_VERSION) {
+        Ok(Some(warning)) => log::warn!("{:?}: {}", path, warning),
+        Ok(None) => {}
+        Err(e) => return Err(format!("Cannot load config {:?}: {}", path, e)),
+    }
+
+    if let Some(map) = raw.as_object_mut() {
+        map.remove(WRITTEN_BY_VERSION_KEY);
+        map.remove(MIN_APP_VERSION_KEY);
+    }
+    serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+/// 保存配置并记录写入的应用版本
+///
+/// 写入 `_writtenByVersion`；文件中已有的 `_minAppVersion` 会被保留
+pub fn save_json_config_versioned<T>(
+    config: &T,
+    config_path: impl AsRef<Path>,
+) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let mut value =
+        serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let Some(map) = value.as_object_mut() else {
+        return Err(format!(
+            "Cannot stamp app version into {:?}: config is not a JSON object",
+            path
+        ));
+    };
+
+    let existing: Value = load_json_config(path).unwrap_or_default();
+    if let Some(required) = existing.get(MIN_APP_VERSION_KEY) {
+        map.entry(MIN_APP_VERSION_KEY)
+            .or_insert_with(|| required.clone());
+    }
+    map.insert(
+        WRITTEN_BY_VERSION_KEY.to_string(),
+        Value::String(APP_VERSION.to_string()),
+    );
+    save_json_config(&value, path)
+}
+
+/// 按数字逐段比较版本号（忽略 `-beta.1` 等预发布后缀）
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_config_compatibility() {
+        assert_eq!(compare_versions("5.10.0", "5.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v5.9", "5.9.0-beta.1"), Ordering::Equal);
+
+        let newer = json!({ "_writtenByVersion": "5.30.0", "_minAppVersion": "5.20" });
+        let warning = check_config_compatibility(&newer, "5.28.5")
+            .unwrap()
+            .unwrap();
+        assert!(warning.contains("5.30.0"), "{}", warning);
+        assert_eq!(check_config_compatibility(&newer, "5.30.1").unwrap(), None);
+        let err = check_config_compatibility(&newer, "5.19.9").unwrap_err();
+        assert!(err.contains("requires app version 5.20"), "{}", err);
+    }
+
+    #[test]
+    fn test_versioned_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("workbench.json");
+        std::fs::write(&path, r#"{"theme": "dark", "_minAppVersion": "0.0.1"}"#).unwrap();
+
+        save_json_config_versioned(&json!({ "theme": "light" }), &path).unwrap();
+        let raw: Value = load_json_config(&path).unwrap();
+        assert_eq!(raw[WRITTEN_BY_VERSION_KEY], APP_VERSION);
+        assert_eq!(raw[MIN_APP_VERSION_KEY], "0.0.1");
+
+        let loaded: Value = load_json_config_versioned(&path).unwrap();
+        assert_eq!(loaded, json!({ "theme": "light" }));
+
+        std::fs::write(&path, r#"{"_minAppVersion": "99999.0"}"#).unwrap();
+        let err = load_json_config_versioned::<Value>(&path).unwrap_err();
+        assert!(err.starts_with("Cannot load config"), "{}", err);
+    }
+}
@@ -0,0 +1,89 @@
+//! 配置文件流式复制
+//!
+//! 会话索引等文件可能有数 MB，快照和备份不应整体读入内存再写出。
+//! 这里用带缓冲的流式复制，并保留权限和修改时间，使副本与原文件在元数据上一致。
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// 复制缓冲区大小
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 流式复制配置文件
+///
+/// # 参数
+/// - `from`: 源文件
+/// - `to`: 目标文件（已存在时覆盖，父目录需已存在）
+///
+/// # 返回值
+/// - `Ok(u64)`: 复制的字节数
+/// - `Err(String)`: 读取、写入或设置元数据失败
+///
+/// # 特性
+/// - ✅ 内存占用与文件大小无关
+/// - ✅ 保留权限和修改时间
+pub fn copy_config_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<u64, String> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    let err = |e: io::Error| format!("Failed to copy {:?} to {:?}: {}", from, to, e);
+
+    let source = File::open(from).map_err(err)?;
+    let metadata = source.metadata().map_err(err)?;
+
+    let target = File::create(to).map_err(err)?;
+    let mut reader = BufReader::with_capacity(COPY_BUFFER_SIZE, source);
+    let mut writer = BufWriter::with_capacity(COPY_BUFFER_SIZE, target);
+    let bytes = io::copy(&mut reader, &mut writer).map_err(err)?;
+    writer.flush().map_err(err)?;
+
+    let target = writer.into_inner().map_err(|e| err(e.into_error()))?;
+    if let Ok(modified) = metadata.modified() {
+        target.set_modified(modified).map_err(err)?;
+    }
+    drop(target);
+    fs::set_permissions(to, metadata.permissions()).map_err(err)?;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_copy_config_file_preserves_content_and_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let from = temp.path().join("sessions.json");
+        let to = temp.path().join("copy.json");
+
+        let content = "x".repeat(3 * COPY_BUFFER_SIZE + 17);
+        fs::write(&from, &content).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(&from)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let mut permissions = fs::metadata(&from).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&from, permissions).unwrap();
+
+        let bytes = copy_config_file(&from, &to).unwrap();
+        assert_eq!(bytes, content.len() as u64);
+        assert_eq!(fs::read_to_string(&to).unwrap(), content);
+
+        let copied = fs::metadata(&to).unwrap();
+        assert_eq!(copied.modified().unwrap(), mtime);
+        assert!(copied.permissions().readonly());
+
+        // 清理只读文件，避免在部分平台上 tempdir 删除失败
+        for path in [&from, &to] {
+            let mut permissions = fs::metadata(path).unwrap().permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            fs::set_permissions(path, permissions).unwrap();
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! 保存前去除数组中的重复元素
+//!
+//! 重复的界面操作有时会向 `permissions.allow`、`recentProjects` 等数组追加重复项。
+//! 在保存时统一去重，调用方无需各自处理。
+//! [`concat_list_configs`] 则把多个文件各自贡献的列表（允许的工具、MCP 服务器）合并为一个去重后的列表。
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{load_json_config, save_json_config};
+
+/// 去除指定数组中的重复元素
+///
+/// 保留每个元素第一次出现的位置；对象元素按结构比较（与键的顺序无关）。
+/// 不存在或不是数组的路径会被跳过
+///
+/// # 参数
+/// - `value`: 配置值（原地修改）
+/// - `pointers`: 数组的 JSON Pointer 列表
+///
+/// # 返回值
+/// 删除的元素总数
+pub fn dedupe_config_arrays(value: &mut Value, pointers: &[&str]) -> usize {
+    let mut removed = 0;
+    for pointer in pointers {
+        let Some(Value::Array(items)) = value.pointer_mut(pointer) else {
+            continue;
+        };
+        let mut unique: Vec<Value> = Vec::with_capacity(items.len());
+        for item in items.drain(..) {
+            if unique.contains(&item) {
+                removed += 1;
+            } else {
+                unique.push(item);
+            }
+        }
+        *items = unique;
+    }
+    removed
+}
+
+/// 去除指定数组中的重复元素后保存配置
+///
+/// # 参数
+/// - `config`: 配置对象
+/// - `config_path`: 配置文件路径
+/// - `dedupe_arrays`: 需要去重的数组的 JSON Pointer 列表
+pub fn save_json_config_deduped<T>(
+    config: &T,
+    config_path: impl AsRef<Path>,
+    dedupe_arrays: &[&str],
+) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let mut value =
+        serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    let removed = dedupe_config_arrays(&mut value, dedupe_arrays);
+    if removed > 0 {
+        log::debug!(
+            "Removed {} duplicate array entries from {:?}",
+            removed,
+            path
+        );
+    }
+    save_json_config(&value, path)
+}
+
+/// 拼接多个配置文件中同一位置的数组并去重
+///
+/// # 参数
+/// - `config_paths`: 配置文件路径，按顺序拼接；不存在的文件和不含该数组的文件被跳过
+/// - `pointer`: 数组的 JSON Pointer，如 `/permissions/allow`
+/// - `dedupe_key`: 对象元素的标识键（如 `"name"`）；为 `None` 或元素缺少该键时按结构比较
+///
+/// # 返回值
+/// - `Ok(items)`: 拼接后的列表，标识相同的元素只保留第一次出现的那个
+/// - `Err(String)`: 某个文件读取失败，或该位置存在但不是数组（错误中包含文件和指针）
+pub fn concat_list_configs<P>(
+    config_paths: &[P],
+    pointer: &str,
+    dedupe_key: Option<&str>,
+) -> Result<Vec<Value>, String>
+where
+    P: AsRef<Path>,
+{
+    let mut items: Vec<Value> = Vec::new();
+    for path in config_paths {
+        let path = path.as_ref();
+        let mut config: Value = load_json_config(path)?;
+        let list = match config.pointer_mut(pointer).map(Value::take) {
+            None | Some(Value::Null) => continue,
+            Some(Value::Array(list)) => list,
+            Some(_) => return Err(format!("{} in {:?} is not an array", pointer, path)),
+        };
+
+        for item in list {
+            let keyed = dedupe_key.and_then(|key| Some((key, item.get(key)?)));
+            let is_duplicate = items.iter().any(|existing| match keyed {
+                Some((key, id)) => existing.get(key) == Some(id),
+                None => *existing == item,
+            });
+            if is_duplicate {
+                log::debug!("Skipping duplicate entry at {} in {:?}", pointer, path);
+            } else {
+                items.push(item);
+            }
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_dedupe_config_arrays() {
+        let mut value: Value = serde_json::from_str(
+            r#"{
+                "permissions": { "allow": ["Read", "Bash", "Read", "Edit", "Bash"] },
+                "recentProjects": [
+                    { "path": "/a", "pinned": true },
+                    { "path": "/b" },
+                    { "pinned": true, "path": "/a" }
+                ],
+                "tags": ["x", "x"],
+                "model": "opus"
+            }"#,
+        )
+        .unwrap();
+
+        let removed = dedupe_config_arrays(
+            &mut value,
+            &[
+                "/permissions/allow",
+                "/recentProjects",
+                "/model",
+                "/missing",
+            ],
+        );
+        assert_eq!(removed, 3);
+        assert_eq!(
+            value,
+            json!({
+                "permissions": { "allow": ["Read", "Bash", "Edit"] },
+                "recentProjects": [{ "path": "/a", "pinned": true }, { "path": "/b" }],
+                "tags": ["x", "x"],
+                "model": "opus"
+            })
+        );
+    }
+
+    #[test]
+    fn test_save_json_config_deduped() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        let config = json!({ "permissions": { "allow": ["Read", "Read"] } });
+        save_json_config_deduped(&config, &path, &["/permissions/allow"]).unwrap();
+
+        let saved: Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(saved, json!({ "permissions": { "allow": ["Read"] } }));
+    }
+
+    #[test]
+    fn test_concat_list_configs() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("base.json");
+        let team = temp.path().join("team.json");
+        let empty = temp.path().join("empty.json");
+        std::fs::write(
+            &base,
+            r#"{"mcpServers": [{"name": "git", "command": "git-mcp"}, {"command": "anon"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &team,
+            r#"{"mcpServers": [{"name": "git", "command": "other"}, {"name": "db"}, {"command": "anon"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(&empty, r#"{"model": "opus"}"#).unwrap();
+        let paths = [base.clone(), empty, team, temp.path().join("missing.json")];
+
+        let servers = concat_list_configs(&paths, "/mcpServers", Some("name")).unwrap();
+        assert_eq!(
+            servers,
+            vec![
+                json!({ "name": "git", "command": "git-mcp" }),
+                json!({ "command": "anon" }),
+                json!({ "name": "db" }),
+            ]
+        );
+        assert_eq!(
+            concat_list_configs(&paths, "/mcpServers", None)
+                .unwrap()
+                .len(),
+            4
+        );
+
+        std::fs::write(&base, r#"{"mcpServers": {"git": {}}}"#).unwrap();
+        let err = concat_list_configs(&paths, "/mcpServers", Some("name")).unwrap_err();
+        assert!(err.contains("not an array"), "{}", err);
+    }
+}
@@ -0,0 +1,130 @@
+//! 多来源的默认配置
+//!
+//! 核心模块和各插件各自提供自己那部分配置的默认值，而不是集中在一个庞大的 `T::default()` 中。
+//! [`load_json_config_with_defaults`] 把所有提供者的默认值按优先级深度合并，
+//! 再以配置文件中的值覆盖。
+
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::load_json_config;
+use super::merge::deep_merge;
+
+/// 默认配置的提供者
+pub trait DefaultProvider {
+    /// 提供者名称（如 `"core"`、`"plugin:git"`），用于日志和错误信息
+    fn name(&self) -> &str;
+
+    /// 该提供者负责的默认值，为配置对象的一部分（如 `{ "git": { "autoFetch": true } }`）
+    fn defaults(&self) -> Value;
+}
+
+/// 按顺序深度合并所有提供者的默认值
+///
+/// # 参数
+/// - `providers`: 按优先级从低到高排列（与 [`merge_with_provenance`] 的层顺序一致），
+///   后面的提供者覆盖前面提供者的同名字段
+///
+/// # 返回值
+/// - `Ok(Value)`: 合并后的默认配置对象
+/// - `Err(String)`: 某个提供者返回的不是对象
+///
+/// [`merge_with_provenance`]: super::merge::merge_with_provenance
+pub fn layered_defaults(providers: &[&dyn DefaultProvider]) -> Result<Value, String> {
+    let mut merged = Value::Object(Default::default());
+    for provider in providers {
+        match provider.defaults() {
+            Value::Null => {}
+            defaults @ Value::Object(_) => deep_merge(&mut merged, defaults),
+            _ => {
+                return Err(format!(
+                    "Default provider {:?} must return a JSON object",
+                    provider.name()
+                ))
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// 加载配置，缺失的字段由各提供者的默认值补全
+///
+/// # 参数
+/// - `config_path`: 配置文件路径（不存在时只使用默认值）
+/// - `providers`: 默认值提供者，顺序见 [`layered_defaults`]
+///
+/// # 返回值
+/// - `Ok(T)`: 文件中的值覆盖所有默认值后的配置
+/// - `Err(String)`: 读取、合并或反序列化失败
+///
+/// # 特性
+/// - ✅ 深度合并：文件只写了 `git.autoFetch` 时，插件提供的其他 `git.*` 默认值保留
+/// - ✅ 结果是确定的，只取决于提供者的顺序和各自返回的值
+/// - ✅ 所有提供者都未覆盖的字段仍按 `T` 的 `#[serde(default)]` 处理
+pub fn load_json_config_with_defaults<T>(
+    config_path: impl AsRef<Path>,
+    providers: &[&dyn DefaultProvider],
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let path = config_path.as_ref();
+    let mut merged = layered_defaults(providers)?;
+    let file: Value = load_json_config(path)?;
+    if !file.is_null() {
+        deep_merge(&mut merged, file);
+    }
+    serde_json::from_value(merged)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct StaticDefaults(&'static str, Value);
+
+    impl DefaultProvider for StaticDefaults {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn defaults(&self) -> Value {
+            self.1.clone()
+        }
+    }
+
+    #[test]
+    fn test_load_json_config_with_defaults() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        let core = StaticDefaults(
+            "core",
+            json!({ "model": "sonnet", "git": { "enabled": false } }),
+        );
+        let git = StaticDefaults(
+            "plugin:git",
+            json!({ "git": { "enabled": true, "autoFetch": true, "remote": "origin" } }),
+        );
+
+        let value: Value = load_json_config_with_defaults(&path, &[&core, &git]).unwrap();
+        assert_eq!(value["git"]["enabled"], true);
+
+        std::fs::write(&path, r#"{"git": {"autoFetch": false}, "model": "opus"}"#).unwrap();
+        let value: Value = load_json_config_with_defaults(&path, &[&core, &git]).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "model": "opus",
+                "git": { "enabled": true, "autoFetch": false, "remote": "origin" },
+            })
+        );
+
+        let broken = StaticDefaults("broken", json!([1]));
+        let err = layered_defaults(&[&core, &broken]).unwrap_err();
+        assert!(err.contains("\"broken\""), "{}", err);
+    }
+}
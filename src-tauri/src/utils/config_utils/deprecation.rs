@@ -0,0 +1,135 @@
+//! 废弃配置键提示
+//!
+//! 删除或重命名配置键后，已有文件中仍可能保留旧键。
+//! 加载时检测这些键并返回警告（而不是错误），让用户在旧键彻底失效前得到提示。
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 废弃键声明
+#[derive(Debug, Clone, Copy)]
+pub struct DeprecatedKey<'a> {
+    /// 废弃键的 JSON Pointer
+    pub pointer: &'a str,
+    /// 替代键的 JSON Pointer（没有替代时为 `None`）
+    pub replacement: Option<&'a str>,
+}
+
+/// 废弃键警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecationWarning {
+    pub pointer: String,
+    pub replacement: Option<String>,
+    /// 面向用户的提示文本
+    pub message: String,
+}
+
+impl fmt::Display for DeprecationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// 检查配置中出现的废弃键
+pub fn check_deprecated_keys(
+    value: &Value,
+    deprecated: &[DeprecatedKey],
+) -> Vec<DeprecationWarning> {
+    deprecated
+        .iter()
+        .filter(|key| value.pointer(key.pointer).is_some())
+        .map(|key| {
+            let name = display_key(key.pointer);
+            let message = match key.replacement {
+                Some(replacement) => format!(
+                    "'{}' is deprecated, use '{}'",
+                    name,
+                    display_key(replacement)
+                ),
+                None => format!("'{}' is deprecated and will be ignored", name),
+            };
+            DeprecationWarning {
+                pointer: key.pointer.to_string(),
+                replacement: key.replacement.map(str::to_string),
+                message,
+            }
+        })
+        .collect()
+}
+
+/// 加载配置并返回废弃键警告
+///
+/// # 返回值
+/// - `Ok((T, warnings))`: 配置对象及废弃键警告（文件不存在时为默认值和空列表）
+/// - `Err(String)`: 读取或解析失败
+pub fn load_json_config_with_deprecations<T>(
+    config_path: impl AsRef<Path>,
+    deprecated: &[DeprecatedKey],
+) -> Result<(T, Vec<DeprecationWarning>), String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    if !path.exists() {
+        return Ok((T::default(), Vec::new()));
+    }
+
+    let raw: Value = super::load_json_config(path)?;
+    let warnings = check_deprecated_keys(&raw, deprecated);
+    for warning in &warnings {
+        log::warn!("{:?}: {}", path, warning);
+    }
+
+    let config = serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+    Ok((config, warnings))
+}
+
+/// 将 JSON Pointer 转为便于阅读的点分键名
+fn display_key(pointer: &str) -> String {
+    pointer.trim_start_matches('/').replace('/', ".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_deprecated_keys() {
+        let deprecated = [
+            DeprecatedKey {
+                pointer: "/apiKey",
+                replacement: Some("/anthropicApiKey"),
+            },
+            DeprecatedKey {
+                pointer: "/legacy/flag",
+                replacement: None,
+            },
+        ];
+        let value = json!({ "apiKey": "x", "legacy": {} });
+
+        let warnings = check_deprecated_keys(&value, &deprecated);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "'apiKey' is deprecated, use 'anthropicApiKey'"
+        );
+
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"apiKey": "x"}"#).unwrap();
+        let (loaded, loaded_warnings): (Value, _) =
+            load_json_config_with_deprecations(&path, &deprecated).unwrap();
+        assert_eq!(loaded, json!({ "apiKey": "x" }));
+        assert_eq!(loaded_warnings, warnings);
+
+        let (missing, none): (Value, _) =
+            load_json_config_with_deprecations(temp.path().join("none.json"), &deprecated).unwrap();
+        assert!(missing.is_null() && none.is_empty());
+    }
+}
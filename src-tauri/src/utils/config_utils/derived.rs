@@ -0,0 +1,142 @@
+//! 派生配置缓存
+//!
+//! 某些索引（如扁平化的查找表）由设置文件计算而来。源文件变化时重新计算并写出派生文件，
+//! 结果未变化时不重写，避免下游监听者被无意义的修改时间触发。
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::watch::{spawn_watcher, ConfigWatcher};
+use super::{load_json_config, save_json_config};
+
+/// 派生配置监听器（即 [`ConfigWatcher`]），drop 时停止监听线程
+pub type DerivedConfigWatcher = ConfigWatcher;
+
+/// 从源配置计算并写出派生文件
+///
+/// # 参数
+/// - `source`: 源配置路径
+/// - `transform`: 由源配置计算派生值
+/// - `derived`: 派生文件路径
+///
+/// # 返回值
+/// - `Ok(true)`: 派生文件已写入
+/// - `Ok(false)`: 计算结果与现有派生文件一致，未重写
+pub fn build_derived_config<T, D, F>(
+    source: impl AsRef<Path>,
+    transform: F,
+    derived: impl AsRef<Path>,
+) -> Result<bool, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+    D: Serialize,
+    F: Fn(&T) -> D,
+{
+    let config: T = load_json_config(source.as_ref())?;
+    let output = serde_json::to_value(transform(&config))
+        .map_err(|e| format!("Failed to serialize derived config: {}", e))?;
+
+    let derived = derived.as_ref();
+    if derived.exists() {
+        let existing: serde_json::Value = load_json_config(derived).unwrap_or_default();
+        if existing == output {
+            return Ok(false);
+        }
+    }
+
+    save_json_config(&output, derived)?;
+    Ok(true)
+}
+
+/// 监听源配置，每次变化后重新生成派生文件
+///
+/// 启动时立即生成一次，之后按 `interval` 轮询源文件的修改时间与大小。
+/// 生成失败（如源文件暂时不完整）只记录警告，下次变化时重试。
+pub fn watch_derived_config<T, D, F>(
+    source: impl AsRef<Path>,
+    transform: F,
+    derived: impl AsRef<Path>,
+    interval: Duration,
+) -> DerivedConfigWatcher
+where
+    T: for<'de> Deserialize<'de> + Default,
+    D: Serialize,
+    F: Fn(&T) -> D + Send + 'static,
+{
+    let derived = derived.as_ref().to_path_buf();
+    spawn_watcher(
+        source.as_ref().to_path_buf(),
+        interval,
+        true,
+        move |source| rebuild(source, &transform, &derived),
+    )
+}
+
+fn rebuild<T, D, F>(source: &Path, transform: &F, derived: &Path)
+where
+    T: for<'de> Deserialize<'de> + Default,
+    D: Serialize,
+    F: Fn(&T) -> D,
+{
+    match build_derived_config(source, transform, derived) {
+        Ok(true) => log::debug!("Rebuilt derived config {:?} from {:?}", derived, source),
+        Ok(false) => {}
+        Err(e) => log::warn!("Failed to rebuild derived config {:?}: {}", derived, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::thread;
+
+    fn key_count(config: &Value) -> usize {
+        config.as_object().map_or(0, |map| map.len())
+    }
+
+    #[test]
+    fn test_build_derived_config_skips_unchanged_output() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("settings.json");
+        let derived = temp.path().join("derived.json");
+        save_json_config(&json!({ "a": 1, "b": 2 }), &source).unwrap();
+
+        assert!(build_derived_config(&source, key_count, &derived).unwrap());
+        assert!(!build_derived_config(&source, key_count, &derived).unwrap());
+
+        save_json_config(&json!({ "a": 1, "c": 3 }), &source).unwrap();
+        assert!(!build_derived_config(&source, key_count, &derived).unwrap());
+
+        save_json_config(&json!({ "a": 1 }), &source).unwrap();
+        assert!(build_derived_config(&source, key_count, &derived).unwrap());
+        assert_eq!(load_json_config::<Value>(&derived).unwrap(), json!(1));
+    }
+
+    #[test]
+    fn test_watch_derived_config_rebuilds_on_change() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("settings.json");
+        let derived = temp.path().join("derived.json");
+        save_json_config(&json!({ "a": 1 }), &source).unwrap();
+
+        let watcher = watch_derived_config(&source, key_count, &derived, Duration::from_millis(10));
+        wait_for(|| load_json_config::<Value>(&derived).ok() == Some(json!(1)));
+
+        save_json_config(&json!({ "a": 1, "b": 2, "c": 3 }), &source).unwrap();
+        wait_for(|| load_json_config::<Value>(&derived).ok() == Some(json!(3)));
+        watcher.stop();
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("condition not met in time");
+    }
+}
@@ -0,0 +1,106 @@
+//! 配置差异比较
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::pointer::escape_pointer_token;
+
+/// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 单个键的差异
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    /// 键的 JSON Pointer
+    pub pointer: String,
+    pub kind: ConfigChangeKind,
+    /// 旧值（新增时为 `None`）
+    pub old_value: Option<Value>,
+    /// 新值（删除时为 `None`）
+    pub new_value: Option<Value>,
+}
+
+/// 比较两个配置
+///
+/// 对象逐键递归比较，数组和标量作为整体比较
+///
+/// # 返回值
+/// 按 JSON Pointer 排序的差异列表
+pub fn diff_json_configs(old: &Value, new: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_at("", old, new, &mut changes);
+    changes.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    changes
+}
+
+fn diff_at(pointer: &str, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            // serde_json 的 Map 默认按键排序，输出顺序稳定
+            for (key, old_value) in old_map {
+                let child = format!("{}/{}", pointer, escape_pointer_token(key));
+                match new_map.get(key) {
+                    Some(new_value) => diff_at(&child, old_value, new_value, changes),
+                    None => changes.push(ConfigChange {
+                        pointer: child,
+                        kind: ConfigChangeKind::Removed,
+                        old_value: Some(old_value.clone()),
+                        new_value: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    changes.push(ConfigChange {
+                        pointer: format!("{}/{}", pointer, escape_pointer_token(key)),
+                        kind: ConfigChangeKind::Added,
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        _ if old == new => {}
+        _ => changes.push(ConfigChange {
+            pointer: pointer.to_string(),
+            kind: ConfigChangeKind::Changed,
+            old_value: Some(old.clone()),
+            new_value: Some(new.clone()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_json_configs() {
+        let old = json!({ "model": "a", "proxy": { "url": "x" }, "tools": ["a"] });
+        let new = json!({ "model": "b", "proxy": {}, "tools": ["a"], "theme": "dark" });
+
+        let changes = diff_json_configs(&old, &new);
+        let summary: Vec<(&str, ConfigChangeKind)> = changes
+            .iter()
+            .map(|c| (c.pointer.as_str(), c.kind))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("/model", ConfigChangeKind::Changed),
+                ("/proxy/url", ConfigChangeKind::Removed),
+                ("/theme", ConfigChangeKind::Added),
+            ]
+        );
+        assert!(diff_json_configs(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+//! 配置的规范形式与摘要编码
+//!
+//! 签名、指纹、打包清单和 Schema 缓存等都需要与键顺序、格式化方式无关的字节形式，
+//! 或 SHA-256 等摘要的十六进制表示，统一在这里实现，保证各处结果一致。
+
+#[cfg(test)]
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// 配置的规范字节形式：键按字典序排列、无空白
+#[cfg(test)]
+pub fn canonical_config_bytes(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+/// 内容的 SHA-256 摘要（小写十六进制）
+pub(crate) fn sha256_hex(content: &[u8]) -> String {
+    to_hex(&Sha256::digest(content))
+}
+
+/// 小写十六进制编码
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+fn write_canonical(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(map) => {
+            out.push(b'{');
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                out.extend_from_slice(Value::String(key.clone()).to_string().as_bytes());
+                out.push(b':');
+                write_canonical(&map[key], out);
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        scalar => out.extend_from_slice(scalar.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_config_bytes() {
+        let value = json!({ "b": [1, { "d": null, "c": "x\"y" }], "a": true });
+        assert_eq!(
+            canonical_config_bytes(&value),
+            br#"{"a":true,"b":[1,{"c":"x\"y","d":null}]}"#
+        );
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xab]), "000fab");
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! 应用内配置查看器的显示格式
+//!
+//! 密钥只显示最后 4 个字符（如 `...ab12`），用户可以确认密钥已设置且"看起来没错"，
+//! 又不会完整暴露。
+
+use serde_json::Value;
+
+/// Claude 设置中常见的密钥位置
+pub const DEFAULT_SECRET_POINTERS: &[&str] = &[
+    "/env/ANTHROPIC_API_KEY",
+    "/env/ANTHROPIC_AUTH_TOKEN",
+    "/apiKeyHelper",
+];
+
+/// 显示的密钥末尾字符数
+const VISIBLE_SUFFIX_LEN: usize = 4;
+
+/// 短于该长度的密钥完全隐藏，避免只显示末尾也泄露大部分内容
+const MIN_PARTIAL_LEN: usize = 12;
+
+/// 美化输出配置，并折叠密钥
+///
+/// # 参数
+/// - `value`: 配置值
+/// - `secret_pointers`: 密钥的 JSON Pointer 列表；指向对象或数组时折叠其中所有的值
+///
+/// # 返回值
+/// 美化格式的 JSON 文本，密钥显示为 `...ab12`（过短的密钥显示为 `...`）
+pub fn display_config(value: &Value, secret_pointers: &[&str]) -> String {
+    let mut masked = value.clone();
+    for pointer in secret_pointers {
+        if let Some(secret) = masked.pointer_mut(pointer) {
+            fold_secrets(secret);
+        }
+    }
+    serde_json::to_string_pretty(&masked).unwrap_or_else(|_| masked.to_string())
+}
+
+fn fold_secrets(value: &mut Value) {
+    match value {
+        Value::Null => {}
+        Value::Object(map) => map.values_mut().for_each(fold_secrets),
+        Value::Array(items) => items.iter_mut().for_each(fold_secrets),
+        Value::String(text) => *value = Value::String(mask_secret(text)),
+        other => *other = Value::String(mask_secret(&other.to_string())),
+    }
+}
+
+fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < MIN_PARTIAL_LEN {
+        return "...".to_string();
+    }
+    let suffix: String = chars[chars.len() - VISIBLE_SUFFIX_LEN..].iter().collect();
+    format!("...{}", suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_display_config_folds_secrets() {
+        let value = json!({
+            "model": "opus",
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-ant-0123456789ab12", "HTTPS_PROXY": "http://p" },
+            "apiKeyHelper": "short",
+            "headers": { "x-api-key": "abcdefghijklmnop", "retries": 3 }
+        });
+
+        let mut pointers = DEFAULT_SECRET_POINTERS.to_vec();
+        pointers.push("/headers");
+        let shown: Value = serde_json::from_str(&display_config(&value, &pointers)).unwrap();
+
+        assert_eq!(
+            shown,
+            json!({
+                "model": "opus",
+                "env": { "ANTHROPIC_AUTH_TOKEN": "...ab12", "HTTPS_PROXY": "http://p" },
+                "apiKeyHelper": "...",
+                "headers": { "x-api-key": "...mnop", "retries": "..." }
+            })
+        );
+    }
+}
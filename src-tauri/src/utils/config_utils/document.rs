@@ -0,0 +1,127 @@
+//! 配置文档句柄
+//!
+//! 同时支持类型化读取和无损的结构化编辑：持有路径和解析后的 `Value`，
+//! 需要时反序列化为任意类型，编辑 `Value` 后用标准格式写回。
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::pointer::{remove_pointer, set_pointer};
+use super::{load_json_config, save_json_config};
+
+/// 配置文档
+#[derive(Debug, Clone)]
+pub struct ConfigDocument {
+    path: PathBuf,
+    value: Value,
+}
+
+impl ConfigDocument {
+    /// 加载配置文档（文件不存在时为空对象）
+    pub fn load(config_path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = config_path.as_ref().to_path_buf();
+        let value: Value = load_json_config(&path)?;
+        let value = if value.is_null() {
+            Value::Object(Map::new())
+        } else {
+            value
+        };
+        Ok(Self { path, value })
+    }
+
+    /// 配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 原始 JSON 值
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// 可变的原始 JSON 值，用于结构化编辑
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+
+    /// 将整个文档反序列化为 `T`
+    pub fn get<T>(&self) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_value(self.value.clone())
+            .map_err(|e| format!("Failed to parse config from {:?}: {}", self.path, e))
+    }
+
+    /// 将指针处的值反序列化为 `T`（不存在时返回 `None`）
+    pub fn get_at<T>(&self, pointer: &str) -> Result<Option<T>, String>
+    where
+        T: DeserializeOwned,
+    {
+        self.value
+            .pointer(pointer)
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .map_err(|e| format!("Failed to parse {} in {:?}: {}", pointer, self.path, e))
+            })
+            .transpose()
+    }
+
+    /// 在指针处写入值（仅修改内存，需调用 [`ConfigDocument::save`] 持久化）
+    pub fn set_at<V>(&mut self, pointer: &str, value: V) -> Result<(), String>
+    where
+        V: Serialize,
+    {
+        let value = serde_json::to_value(value)
+            .map_err(|e| format!("Failed to serialize {}: {}", pointer, e))?;
+        set_pointer(&mut self.value, pointer, value)
+    }
+
+    /// 删除指针处的值（仅修改内存）
+    pub fn remove_at(&mut self, pointer: &str) -> Option<Value> {
+        remove_pointer(&mut self.value, pointer)
+    }
+
+    /// 将文档写回磁盘
+    pub fn save(&self) -> Result<(), String> {
+        save_json_config(&self.value, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_document_edit_and_save() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "a", "hooks": {"pre": []}}"#).unwrap();
+
+        let mut doc = ConfigDocument::load(&path).unwrap();
+        assert_eq!(doc.path(), path);
+        assert_eq!(
+            doc.get::<Value>().unwrap(),
+            json!({ "model": "a", "hooks": { "pre": [] } })
+        );
+        assert_eq!(
+            doc.get_at::<String>("/model").unwrap().as_deref(),
+            Some("a")
+        );
+
+        doc.set_at("/hooks/pre/-", "lint").unwrap();
+        doc.value_mut()["theme"] = json!("dark");
+        assert_eq!(doc.remove_at("/model"), Some(json!("a")));
+        doc.save().unwrap();
+
+        let reloaded = ConfigDocument::load(&path).unwrap();
+        assert_eq!(
+            reloaded.value(),
+            &json!({ "hooks": { "pre": ["lint"] }, "theme": "dark" })
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! 配置漂移检测
+//!
+//! 托管部署中，组织下发一份签名的基线配置。[`detect_drift`] 先确认基线的签名有效，
+//! 再列出用户配置与基线不同的每个键，管理员据此了解用户具体改动了什么。
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::diff::{diff_json_configs, ConfigChangeKind};
+use super::load_json_config;
+use super::signature::check_signature;
+
+/// 与基线不同的一个键
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDrift {
+    /// 键的 JSON Pointer
+    pub pointer: String,
+    /// 相对基线：`added` 为用户新增，`removed` 为用户删除，`changed` 为值不同
+    pub kind: ConfigChangeKind,
+    /// 基线中的值（用户新增的键为 `None`）
+    pub baseline_value: Option<Value>,
+    /// 用户配置中的值（用户删除的键为 `None`）
+    pub current_value: Option<Value>,
+}
+
+/// 漂移检测报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub config_path: PathBuf,
+    pub baseline_path: PathBuf,
+    /// 按 JSON Pointer 排序的差异
+    pub drifts: Vec<ConfigDrift>,
+}
+
+impl DriftReport {
+    /// 用户配置是否偏离了基线
+    pub fn has_drift(&self) -> bool {
+        !self.drifts.is_empty()
+    }
+}
+
+/// 检测用户配置相对签名基线的漂移
+///
+/// # 参数
+/// - `config_path`: 用户配置路径（不存在时视为空对象，基线中的每个键都记为删除）
+/// - `baseline_path`: 基线配置路径，签名存放在 [`signature_path`] 处
+/// - `public_key`: 校验基线签名的 32 字节 Ed25519 公钥
+///
+/// # 返回值
+/// - `Ok(DriftReport)`: 差异报告；有漂移时同时记录一条警告日志
+/// - `Err(String)`: 基线不存在、没有签名、签名无效（以 [`CONFIG_SIGNATURE_INVALID`] 开头）或读取失败
+///
+/// # 特性
+/// - ✅ 签名校验与比较使用同一次读取的基线内容
+/// - ✅ 对象逐键比较，数组作为整体比较（与 [`diff_json_configs`] 一致）
+///
+/// [`signature_path`]: super::signature::signature_path
+/// [`CONFIG_SIGNATURE_INVALID`]: super::signature::CONFIG_SIGNATURE_INVALID
+pub fn detect_drift(
+    config_path: impl AsRef<Path>,
+    baseline_path: impl AsRef<Path>,
+    public_key: &[u8],
+) -> Result<DriftReport, String> {
+    let config_path = config_path.as_ref();
+    let baseline_path = baseline_path.as_ref();
+    if !baseline_path.is_file() {
+        return Err(format!("Baseline config not found at {:?}", baseline_path));
+    }
+
+    let baseline: Value = load_json_config(baseline_path)?;
+    if !check_signature(baseline_path, &baseline, public_key)? {
+        return Err(format!("Baseline config {:?} is not signed", baseline_path));
+    }
+    let current = match load_json_config::<Value>(config_path)? {
+        Value::Null => Value::Object(Default::default()),
+        current => current,
+    };
+
+    let drifts: Vec<ConfigDrift> = diff_json_configs(&baseline, &current)
+        .into_iter()
+        .map(|change| ConfigDrift {
+            pointer: change.pointer,
+            kind: change.kind,
+            baseline_value: change.old_value,
+            current_value: change.new_value,
+        })
+        .collect();
+    if !drifts.is_empty() {
+        let pointers: Vec<&str> = drifts.iter().map(|drift| drift.pointer.as_str()).collect();
+        log::warn!(
+            "Config {:?} drifted from baseline {:?}: {}",
+            config_path,
+            baseline_path,
+            pointers.join(", ")
+        );
+    }
+
+    Ok(DriftReport {
+        config_path: config_path.to_path_buf(),
+        baseline_path: baseline_path.to_path_buf(),
+        drifts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::signature::{
+        generate_config_signing_key, sign_config_file, CONFIG_SIGNATURE_INVALID,
+    };
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detect_drift() {
+        let temp = tempfile::tempdir().unwrap();
+        let baseline = temp.path().join("baseline.json");
+        let config = temp.path().join("settings.json");
+        let (signing_key, public_key) = generate_config_signing_key().unwrap();
+
+        fs::write(
+            &baseline,
+            r#"{"model": "sonnet", "permissions": {"deny": ["WebFetch"]}, "telemetry": false}"#,
+        )
+        .unwrap();
+        let err = detect_drift(&config, &baseline, &public_key).unwrap_err();
+        assert!(err.contains("is not signed"), "{}", err);
+        sign_config_file(&baseline, &signing_key).unwrap();
+
+        fs::write(
+            &config,
+            r#"{"model": "opus", "permissions": {"deny": ["WebFetch"]}, "theme": "dark"}"#,
+        )
+        .unwrap();
+        let report = detect_drift(&config, &baseline, &public_key).unwrap();
+        let summary: Vec<(&str, ConfigChangeKind)> = report
+            .drifts
+            .iter()
+            .map(|drift| (drift.pointer.as_str(), drift.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/model", ConfigChangeKind::Changed),
+                ("/telemetry", ConfigChangeKind::Removed),
+                ("/theme", ConfigChangeKind::Added),
+            ]
+        );
+        assert!(report.has_drift());
+        assert_eq!(report.drifts[0].baseline_value, Some("sonnet".into()));
+
+        fs::write(&baseline, r#"{"model": "opus"}"#).unwrap();
+        let err = detect_drift(&config, &baseline, &public_key).unwrap_err();
+        assert!(err.starts_with(CONFIG_SIGNATURE_INVALID), "{}", err);
+    }
+}
@@ -0,0 +1,260 @@
+//! Claude CLI 的生效设置
+//!
+//! CLI 按作用域逐层合并设置：用户 `~/.claude/settings.json` →
+//! 项目 `.claude/settings.json` → 项目本地 `.claude/settings.local.json` →
+//! 企业托管 `managed-settings.json`，越靠后优先级越高。
+//! 按同样的顺序计算生效设置，与工作台加载的配置比较，
+//! 可以发现 `settings.local.json` 等覆盖导致的 CLI 行为与界面显示不一致。
+//!
+//! 工作台自身的配置同样分层：默认值（[`DefaultProvider`]）→ 基础配置 → 激活的档案，
+//! 每个文件先展开 `$include`，最后应用命令行覆盖参数。[`write_effective_config`]
+//! 把这些层的合并结果导出为单个扁平文件。
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::defaults::{layered_defaults, DefaultProvider};
+use super::diff::{diff_json_configs, ConfigChange};
+use super::include::load_json_config_with_includes;
+use super::merge::deep_merge;
+use super::overrides::apply_cli_overrides;
+use super::profile::{active_profile, profile_dir};
+use super::{load_json_config, save_json_config};
+
+/// 权限规则在各作用域之间合并而不是覆盖
+const MERGED_PERMISSION_LISTS: &[&str] = &["allow", "deny", "ask"];
+
+/// 企业托管设置文件路径
+pub fn managed_settings_path() -> PathBuf {
+    #[cfg(target_os = "macos")]
+    return PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json");
+    #[cfg(windows)]
+    return PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json");
+    #[cfg(not(any(target_os = "macos", windows)))]
+    return PathBuf::from("/etc/claude-code/managed-settings.json");
+}
+
+/// 按优先级从低到高列出参与合并的设置文件
+///
+/// # 参数
+/// - `claude_dir`: 用户配置目录（通常为 `~/.claude`）
+/// - `project_dir`: 项目目录（不在项目中时为 `None`）
+pub fn claude_settings_layers(claude_dir: &Path, project_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut layers = vec![claude_dir.join("settings.json")];
+    if let Some(project) = project_dir {
+        let project_claude = project.join(".claude");
+        layers.push(project_claude.join("settings.json"));
+        layers.push(project_claude.join("settings.local.json"));
+    }
+    layers.push(managed_settings_path());
+    layers
+}
+
+/// 按顺序合并各层设置（不存在的文件跳过）
+///
+/// 对象递归合并，标量与数组由高优先级覆盖；
+/// `permissions.allow` / `deny` / `ask` 规则列表按 CLI 行为取并集
+pub fn load_effective_settings(layers: &[PathBuf]) -> Result<Value, String> {
+    let mut effective = Value::Object(Default::default());
+    for layer in layers {
+        let Value::Object(mut overlay) = load_json_config::<Value>(layer)? else {
+            continue;
+        };
+        if let Some(Value::Object(permissions)) = overlay.get_mut("permissions") {
+            for list in MERGED_PERMISSION_LISTS {
+                let (Some(Value::Array(incoming)), Some(Value::Array(existing))) = (
+                    permissions.get_mut(*list),
+                    effective.pointer(&format!("/permissions/{}", list)),
+                ) else {
+                    continue;
+                };
+                let mut combined = existing.clone();
+                for rule in incoming.drain(..) {
+                    if !combined.contains(&rule) {
+                        combined.push(rule);
+                    }
+                }
+                *incoming = combined;
+            }
+        }
+        deep_merge(&mut effective, Value::Object(overlay));
+    }
+    Ok(effective)
+}
+
+/// 比较工作台加载的配置与 CLI 的生效设置
+///
+/// # 返回值
+/// - `Ok(Vec<ConfigChange>)`: 从工作台配置到生效设置的差异（`new_value` 为 CLI 实际使用的值）
+/// - `Err(String)`: 某一层设置无法读取或解析
+pub fn diff_effective_settings(
+    workbench: &Value,
+    claude_dir: &Path,
+    project_dir: Option<&Path>,
+) -> Result<Vec<ConfigChange>, String> {
+    let effective = load_effective_settings(&claude_settings_layers(claude_dir, project_dir))?;
+    Ok(diff_json_configs(workbench, &effective))
+}
+
+/// 计算工作台配置的生效值
+///
+/// # 参数
+/// - `base_dir`: 配置目录（如 `~/.claude`）
+/// - `filename`: 配置文件名（如 `"settings.json"`）
+/// - `defaults`: 默认值提供者，顺序见 [`layered_defaults`]
+/// - `overrides`: 命令行覆盖参数，格式见 [`apply_cli_overrides`]
+///
+/// # 合并顺序（后者覆盖前者）
+/// 1. 各提供者的默认值
+/// 2. `<base_dir>/<filename>`，展开 `$include`（见 [`load_json_config_with_includes`]）
+/// 3. 激活档案中的 `<base_dir>/profiles/<档案名>/<filename>`，同样展开 `$include`
+/// 4. 命令行覆盖参数
+///
+/// # 返回值
+/// - `Ok((value, files))`: 生效配置，以及参与合并的文件（按合并顺序）
+/// - `Err(String)`: 某一层无法读取或解析，或覆盖参数非法
+pub fn load_effective_config(
+    base_dir: &Path,
+    filename: &str,
+    defaults: &[&dyn DefaultProvider],
+    overrides: &[(String, String)],
+) -> Result<(Value, Vec<PathBuf>), String> {
+    let mut effective = layered_defaults(defaults)?;
+    let mut layers = vec![base_dir.join(filename)];
+    if let Some(profile) = active_profile(base_dir)? {
+        layers.push(profile_dir(base_dir, &profile)?.join(filename));
+    }
+
+    let mut files = Vec::new();
+    for layer in layers.iter().filter(|layer| layer.is_file()) {
+        let (value, included) = load_json_config_with_includes(layer)?;
+        deep_merge(&mut effective, value);
+        files.extend(included);
+    }
+    apply_cli_overrides(&mut effective, overrides).map_err(|errors| errors.join("; "))?;
+    Ok((effective, files))
+}
+
+/// 将工作台配置的生效值导出为单个 JSON 文件
+///
+/// 用于问题反馈（"实际生效的到底是哪份配置"），或在新机器上还原完全相同的设置，
+/// 导出的文件不含 `$include`，不依赖默认值、档案或覆盖参数。内容包含 `env` 中的密钥等原样的值，
+/// 对外分享前应使用 [`display_config`] 折叠
+///
+/// # 参数
+/// 同 [`load_effective_config`]，另加输出文件路径 `out_path`
+///
+/// # 返回值
+/// - `Ok(Vec<PathBuf>)`: 参与合并的配置文件（按合并顺序）
+/// - `Err(String)`: 某一层无法读取或解析，覆盖参数非法，或写入失败
+///
+/// [`display_config`]: super::display::display_config
+pub fn write_effective_config(
+    base_dir: &Path,
+    filename: &str,
+    defaults: &[&dyn DefaultProvider],
+    overrides: &[(String, String)],
+    out_path: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, String> {
+    let (effective, files) = load_effective_config(base_dir, filename, defaults, overrides)?;
+    save_json_config(&effective, out_path)?;
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::diff::ConfigChangeKind;
+    use super::super::profile::{ACTIVE_PROFILE_FILE, PROFILES_DIR};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_effective_settings() {
+        let temp = tempfile::tempdir().unwrap();
+        let claude_dir = temp.path().join(".claude");
+        let project = temp.path().join("project");
+
+        save_json_config(
+            &json!({ "model": "sonnet", "permissions": { "allow": ["Read"] } }),
+            claude_dir.join("settings.json"),
+        )
+        .unwrap();
+        save_json_config(
+            &json!({ "permissions": { "allow": ["Bash(npm test)"] } }),
+            project.join(".claude").join("settings.json"),
+        )
+        .unwrap();
+        save_json_config(
+            &json!({ "model": "opus" }),
+            project.join(".claude").join("settings.local.json"),
+        )
+        .unwrap();
+
+        let layers = claude_settings_layers(&claude_dir, Some(&project));
+        let effective = load_effective_settings(&layers[..3]).unwrap();
+        assert_eq!(
+            effective,
+            json!({ "model": "opus", "permissions": { "allow": ["Read", "Bash(npm test)"] } })
+        );
+
+        let workbench = json!({ "model": "sonnet", "permissions": { "allow": ["Read"] } });
+        let changes = diff_json_configs(&workbench, &effective);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].pointer, "/model");
+        assert_eq!(changes[0].kind, ConfigChangeKind::Changed);
+        assert_eq!(changes[0].new_value, Some(json!("opus")));
+        if !managed_settings_path().exists() {
+            assert_eq!(
+                diff_effective_settings(&workbench, &claude_dir, Some(&project)).unwrap(),
+                changes
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_effective_config() {
+        struct CoreDefaults;
+
+        impl DefaultProvider for CoreDefaults {
+            fn name(&self) -> &str {
+                "core"
+            }
+
+            fn defaults(&self) -> Value {
+                json!({ "model": "sonnet", "theme": "light", "env": { "A": "default" } })
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join(".claude");
+        let work = base.join(PROFILES_DIR).join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        std::fs::write(
+            base.join("settings.json"),
+            r#"{"$include": "shared.json", "env": {"B": "base"}}"#,
+        )
+        .unwrap();
+        std::fs::write(base.join("shared.json"), r#"{"theme": "dark"}"#).unwrap();
+        std::fs::write(work.join("settings.json"), r#"{"model": "opus"}"#).unwrap();
+        std::fs::write(base.join(PROFILES_DIR).join(ACTIVE_PROFILE_FILE), "work").unwrap();
+
+        let out = temp.path().join("effective.json");
+        let overrides = vec![("env.A".to_string(), "cli".to_string())];
+        let used =
+            write_effective_config(&base, "settings.json", &[&CoreDefaults], &overrides, &out)
+                .unwrap();
+        assert_eq!(used.len(), 3);
+        assert!(used[2].ends_with("work/settings.json"));
+
+        let written: Value = load_json_config(&out).unwrap();
+        assert_eq!(
+            written,
+            json!({
+                "model": "opus",
+                "theme": "dark",
+                "env": { "A": "cli", "B": "base" },
+            })
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! 配置文件编码检测
+//!
+//! 部分旧版 Windows 环境会把 settings.json 保存为本地代码页（如 GBK、Windows-1252）。
+//! 读取时按 BOM 或内容猜测编码并转码为 UTF-8；保存始终写出 UTF-8，
+//! 因此下一次保存会自动把文件改写为 UTF-8。
+
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_8};
+
+/// 将配置文件的原始字节解码为 UTF-8 文本
+///
+/// # 返回值
+/// - `Ok((text, None))`: 文件本身是 UTF-8（可带 BOM，BOM 会被去掉）
+/// - `Ok((text, Some(name)))`: 从 `name` 编码转码而来
+/// - `Err(String)`: 无法可靠转码，错误信息中包含检测到的编码
+pub fn decode_config_bytes(bytes: &[u8]) -> Result<(String, Option<&'static str>), String> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return decode_with(encoding, &bytes[bom_len..]);
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Ok((text.to_string(), None));
+    }
+
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, Utf8Detection::Deny);
+    decode_with(encoding, bytes)
+}
+
+fn decode_with(
+    encoding: &'static Encoding,
+    bytes: &[u8],
+) -> Result<(String, Option<&'static str>), String> {
+    let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if had_errors {
+        return Err(format!(
+            "file is not valid UTF-8 (detected {})",
+            encoding.name()
+        ));
+    }
+    let source = (encoding != UTF_8).then(|| encoding.name());
+    Ok((text.into_owned(), source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_config_bytes() {
+        let utf8 = r#"{"name": "配置"}"#;
+        assert_eq!(
+            decode_config_bytes(utf8.as_bytes()).unwrap(),
+            (utf8.to_string(), None)
+        );
+
+        let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+        with_bom.extend_from_slice(utf8.as_bytes());
+        assert_eq!(decode_config_bytes(&with_bom).unwrap().0, utf8);
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(utf8.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(
+            decode_config_bytes(&utf16).unwrap(),
+            (utf8.to_string(), Some("UTF-16LE"))
+        );
+
+        let text = r#"{"note": "Café pâtisserie déjà réglé"}"#;
+        let (latin, _, _) = encoding_rs::WINDOWS_1252.encode(text);
+        let (decoded, source) = decode_config_bytes(&latin).unwrap();
+        assert_eq!(decoded, text);
+        assert!(source.is_some());
+    }
+}
@@ -0,0 +1,98 @@
+//! 配置指纹
+//!
+//! 只保存配置的短哈希，不保存任何配置内容，就能判断"自上次会话以来用户是否修改过配置"，
+//! 用于在不收集数据的前提下触发重新索引等操作。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::digest::{canonical_config_bytes, sha256_hex};
+use super::{load_json_config, update_json_config};
+
+/// 指纹长度（十六进制字符数）
+const FINGERPRINT_LEN: usize = 16;
+
+/// 计算配置的稳定指纹
+///
+/// 对规范形式（见 [`canonical_config_bytes`]）哈希，与键的书写顺序和格式化方式无关
+pub fn config_fingerprint(value: &Value) -> String {
+    let mut hex = sha256_hex(&canonical_config_bytes(value));
+    hex.truncate(FINGERPRINT_LEN);
+    hex
+}
+
+/// 读取上次记录的指纹
+///
+/// # 参数
+/// - `state_path`: 指纹记录文件（`名称 -> 指纹` 的 JSON 对象）
+/// - `name`: 配置名称（如 `settings.json`）
+pub fn last_config_fingerprint(state_path: &Path, name: &str) -> Result<Option<String>, String> {
+    let state: BTreeMap<String, String> = load_json_config(state_path)?;
+    Ok(state.get(name).cloned())
+}
+
+/// 比较配置与上次记录的指纹，并记录新的指纹
+///
+/// # 返回值
+/// - `Ok(true)`: 配置自上次记录后发生了变化（首次记录同样返回 `true`）
+/// - `Ok(false)`: 配置未变化
+/// - `Err(String)`: 记录文件无法读写，或不是 JSON 对象（此时不修改记录文件）
+pub fn check_config_changed(state_path: &Path, name: &str, value: &Value) -> Result<bool, String> {
+    let fingerprint = config_fingerprint(value);
+    update_json_config(state_path, |state| {
+        let state = state
+            .as_object_mut()
+            .ok_or_else(|| format!("Fingerprint state {:?} is not a JSON object", state_path))?;
+        let previous = state.get(name).and_then(Value::as_str);
+        let changed = previous != Some(fingerprint.as_str());
+        if changed {
+            state.insert(name.to_string(), Value::String(fingerprint));
+        }
+        Ok(changed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_fingerprint_is_stable() {
+        let a: Value =
+            serde_json::from_str(r#"{"b": [1, 2], "a": {"y": true, "x": null}}"#).unwrap();
+        let b: Value =
+            serde_json::from_str("{\n  \"a\": {\"x\": null, \"y\": true},\n  \"b\": [1, 2]\n}")
+                .unwrap();
+        assert_eq!(config_fingerprint(&a), config_fingerprint(&b));
+        assert_eq!(config_fingerprint(&a).len(), FINGERPRINT_LEN);
+
+        let c: Value =
+            serde_json::from_str(r#"{"b": [2, 1], "a": {"y": true, "x": null}}"#).unwrap();
+        assert_ne!(config_fingerprint(&a), config_fingerprint(&c));
+    }
+
+    #[test]
+    fn test_check_config_changed() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = temp.path().join("fingerprints.json");
+        let value = serde_json::json!({ "model": "opus" });
+
+        assert!(check_config_changed(&state, "settings.json", &value).unwrap());
+        assert!(!check_config_changed(&state, "settings.json", &value).unwrap());
+        assert_eq!(
+            last_config_fingerprint(&state, "settings.json").unwrap(),
+            Some(config_fingerprint(&value))
+        );
+
+        let edited = serde_json::json!({ "model": "sonnet" });
+        assert!(check_config_changed(&state, "settings.json", &edited).unwrap());
+        assert!(!std::fs::read_to_string(&state).unwrap().contains("sonnet"));
+
+        std::fs::write(&state, "[]").unwrap();
+        let err = check_config_changed(&state, "settings.json", &value).unwrap_err();
+        assert!(err.contains("is not a JSON object"), "{}", err);
+        assert_eq!(std::fs::read_to_string(&state).unwrap(), "[]");
+    }
+}
@@ -0,0 +1,131 @@
+//! 配置扁平化
+//!
+//! 供 UI 的"高级设置"表格使用：嵌套配置展开为点分键（`proxy.url`、`allowedTools.0`），
+//! 编辑后再还原为嵌套 JSON。
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+/// 将嵌套配置展开为点分键值对
+///
+/// - 对象键以 `.` 连接，数组元素以下标表示
+/// - 空对象、空数组作为叶子值保留，保证可以原样还原
+/// - 键名本身包含 `.` 时无法区分层级，还原结果可能不同
+pub fn flatten_config(value: &Value) -> BTreeMap<String, Value> {
+    let mut out = BTreeMap::new();
+    flatten_into("", value, &mut out);
+    out
+}
+
+/// 将点分键值对还原为嵌套配置，[`flatten_config`] 的逆操作
+///
+/// 所有子键都是从 0 开始连续下标的对象会还原为数组
+pub fn unflatten_config(map: &BTreeMap<String, Value>) -> Value {
+    let mut root = Value::Object(Map::new());
+    for (key, value) in map {
+        if key.is_empty() {
+            root = value.clone();
+            continue;
+        }
+
+        let mut current = &mut root;
+        for segment in key.split('.') {
+            if !current.is_object() {
+                *current = Value::Object(Map::new());
+            }
+            current = current
+                .as_object_mut()
+                .expect("just ensured object")
+                .entry(segment.to_string())
+                .or_insert(Value::Null);
+        }
+        *current = value.clone();
+    }
+
+    restore_arrays(root)
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                flatten_into(&join(key), child, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, child) in items.iter().enumerate() {
+                flatten_into(&join(&index.to_string()), child, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn restore_arrays(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let map: Map<String, Value> = map
+                .into_iter()
+                .map(|(key, child)| (key, restore_arrays(child)))
+                .collect();
+
+            let indices: Option<Vec<usize>> = map
+                .keys()
+                .map(|key| key.parse::<usize>().ok().filter(|i| i.to_string() == *key))
+                .collect();
+            let contiguous = match indices {
+                Some(mut indices) if !indices.is_empty() => {
+                    indices.sort_unstable();
+                    indices.iter().enumerate().all(|(i, index)| i == *index)
+                }
+                _ => false,
+            };
+
+            if contiguous {
+                let mut items: Vec<(usize, Value)> = map
+                    .into_iter()
+                    .filter_map(|(key, child)| key.parse().ok().map(|i| (i, child)))
+                    .collect();
+                items.sort_by_key(|(index, _)| *index);
+                Value::Array(items.into_iter().map(|(_, child)| child).collect())
+            } else {
+                Value::Object(map)
+            }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_and_unflatten_round_trip() {
+        let value = json!({
+            "proxy": { "url": "http://localhost", "enabled": true },
+            "allowedTools": ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"],
+            "empty": {},
+            "none": []
+        });
+
+        let flat = flatten_config(&value);
+        assert_eq!(flat["proxy.url"], json!("http://localhost"));
+        assert_eq!(flat["allowedTools.10"], json!("k"));
+        assert_eq!(flat["empty"], json!({}));
+
+        assert_eq!(unflatten_config(&flat), value);
+    }
+}
@@ -0,0 +1,197 @@
+//! 配置文件健康检查
+//!
+//! 设置页为每个配置文件显示绿/黄/红指示灯。[`analyze_config_health`] 一次性运行编码、
+//! 格式与语法检查，汇总为 [`ConfigHealth`]，界面无需分别调用各项检查。
+
+use std::fs;
+use std::path::Path;
+
+use encoding_rs::Encoding;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::encoding::decode_config_bytes;
+use super::lint::{find_duplicate_keys, DuplicateKey};
+use super::relaxed::sanitize_lenient_json;
+
+/// 总体健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    /// 没有问题
+    Ok,
+    /// 可以加载，但存在编码、格式或重复键问题
+    Warn,
+    /// 无法读取、解码或解析
+    Error,
+}
+
+/// 配置文件的健康检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHealth {
+    pub status: HealthStatus,
+    /// 文件以 BOM 开头
+    pub has_bom: bool,
+    /// 文件不是 UTF-8
+    pub non_utf8: bool,
+    /// 转码时检测到的源编码（如 `GBK`）
+    pub encoding: Option<String>,
+    /// 行尾有空白的行号（从 1 开始）
+    pub trailing_whitespace_lines: Vec<usize>,
+    /// 文件末尾没有换行符
+    pub missing_trailing_newline: bool,
+    /// 缩进混用制表符与空格，或空格数不是统一缩进宽度的倍数
+    pub inconsistent_indentation: bool,
+    /// 重复键
+    pub duplicate_keys: Vec<DuplicateKey>,
+    /// 能否被 [`load_json_config`](super::load_json_config) 解析
+    pub valid: bool,
+    /// 含注释或尾随逗号，只能宽松解析
+    pub lenient_syntax: bool,
+    /// 无法读取、解码或解析时的错误
+    pub error: Option<String>,
+}
+
+/// 检查配置文件的编码、格式和语法
+///
+/// # 状态
+/// - `Error`: 文件无法读取、无法转码或无法解析
+/// - `Warn`: 带 BOM、非 UTF-8、行尾空白、缩进不一致、重复键或宽松语法
+/// - `Ok`: 其余情况；缺少末尾换行符只作提示，不影响状态（应用自身保存的文件也没有末尾换行符）
+pub fn analyze_config_health(config_path: impl AsRef<Path>) -> ConfigHealth {
+    let path = config_path.as_ref();
+    let mut health = ConfigHealth {
+        status: HealthStatus::Ok,
+        has_bom: false,
+        non_utf8: false,
+        encoding: None,
+        trailing_whitespace_lines: Vec::new(),
+        missing_trailing_newline: false,
+        inconsistent_indentation: false,
+        duplicate_keys: Vec::new(),
+        valid: false,
+        lenient_syntax: false,
+        error: None,
+    };
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => return health.fail(format!("Failed to read config from {:?}: {}", path, e)),
+    };
+    health.has_bom = Encoding::for_bom(&bytes).is_some();
+    health.non_utf8 = std::str::from_utf8(&bytes).is_err();
+    let text = match decode_config_bytes(&bytes) {
+        Ok((text, encoding)) => {
+            health.encoding = encoding.map(str::to_string);
+            text
+        }
+        Err(e) => return health.fail(format!("Failed to decode {:?}: {}", path, e)),
+    };
+
+    health.missing_trailing_newline = !text.is_empty() && !text.ends_with('\n');
+    health.trailing_whitespace_lines = text
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| {
+            line.strip_suffix('\r')
+                .unwrap_or(line)
+                .ends_with([' ', '\t'])
+        })
+        .map(|(index, _)| index + 1)
+        .collect();
+    health.inconsistent_indentation = has_inconsistent_indentation(&text);
+
+    let strict = if serde_json::from_str::<Value>(&text).is_ok() {
+        text
+    } else {
+        let sanitized = sanitize_lenient_json(&text);
+        if let Err(e) = serde_json::from_str::<Value>(&sanitized) {
+            return health.fail(format!("Failed to parse config from {:?}: {}", path, e));
+        }
+        health.lenient_syntax = true;
+        sanitized
+    };
+    health.valid = true;
+    health.duplicate_keys = find_duplicate_keys(&strict).unwrap_or_default();
+
+    let warn = health.has_bom
+        || health.non_utf8
+        || !health.trailing_whitespace_lines.is_empty()
+        || health.inconsistent_indentation
+        || !health.duplicate_keys.is_empty()
+        || health.lenient_syntax;
+    if warn {
+        health.status = HealthStatus::Warn;
+    }
+    health
+}
+
+impl ConfigHealth {
+    fn fail(mut self, error: String) -> Self {
+        self.status = HealthStatus::Error;
+        self.error = Some(error);
+        self
+    }
+}
+
+/// 以最小的空格缩进为单位，检查各行缩进是否一致
+fn has_inconsistent_indentation(text: &str) -> bool {
+    let indents: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+        .filter(|indent| !indent.is_empty())
+        .collect();
+
+    let uses_tabs = indents.iter().any(|indent| indent.contains('\t'));
+    let uses_spaces = indents.iter().any(|indent| indent.contains(' '));
+    if uses_tabs {
+        return uses_spaces;
+    }
+    let unit = indents.iter().map(|indent| indent.len()).min().unwrap_or(0);
+    unit > 0 && indents.iter().any(|indent| indent.len() % unit != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_config_health() {
+        let temp = tempfile::tempdir().unwrap();
+
+        let clean = temp.path().join("clean.json");
+        fs::write(
+            &clean,
+            "{\n  \"model\": \"opus\",\n  \"env\": {\n    \"A\": \"1\"\n  }\n}",
+        )
+        .unwrap();
+        let health = analyze_config_health(&clean);
+        assert_eq!(health.status, HealthStatus::Ok);
+        assert!(health.valid);
+        assert!(health.missing_trailing_newline);
+
+        let messy = temp.path().join("messy.json");
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice(
+            b"{\r\n  \"model\": \"opus\", \r\n   \"model\": \"haiku\" // note\r\n}\r\n",
+        );
+        fs::write(&messy, bytes).unwrap();
+        let health = analyze_config_health(&messy);
+        assert_eq!(health.status, HealthStatus::Warn);
+        assert!(health.has_bom && !health.non_utf8);
+        assert_eq!(health.trailing_whitespace_lines, vec![2]);
+        assert!(!health.missing_trailing_newline);
+        assert!(health.inconsistent_indentation);
+        assert!(health.lenient_syntax);
+        assert_eq!(health.duplicate_keys[0].pointer, "/model");
+
+        let broken = temp.path().join("broken.json");
+        fs::write(&broken, "{\"model\": ").unwrap();
+        let health = analyze_config_health(&broken);
+        assert_eq!(health.status, HealthStatus::Error);
+        assert!(!health.valid);
+        assert!(health.error.unwrap().contains("Failed to parse"));
+    }
+}
@@ -0,0 +1,1139 @@
+//! 配置历史：备份、版本与日志
+//!
+//! 对于 `<dir>/<filename>`：
+//! - 备份：`<dir>/.backups/<filename>.<时间戳>.<序号>.bak`，保存时的完整副本；
+//!   序号单调递增，排序与清理以序号为准，系统时钟回拨（如 NTP 校正）时不会误删最新的备份
+//! - 增量备份：`<dir>/.backups/<filename>.<时间戳>.<序号>.delta`，见 [`backup_config_file_delta`]
+//! - 版本：`<dir>/.versions/objects/<sha256>.json` 按内容寻址存储，
+//!   `<dir>/.versions/<filename>.index.json` 记录该文件引用的版本
+//! - 日志：`<dir>/.journal/<filename>.log`，每行一条变更记录
+//!
+//! 通过 [`set_config_history`] 为配置目录开启后，[`save_json_config`] 等保存函数会在写入时
+//! 自动创建备份、记录版本和日志（默认关闭）。这些目录会随时间无限增长，
+//! [`vacuum_config_storage`] 按保留策略清理。
+//!
+//! [`save_json_config`]: super::save_json_config
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+#[cfg(test)]
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::Value;
+
+use super::atomic::atomic_write;
+use super::copy::copy_config_file;
+use super::digest::sha256_hex;
+#[cfg(test)]
+use super::patch::{apply_patch_to_value, create_config_patch};
+use super::paths::canonical_config_path;
+
+/// 备份目录名
+pub const BACKUPS_DIR: &str = ".backups";
+/// 版本目录名
+pub const VERSIONS_DIR: &str = ".versions";
+/// 日志目录名
+pub const JOURNAL_DIR: &str = ".journal";
+
+const BACKUP_SUFFIX: &str = ".bak";
+const DELTA_SUFFIX: &str = ".delta";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%6f";
+
+/// 保存时自动记录的配置历史
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfigHistory {
+    /// 覆盖前为原文件创建备份（见 [`backup_config_file`]）
+    pub backups: bool,
+    /// 写入后记录版本（见 [`record_config_version`]）
+    pub versions: bool,
+    /// 写入后向日志追加一条记录（见 [`append_config_journal`]）
+    pub journal: bool,
+}
+
+/// 开启了历史记录的配置目录（规范路径）
+static CONFIG_HISTORY: RwLock<Vec<(PathBuf, ConfigHistory)>> = RwLock::new(Vec::new());
+
+/// 单个备份
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    /// 被备份的配置文件名
+    pub filename: String,
+    /// 备份序号（同一配置文件内单调递增）
+    pub sequence: u64,
+    pub created_at: DateTime<Local>,
+    pub size: u64,
+    /// 是否为增量备份（相对于下一个较新备份的 JSON Patch）
+    pub delta: bool,
+}
+
+/// 版本索引中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionEntry {
+    pub hash: String,
+    pub saved_at: DateTime<Local>,
+}
+
+/// 清理策略
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct VacuumPolicy {
+    /// 超过该时长的备份/版本会被删除
+    pub max_age: Option<Duration>,
+    /// 每个配置文件最多保留的备份/版本数
+    pub max_count: Option<usize>,
+    /// 每个日志文件的最大字节数（超出时丢弃最早的记录）
+    pub max_journal_bytes: Option<u64>,
+    /// 备份与版本对象的总字节预算（超出时从最旧的开始删除，不分配置文件）
+    pub max_total_bytes: Option<u64>,
+}
+
+/// 清理结果
+#[cfg(test)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumReport {
+    pub bytes_reclaimed: u64,
+    pub files_removed: usize,
+}
+
+/// 为配置目录（递归）设置保存时自动记录的历史
+///
+/// 传入 `ConfigHistory::default()` 关闭；嵌套目录分别设置时，以最深的目录为准
+#[cfg(test)]
+pub fn set_config_history(base_dir: impl AsRef<Path>, history: ConfigHistory) {
+    let dir = canonical_config_path(base_dir.as_ref());
+    let mut registry = CONFIG_HISTORY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    registry.retain(|(existing, _)| *existing != dir);
+    if history != ConfigHistory::default() {
+        registry.push((dir, history));
+    }
+}
+
+/// 配置文件保存时自动记录的历史（未设置时全部关闭）
+pub fn config_history(config_path: impl AsRef<Path>) -> ConfigHistory {
+    let registry = CONFIG_HISTORY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if registry.is_empty() {
+        return ConfigHistory::default();
+    }
+    let path = canonical_config_path(config_path.as_ref());
+    registry
+        .iter()
+        .filter(|(dir, _)| path.starts_with(dir))
+        .max_by_key(|(dir, _)| dir.components().count())
+        .map_or_else(ConfigHistory::default, |(_, history)| *history)
+}
+
+/// 保存函数在写入前调用：按设置备份即将被覆盖的文件
+///
+/// 历史记录失败只记录警告，不影响保存本身
+pub(crate) fn before_save(path: &Path) {
+    if config_history(path).backups {
+        if let Err(e) = backup_config_file(path) {
+            log::warn!("Failed to back up {:?} before saving: {}", path, e);
+        }
+    }
+}
+
+/// 保存函数在写入后调用：按设置记录版本和日志
+pub(crate) fn after_save(path: &Path) {
+    let history = config_history(path);
+    if history.versions {
+        if let Err(e) = record_config_version(path) {
+            log::warn!("Failed to record version of {:?}: {}", path, e);
+        }
+    }
+    if history.journal {
+        if let Err(e) = append_config_journal(path, "saved") {
+            log::warn!("Failed to append journal for {:?}: {}", path, e);
+        }
+    }
+}
+
+/// 为配置文件创建备份
+///
+/// # 返回值
+/// - `Ok(Some(path))`: 备份文件路径
+/// - `Ok(None)`: 配置文件不存在，无需备份
+pub fn backup_config_file(config_path: impl AsRef<Path>) -> Result<Option<PathBuf>, String> {
+    let path = config_path.as_ref();
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let (dir, filename) = split_config_path(path)?;
+    let backups = dir.join(BACKUPS_DIR);
+    fs::create_dir_all(&backups)
+        .map_err(|e| format!("Failed to create backup directory {:?}: {}", backups, e))?;
+
+    let mut sequence = read_backups(&backups)?
+        .iter()
+        .filter(|entry| entry.filename == filename)
+        .map(|entry| entry.sequence + 1)
+        .max()
+        .unwrap_or(1);
+    let stamp = Local::now().format(STAMP_FORMAT).to_string();
+    let mut target = backup_path(&backups, &filename, &stamp, sequence);
+    while target.exists() {
+        sequence += 1;
+        target = backup_path(&backups, &filename, &stamp, sequence);
+    }
+
+    copy_config_file(path, &target)?;
+    Ok(Some(target))
+}
+
+/// 创建备份，并把上一个完整备份转为增量备份
+///
+/// 大配置频繁保存时，完整副本中绝大部分内容都是重复的。新备份总是完整副本；
+/// 上一个备份改为存储"从新备份还原到它"的 JSON Patch。增量方向从新到旧，
+/// 因此清理时从最旧的备份开始删除不会破坏补丁链
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `full_every`: 每多少个备份至少保留一个完整副本，限制还原时需要重放的补丁数；
+///   `0` 或 `1` 时等同于 [`backup_config_file`]
+///
+/// # 返回值
+/// - `Ok(Some(path))`: 新备份（完整副本）的路径
+/// - `Ok(None)`: 配置文件不存在，无需备份
+#[cfg(test)]
+pub fn backup_config_file_delta(
+    config_path: impl AsRef<Path>,
+    full_every: usize,
+) -> Result<Option<PathBuf>, String> {
+    let path = config_path.as_ref();
+    let Some(target) = backup_config_file(path)? else {
+        return Ok(None);
+    };
+
+    let backups = list_config_backups(path)?;
+    let [newest, previous, older @ ..] = backups.as_slice() else {
+        return Ok(Some(target));
+    };
+    let deltas_before = older.iter().take_while(|entry| entry.delta).count();
+    if previous.delta || deltas_before + 2 > full_every {
+        return Ok(Some(target));
+    }
+
+    // 内容无法解析为 JSON 时保留完整副本
+    let (Ok(new_value), Ok(old_value)) = (
+        super::load_json_config::<Value>(&newest.path),
+        super::load_json_config::<Value>(&previous.path),
+    ) else {
+        return Ok(Some(target));
+    };
+    let patch = serde_json::to_string(&create_config_patch(&new_value, &old_value))
+        .map_err(|e| format!("Failed to serialize backup delta: {}", e))?;
+    let delta_path = previous.path.with_extension(&DELTA_SUFFIX[1..]);
+    atomic_write(&delta_path, patch.as_bytes())?;
+    fs::remove_file(&previous.path)
+        .map_err(|e| format!("Failed to remove {:?}: {}", previous.path, e))?;
+    Ok(Some(target))
+}
+
+/// 读取备份内容，增量备份从最近的较新完整副本开始重放补丁
+///
+/// # 返回值
+/// - `Ok(T)`: 备份时的配置
+/// - `Err(String)`: 备份或补丁链中的某个文件缺失、无法解析，或补丁与基准不一致
+#[cfg(test)]
+pub fn load_config_backup<T>(entry: &BackupEntry) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    if !entry.delta {
+        return super::load_json_config(&entry.path);
+    }
+
+    let config_path = entry
+        .path
+        .parent()
+        .and_then(Path::parent)
+        .map(|dir| dir.join(&entry.filename))
+        .ok_or_else(|| format!("Invalid backup path: {:?}", entry.path))?;
+    let backups = list_config_backups(&config_path)?;
+    let position = backups
+        .iter()
+        .position(|candidate| candidate.path == entry.path)
+        .ok_or_else(|| format!("Backup {:?} no longer exists", entry.path))?;
+    let base = backups[..position]
+        .iter()
+        .rposition(|candidate| !candidate.delta)
+        .filter(|base| backups[base + 1..position].iter().all(|c| c.delta))
+        .ok_or_else(|| format!("No full backup to reconstruct {:?} from", entry.path))?;
+
+    let mut value: Value = super::load_json_config(&backups[base].path)?;
+    for delta in &backups[base + 1..=position] {
+        let patch: Value = super::load_json_config(&delta.path)?;
+        apply_patch_to_value(&mut value, &patch)
+            .map_err(|e| format!("Failed to replay backup delta {:?}: {}", delta.path, e))?;
+    }
+    serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse backup {:?}: {}", entry.path, e))
+}
+
+/// 用备份覆盖配置文件
+///
+/// 增量备份还原后的内容为重新格式化的 JSON
+#[cfg(test)]
+pub fn restore_config_backup(
+    config_path: impl AsRef<Path>,
+    entry: &BackupEntry,
+) -> Result<(), String> {
+    let path = config_path.as_ref();
+    let value: Value = load_config_backup(entry)?;
+    super::save_json_config(&value, path)?;
+    log::info!("Restored {:?} from backup {:?}", path, entry.path);
+    Ok(())
+}
+
+/// 列出配置文件的所有备份（最新的在前）
+#[cfg(test)]
+pub fn list_config_backups(config_path: impl AsRef<Path>) -> Result<Vec<BackupEntry>, String> {
+    let (dir, filename) = split_config_path(config_path.as_ref())?;
+    let mut entries: Vec<BackupEntry> = read_backups(&dir.join(BACKUPS_DIR))?
+        .into_iter()
+        .filter(|entry| entry.filename == filename)
+        .collect();
+    sort_newest_first(&mut entries);
+    Ok(entries)
+}
+
+/// 检测备份中的时钟回拨
+///
+/// # 返回值
+/// 时间戳早于某个序号更小的备份的所有备份（最新的在前）；
+/// 非空时说明备份期间系统时钟曾被调回，按时间戳排序的结果不可靠
+#[cfg(test)]
+pub fn detect_backup_clock_skew(config_path: impl AsRef<Path>) -> Result<Vec<BackupEntry>, String> {
+    let mut entries = list_config_backups(config_path)?;
+    entries.reverse();
+
+    let mut latest: Option<DateTime<Local>> = None;
+    let mut skewed = Vec::new();
+    for entry in entries {
+        match latest {
+            Some(latest) if entry.created_at < latest => skewed.push(entry),
+            _ => latest = Some(entry.created_at),
+        }
+    }
+    skewed.reverse();
+    Ok(skewed)
+}
+
+/// 将配置文件当前内容记录为一个版本
+///
+/// 内容相同的版本只存储一份对象；与最近一个版本相同时不新增记录
+///
+/// # 返回值
+/// - `Ok(Some(hash))`: 版本内容的 sha256
+/// - `Ok(None)`: 配置文件不存在
+pub fn record_config_version(config_path: impl AsRef<Path>) -> Result<Option<String>, String> {
+    let path = config_path.as_ref();
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read(path).map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    let hash = sha256_hex(&content);
+
+    let (dir, filename) = split_config_path(path)?;
+    let versions = dir.join(VERSIONS_DIR);
+    let objects = versions.join("objects");
+    fs::create_dir_all(&objects)
+        .map_err(|e| format!("Failed to create version directory {:?}: {}", objects, e))?;
+
+    let object = objects.join(format!("{}.json", hash));
+    if !object.exists() {
+        atomic_write(&object, &content)?;
+    }
+
+    let index_path = versions.join(format!("{}.index.json", filename));
+    let mut index = read_version_index(&index_path)?;
+    if index.last().map(|entry| entry.hash.as_str()) != Some(hash.as_str()) {
+        index.push(VersionEntry {
+            hash: hash.clone(),
+            saved_at: Local::now(),
+        });
+        write_version_index(&index_path, &index)?;
+    }
+
+    Ok(Some(hash))
+}
+
+/// 列出配置文件的版本记录（按保存时间从旧到新）
+#[cfg(test)]
+pub fn list_config_versions(config_path: impl AsRef<Path>) -> Result<Vec<VersionEntry>, String> {
+    let (dir, filename) = split_config_path(config_path.as_ref())?;
+    read_version_index(
+        &dir.join(VERSIONS_DIR)
+            .join(format!("{}.index.json", filename)),
+    )
+}
+
+/// 加载配置在指定时间的内容
+///
+/// 版本记录是保存后的内容，从 `saved_at` 起生效；备份是保存前被替换的内容，
+/// 在 `created_at` 时失效，从上一个备份的时间起生效（最早的备份覆盖此前的所有时间）；
+/// 当前文件覆盖最新备份之后的时间。版本记录晚于所在区间的起点时，以版本记录为准
+///
+/// # 返回值
+/// - `Ok(T)`: 当时的配置
+/// - `Err(String)`: 该时间没有任何记录覆盖，或记录无法解析
+#[cfg(test)]
+pub fn load_config_at<T>(config_path: impl AsRef<Path>, at: DateTime<Local>) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let (dir, filename) = split_config_path(path)?;
+    let index_path = dir
+        .join(VERSIONS_DIR)
+        .join(format!("{}.index.json", filename));
+
+    // 版本对象可能已被清理，跳过不存在的文件
+    let version = read_version_index(&index_path)?
+        .into_iter()
+        .filter(|entry| entry.saved_at <= at)
+        .map(|entry| {
+            (
+                entry.saved_at,
+                version_object_path(&index_path, &entry.hash),
+            )
+        })
+        .filter(|(_, object)| object.is_file())
+        .max_by_key(|(saved_at, _)| *saved_at);
+    // 区间起点未知（`None`）时，版本记录只在没有备份覆盖该时间时使用
+    let newer_version = |since: Option<DateTime<Local>>| {
+        version
+            .as_ref()
+            .filter(|(saved_at, _)| since.is_none_or(|since| *saved_at > since))
+            .map(|(_, object)| object)
+    };
+
+    let mut backups = list_config_backups(path)?;
+    backups.reverse();
+    if let Some(index) = backups.iter().position(|entry| entry.created_at > at) {
+        let since = index.checked_sub(1).map(|prev| backups[prev].created_at);
+        return match since.and(newer_version(since)) {
+            Some(object) => super::load_json_config(object),
+            None => load_config_backup(&backups[index]),
+        };
+    }
+
+    // 当前文件在 `at` 之后才写入时，`at` 落在最新备份与这次写入之间，版本记录更精确
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Local>::from);
+    let since = backups.last().map(|entry| entry.created_at);
+    match (modified, newer_version(since)) {
+        (Some(modified), _) if modified <= at => super::load_json_config(path),
+        (_, Some(object)) => super::load_json_config(object),
+        (Some(_), None) => super::load_json_config(path),
+        (None, None) => Err(format!(
+            "No version of {:?} exists at {}",
+            path,
+            at.format("%Y-%m-%d %H:%M:%S")
+        )),
+    }
+}
+
+/// 向配置文件的日志追加一条记录
+pub fn append_config_journal(config_path: impl AsRef<Path>, message: &str) -> Result<(), String> {
+    let (dir, filename) = split_config_path(config_path.as_ref())?;
+    let journal = dir.join(JOURNAL_DIR);
+    fs::create_dir_all(&journal)
+        .map_err(|e| format!("Failed to create journal directory {:?}: {}", journal, e))?;
+
+    let log_path = journal.join(format!("{}.log", filename));
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|e| format!("Failed to open journal {:?}: {}", log_path, e))?;
+
+    let line = format!(
+        "{} {}\n",
+        Local::now().to_rfc3339(),
+        message.replace('\n', " ")
+    );
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to write journal {:?}: {}", log_path, e))
+}
+
+/// 清理备份、版本与日志存储
+///
+/// 递归查找 `base_dir` 下的 `.backups`、`.versions`、`.journal` 目录：
+/// - 删除超出保留时长或数量的备份和版本记录
+/// - 删除不再被任何索引引用的版本对象
+/// - 将超出大小上限的日志截断为最新的记录
+/// - 最后按总字节预算从最旧的备份/版本开始删除，直到总大小不超过预算
+///
+/// # 返回值
+/// 回收的字节数和删除的文件数，供 UI 显示"已清理 X MB"
+#[cfg(test)]
+pub fn vacuum_config_storage(
+    base_dir: &Path,
+    policy: &VacuumPolicy,
+) -> Result<VacuumReport, String> {
+    let mut report = VacuumReport::default();
+    let cutoff = policy
+        .max_age
+        .and_then(|age| chrono::Duration::from_std(age).ok())
+        .map(|age| Local::now() - age);
+
+    for dir in storage_dirs(base_dir) {
+        match dir.file_name().and_then(|name| name.to_str()) {
+            Some(BACKUPS_DIR) => vacuum_backups(&dir, policy, cutoff, &mut report)?,
+            Some(VERSIONS_DIR) => vacuum_versions(&dir, policy, cutoff, &mut report)?,
+            Some(JOURNAL_DIR) => vacuum_journal(&dir, policy, &mut report)?,
+            _ => {}
+        }
+    }
+    if let Some(max_bytes) = policy.max_total_bytes {
+        vacuum_to_budget(base_dir, max_bytes, &mut report)?;
+    }
+
+    log::info!(
+        "Config storage vacuum removed {} files, reclaimed {} bytes",
+        report.files_removed,
+        report.bytes_reclaimed
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+fn vacuum_backups(
+    dir: &Path,
+    policy: &VacuumPolicy,
+    cutoff: Option<DateTime<Local>>,
+    report: &mut VacuumReport,
+) -> Result<(), String> {
+    for group in group_backups(read_backups(dir)?) {
+        let mut kept = 0;
+        for (effective_at, entry) in group {
+            let too_old = cutoff.is_some_and(|cutoff| effective_at < cutoff);
+            let too_many = policy.max_count.is_some_and(|max| kept >= max);
+
+            if too_old || too_many {
+                remove_file_counted(&entry.path, report)?;
+            } else {
+                kept += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn vacuum_versions(
+    dir: &Path,
+    policy: &VacuumPolicy,
+    cutoff: Option<DateTime<Local>>,
+    report: &mut VacuumReport,
+) -> Result<(), String> {
+    let mut referenced = std::collections::HashSet::new();
+
+    for index_path in read_dir_files(dir)? {
+        let is_index = index_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".index.json"));
+        if !is_index {
+            continue;
+        }
+
+        let index = read_version_index(&index_path)?;
+        let original_len = index.len();
+        let effective = monotonic_times(index.iter().map(|entry| entry.saved_at));
+        let mut kept: Vec<VersionEntry> = index
+            .into_iter()
+            .zip(effective)
+            .filter(|(_, saved_at)| cutoff.is_none_or(|cutoff| *saved_at >= cutoff))
+            .map(|(entry, _)| entry)
+            .collect();
+        if let Some(max) = policy.max_count {
+            if kept.len() > max {
+                kept.drain(..kept.len() - max);
+            }
+        }
+
+        if kept.len() != original_len {
+            write_version_index(&index_path, &kept)?;
+        }
+        referenced.extend(kept.into_iter().map(|entry| entry.hash));
+    }
+
+    for object in read_dir_files(&dir.join("objects"))? {
+        let hash = object
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        if !referenced.contains(hash) {
+            remove_file_counted(&object, report)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn vacuum_journal(
+    dir: &Path,
+    policy: &VacuumPolicy,
+    report: &mut VacuumReport,
+) -> Result<(), String> {
+    let Some(max_bytes) = policy.max_journal_bytes else {
+        return Ok(());
+    };
+
+    for log_path in read_dir_files(dir)? {
+        let content = fs::read_to_string(&log_path)
+            .map_err(|e| format!("Failed to read journal {:?}: {}", log_path, e))?;
+        if content.len() as u64 <= max_bytes {
+            continue;
+        }
+
+        // 从最新的记录往前保留，直到达到大小上限
+        let mut kept: Vec<&str> = Vec::new();
+        let mut size = 0u64;
+        for line in content.lines().rev() {
+            let line_size = line.len() as u64 + 1;
+            if size + line_size > max_bytes {
+                break;
+            }
+            size += line_size;
+            kept.push(line);
+        }
+        kept.reverse();
+
+        let mut truncated = kept.join("\n");
+        if !truncated.is_empty() {
+            truncated.push('\n');
+        }
+        atomic_write(&log_path, truncated.as_bytes())?;
+        report.bytes_reclaimed += content.len() as u64 - truncated.len() as u64;
+    }
+    Ok(())
+}
+
+/// 按时间排序的预算淘汰候选
+#[cfg(test)]
+enum BudgetCandidate {
+    Backup(BackupEntry),
+    /// 版本索引下标与记录下标
+    Version(usize, usize),
+}
+
+#[cfg(test)]
+fn vacuum_to_budget(
+    base_dir: &Path,
+    max_bytes: u64,
+    report: &mut VacuumReport,
+) -> Result<(), String> {
+    let mut candidates: Vec<(DateTime<Local>, BudgetCandidate)> = Vec::new();
+    let mut indices: Vec<(PathBuf, Vec<VersionEntry>)> = Vec::new();
+    // 版本对象路径 -> (大小, 引用次数)
+    let mut objects: std::collections::HashMap<PathBuf, (u64, usize)> = Default::default();
+    let mut total = 0u64;
+
+    for dir in storage_dirs(base_dir) {
+        match dir.file_name().and_then(|name| name.to_str()) {
+            Some(BACKUPS_DIR) => {
+                for (effective_at, entry) in
+                    group_backups(read_backups(&dir)?).into_iter().flatten()
+                {
+                    total += entry.size;
+                    candidates.push((effective_at, BudgetCandidate::Backup(entry)));
+                }
+            }
+            Some(VERSIONS_DIR) => {
+                for object in read_dir_files(&dir.join("objects"))? {
+                    let size = fs::metadata(&object).map(|m| m.len()).unwrap_or(0);
+                    total += size;
+                    objects.insert(object, (size, 0));
+                }
+                for index_path in read_dir_files(&dir)? {
+                    if !index_path.to_string_lossy().ends_with(".index.json") {
+                        continue;
+                    }
+                    let index = read_version_index(&index_path)?;
+                    let effective = monotonic_times(index.iter().map(|entry| entry.saved_at));
+                    for (position, (entry, saved_at)) in index.iter().zip(effective).enumerate() {
+                        let object = version_object_path(&index_path, &entry.hash);
+                        if let Some((_, refs)) = objects.get_mut(&object) {
+                            *refs += 1;
+                        }
+                        candidates
+                            .push((saved_at, BudgetCandidate::Version(indices.len(), position)));
+                    }
+                    indices.push((index_path, index));
+                }
+            }
+            _ => {}
+        }
+    }
+    if total <= max_bytes {
+        return Ok(());
+    }
+
+    candidates.sort_by_key(|(time, _)| *time);
+    let mut evicted = std::collections::HashSet::new();
+    for (_, candidate) in candidates {
+        if total <= max_bytes {
+            break;
+        }
+        match candidate {
+            BudgetCandidate::Backup(entry) => {
+                remove_file_counted(&entry.path, report)?;
+                total = total.saturating_sub(entry.size);
+            }
+            BudgetCandidate::Version(index, position) => {
+                evicted.insert((index, position));
+                let (index_path, entries) = &indices[index];
+                let object = version_object_path(index_path, &entries[position].hash);
+                let Some((size, refs)) = objects.get_mut(&object) else {
+                    continue;
+                };
+                *refs = refs.saturating_sub(1);
+                if *refs == 0 {
+                    let size = *size;
+                    remove_file_counted(&object, report)?;
+                    total = total.saturating_sub(size);
+                }
+            }
+        }
+    }
+
+    for (index, (index_path, entries)) in indices.into_iter().enumerate() {
+        let original_len = entries.len();
+        let kept: Vec<VersionEntry> = entries
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| !evicted.contains(&(index, *position)))
+            .map(|(_, entry)| entry)
+            .collect();
+        if kept.len() != original_len {
+            write_version_index(&index_path, &kept)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn version_object_path(index_path: &Path, hash: &str) -> PathBuf {
+    let versions = index_path.parent().unwrap_or(Path::new("."));
+    versions.join("objects").join(format!("{}.json", hash))
+}
+
+fn split_config_path(path: &Path) -> Result<(PathBuf, String), String> {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid config path: {:?}", path))?
+        .to_string();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    Ok((dir, filename))
+}
+
+fn backup_path(backups: &Path, filename: &str, stamp: &str, sequence: u64) -> PathBuf {
+    backups.join(format!(
+        "{}.{}.{:06}{}",
+        filename, stamp, sequence, BACKUP_SUFFIX
+    ))
+}
+
+fn read_backups(dir: &Path) -> Result<Vec<BackupEntry>, String> {
+    let mut entries = Vec::new();
+    for path in read_dir_files(dir)? {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let delta = name.ends_with(DELTA_SUFFIX);
+        let Some((filename, stamp, sequence)) = name
+            .strip_suffix(BACKUP_SUFFIX)
+            .or_else(|| name.strip_suffix(DELTA_SUFFIX))
+            .and_then(parse_backup_stem)
+        else {
+            continue;
+        };
+        let Some(created_at) = parse_stamp(stamp) else {
+            continue;
+        };
+
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        entries.push(BackupEntry {
+            filename: filename.to_string(),
+            path,
+            sequence,
+            created_at,
+            size,
+            delta,
+        });
+    }
+    Ok(entries)
+}
+
+/// 拆分 `<filename>.<时间戳>.<序号>`
+fn parse_backup_stem(stem: &str) -> Option<(&str, &str, u64)> {
+    let (rest, sequence) = stem.rsplit_once('.')?;
+    let (filename, stamp) = rest.rsplit_once('.')?;
+    Some((filename, stamp, sequence.parse().ok()?))
+}
+
+fn parse_stamp(stamp: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+fn sort_newest_first(entries: &mut [BackupEntry]) {
+    entries.sort_by(|a, b| {
+        b.sequence
+            .cmp(&a.sequence)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+            .then_with(|| b.path.cmp(&a.path))
+    });
+}
+
+/// 按配置文件分组备份（每组最新的在前），并附上用于清理的有效时间
+#[cfg(test)]
+fn group_backups(entries: Vec<BackupEntry>) -> Vec<Vec<(DateTime<Local>, BackupEntry)>> {
+    let mut groups: std::collections::BTreeMap<String, Vec<BackupEntry>> = Default::default();
+    for entry in entries {
+        groups
+            .entry(entry.filename.clone())
+            .or_default()
+            .push(entry);
+    }
+
+    groups
+        .into_values()
+        .map(|mut group| {
+            sort_newest_first(&mut group);
+            group.reverse();
+            let effective = monotonic_times(group.iter().map(|entry| entry.created_at));
+            let mut group: Vec<_> = effective.into_iter().zip(group).collect();
+            group.reverse();
+            group
+        })
+        .collect()
+}
+
+/// 按记录顺序（从旧到新）取时间的累计最大值
+///
+/// 时钟回拨后记录的时间会早于之前的记录；有效时间保证较新的记录不会比较旧的记录先被清理
+#[cfg(test)]
+fn monotonic_times(times: impl Iterator<Item = DateTime<Local>>) -> Vec<DateTime<Local>> {
+    let mut latest: Option<DateTime<Local>> = None;
+    times
+        .map(|time| {
+            let effective = latest.map_or(time, |latest| latest.max(time));
+            latest = Some(effective);
+            effective
+        })
+        .collect()
+}
+
+fn read_version_index(index_path: &Path) -> Result<Vec<VersionEntry>, String> {
+    if !index_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(index_path)
+        .map_err(|e| format!("Failed to read version index {:?}: {}", index_path, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse version index {:?}: {}", index_path, e))
+}
+
+fn write_version_index(index_path: &Path, index: &[VersionEntry]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize version index: {}", e))?;
+    atomic_write(index_path, content.as_bytes())
+}
+
+fn read_dir_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+/// 查找 `base_dir` 下所有的历史存储目录
+#[cfg(test)]
+fn storage_dirs(base_dir: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(base_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir())
+        .filter(|entry| {
+            [BACKUPS_DIR, VERSIONS_DIR, JOURNAL_DIR]
+                .iter()
+                .any(|name| entry.file_name() == *name)
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+#[cfg(test)]
+fn remove_file_counted(path: &Path, report: &mut VacuumReport) -> Result<(), String> {
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    fs::remove_file(path).map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+    report.bytes_reclaimed += size;
+    report.files_removed += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_records_history_when_enabled() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        super::super::save_json_config(&serde_json::json!({ "v": 0 }), &path).unwrap();
+        assert!(!temp.path().join(BACKUPS_DIR).exists());
+
+        let history = ConfigHistory {
+            backups: true,
+            versions: true,
+            journal: true,
+        };
+        set_config_history(temp.path(), history);
+        assert_eq!(config_history(&path), history);
+        for i in 1..3 {
+            super::super::save_json_config(&serde_json::json!({ "v": i }), &path).unwrap();
+        }
+        set_config_history(temp.path(), ConfigHistory::default());
+
+        let backups = list_config_backups(&path).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(load_config_backup::<Value>(&backups[0]).unwrap()["v"], 1);
+        assert_eq!(list_config_versions(&path).unwrap().len(), 2);
+        let journal =
+            fs::read_to_string(temp.path().join(JOURNAL_DIR).join("settings.json.log")).unwrap();
+        assert_eq!(journal.lines().count(), 2);
+
+        super::super::save_json_config(&serde_json::json!({ "v": 3 }), &path).unwrap();
+        assert_eq!(list_config_backups(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_vacuum_prunes_backups_versions_and_journal() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        for i in 0..3 {
+            fs::write(&path, format!("{{\"v\": {}}}", i)).unwrap();
+            backup_config_file(&path).unwrap().unwrap();
+            record_config_version(&path).unwrap().unwrap();
+            append_config_journal(&path, &format!("saved version {}", i)).unwrap();
+        }
+        assert_eq!(list_config_backups(&path).unwrap().len(), 3);
+        assert_eq!(list_config_versions(&path).unwrap().len(), 3);
+
+        let policy = VacuumPolicy {
+            max_count: Some(1),
+            max_journal_bytes: Some(60),
+            ..Default::default()
+        };
+        let report = vacuum_config_storage(temp.path(), &policy).unwrap();
+
+        // 2 个备份 + 2 个孤立的版本对象
+        assert_eq!(report.files_removed, 4);
+        assert!(report.bytes_reclaimed > 0);
+
+        let backups = list_config_backups(&path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "{\"v\": 2}");
+        assert_eq!(list_config_versions(&path).unwrap().len(), 1);
+
+        let journal =
+            fs::read_to_string(temp.path().join(JOURNAL_DIR).join("settings.json.log")).unwrap();
+        assert!(journal.len() <= 60);
+        assert!(journal.contains("saved version 2"));
+    }
+
+    #[test]
+    fn test_vacuum_enforces_total_byte_budget() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        for i in 0..4 {
+            fs::write(&path, format!("{{\"v\": \"{}\"}}", "x".repeat(100 + i))).unwrap();
+            backup_config_file(&path).unwrap().unwrap();
+            record_config_version(&path).unwrap().unwrap();
+        }
+
+        // 4 个备份 + 4 个版本对象，每个约 110 字节；预算只够保留约 3 个文件
+        let policy = VacuumPolicy {
+            max_total_bytes: Some(350),
+            ..Default::default()
+        };
+        let report = vacuum_config_storage(temp.path(), &policy).unwrap();
+        assert_eq!(report.files_removed, 5);
+
+        let backups = list_config_backups(&path).unwrap();
+        let versions = list_config_versions(&path).unwrap();
+        assert_eq!(backups.len() + versions.len(), 3);
+        assert!(fs::read_to_string(&backups[0].path)
+            .unwrap()
+            .contains(&"x".repeat(103)));
+    }
+
+    #[test]
+    fn test_load_config_at() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        set_config_history(
+            temp.path(),
+            ConfigHistory {
+                backups: true,
+                ..Default::default()
+            },
+        );
+        let pause = || std::thread::sleep(std::time::Duration::from_millis(20));
+
+        let save = |v: i32| super::super::save_json_config(&serde_json::json!({ "v": v }), &path);
+        let before_first = Local::now();
+        pause();
+        save(1).unwrap();
+        pause();
+        let after_first = Local::now();
+        pause();
+        save(2).unwrap();
+        pause();
+        let after_second = Local::now();
+        pause();
+        save(3).unwrap();
+        pause();
+        set_config_history(temp.path(), ConfigHistory::default());
+
+        let version_at =
+            |at| load_config_at::<serde_json::Value>(&path, at).map(|v| v["v"].clone());
+        assert_eq!(version_at(after_first).unwrap(), 1);
+        assert_eq!(version_at(after_second).unwrap(), 2);
+        assert_eq!(version_at(Local::now()).unwrap(), 3);
+        // 第一次保存前文件不存在，最早的备份（v1）覆盖此前的时间
+        assert_eq!(version_at(before_first).unwrap(), 1);
+
+        // 只有版本记录时，以版本生效的时间为准
+        let recorded = temp.path().join("recorded.json");
+        fs::write(&recorded, r#"{"v": 1}"#).unwrap();
+        record_config_version(&recorded).unwrap().unwrap();
+        pause();
+        let between = Local::now();
+        pause();
+        fs::write(&recorded, r#"{"v": 2}"#).unwrap();
+        record_config_version(&recorded).unwrap().unwrap();
+        let recorded_at =
+            |at| load_config_at::<serde_json::Value>(&recorded, at).map(|v| v["v"].clone());
+        assert_eq!(recorded_at(between).unwrap(), 1);
+        assert_eq!(recorded_at(Local::now()).unwrap(), 2);
+        assert!(
+            load_config_at::<serde_json::Value>(temp.path().join("missing.json"), between)
+                .unwrap_err()
+                .starts_with("No version of")
+        );
+    }
+
+    #[test]
+    fn test_backups_are_ordered_and_pruned_by_sequence() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        let now = Local::now();
+
+        // 第三次备份前时钟被调回了两小时
+        for (i, offset) in [0, 0, -120].into_iter().enumerate() {
+            fs::write(&path, format!("{{\"v\": {}}}", i)).unwrap();
+            let backup = backup_config_file(&path).unwrap().unwrap();
+            let stamp = (now + chrono::Duration::minutes(offset)).format(STAMP_FORMAT);
+            let sequence = i as u64 + 1;
+            fs::rename(
+                &backup,
+                backup_path(
+                    backup.parent().unwrap(),
+                    "settings.json",
+                    &stamp.to_string(),
+                    sequence,
+                ),
+            )
+            .unwrap();
+        }
+
+        let backups = list_config_backups(&path).unwrap();
+        let sequences: Vec<u64> = backups.iter().map(|entry| entry.sequence).collect();
+        assert_eq!(sequences, vec![3, 2, 1]);
+        let skewed = detect_backup_clock_skew(&path).unwrap();
+        assert_eq!(skewed.len(), 1);
+        assert_eq!(skewed[0].sequence, 3);
+
+        let policy = VacuumPolicy {
+            max_age: Some(Duration::from_secs(60 * 60)),
+            max_count: Some(2),
+            ..Default::default()
+        };
+        vacuum_config_storage(temp.path(), &policy).unwrap();
+
+        let kept = list_config_backups(&path).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(fs::read_to_string(&kept[0].path).unwrap(), "{\"v\": 2}");
+        assert_eq!(kept[1].sequence, 2);
+    }
+
+    #[test]
+    fn test_delta_backups_replay_from_full_copy() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        let big = "x".repeat(2000);
+
+        for i in 0..5 {
+            fs::write(&path, format!(r#"{{"v": {}, "big": "{}"}}"#, i, big)).unwrap();
+            backup_config_file_delta(&path, 3).unwrap().unwrap();
+        }
+
+        let backups = list_config_backups(&path).unwrap();
+        let kinds: Vec<bool> = backups.iter().map(|entry| entry.delta).collect();
+        // 最新的为完整副本，每 3 个备份至少一个完整副本
+        assert_eq!(kinds, vec![false, true, false, true, true]);
+        assert!(backups[1].size < 200);
+        for (entry, v) in backups.iter().zip([4, 3, 2, 1, 0]) {
+            let value: Value = load_config_backup(entry).unwrap();
+            assert_eq!(value["v"], v);
+            assert_eq!(value["big"], big.as_str());
+        }
+
+        // 清理最旧的备份不影响其余增量备份
+        let policy = VacuumPolicy {
+            max_count: Some(3),
+            ..Default::default()
+        };
+        vacuum_config_storage(temp.path(), &policy).unwrap();
+        let kept = list_config_backups(&path).unwrap();
+        assert!(kept[1].delta);
+        restore_config_backup(&path, &kept[1]).unwrap();
+        let restored: Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(restored["v"], 3);
+    }
+}
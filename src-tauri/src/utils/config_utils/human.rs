@@ -0,0 +1,191 @@
+//! 人类友好的时长与大小写法
+//!
+//! 用户更愿意写 `"timeout": "30s"`、`"maxSize": "16MB"`，程序需要的却是数字。
+//! [`parse_human_values`] 按调用方给出的字段类型，把这些字符串转换为规范的数值
+//! （时长为秒，大小为字节），已经是数字的值保持不变。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+/// 字段的单位类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HumanUnit {
+    /// 时长，规范形式为秒，如 `"1h30m"`、`"500ms"`
+    Duration,
+    /// 字节大小，规范形式为字节，如 `"16MB"`、`"512KiB"`
+    ByteSize,
+}
+
+/// 时长单位（秒）
+const DURATION_UNITS: &[(&str, f64)] = &[
+    ("ms", 0.001),
+    ("s", 1.0),
+    ("m", 60.0),
+    ("h", 3600.0),
+    ("d", 86400.0),
+];
+
+/// 大小单位（字节）：`KB`/`MB` 为十进制，`KiB`/`MiB` 为二进制
+const BYTE_UNITS: &[(&str, f64)] = &[
+    ("b", 1.0),
+    ("kb", 1e3),
+    ("mb", 1e6),
+    ("gb", 1e9),
+    ("tb", 1e12),
+    ("kib", 1024.0),
+    ("mib", 1048576.0),
+    ("gib", 1073741824.0),
+    ("tib", 1099511627776.0),
+];
+
+/// 将时长/大小字符串原地转换为规范数值
+///
+/// # 参数
+/// - `value`: 配置
+/// - `schema`: `(JSON Pointer, 单位类型)` 列表；不存在的指针被忽略
+///
+/// # 返回值
+/// - `Ok(count)`: 转换的值数量
+/// - `Err(String)`: 某个字段的字符串无法解析（错误中包含指针和原值），此时 `value` 未被修改
+///
+/// # 写法
+/// - 时长：数字加单位 `ms`/`s`/`m`/`h`/`d`，可组合（`"1h30m"`）；纯数字视为秒
+/// - 大小：数字加单位 `B`/`KB`/`MB`/`GB`/`TB`（1000 进制）或 `KiB`/`MiB`/`GiB`/`TiB`
+///   （1024 进制），不区分大小写；纯数字视为字节
+pub fn parse_human_values(
+    value: &mut Value,
+    schema: &[(&str, HumanUnit)],
+) -> Result<usize, String> {
+    let mut parsed = Vec::new();
+    for (pointer, unit) in schema {
+        let Some(Value::String(raw)) = value.pointer(pointer) else {
+            continue;
+        };
+        let number = match unit {
+            HumanUnit::Duration => parse_duration(raw),
+            HumanUnit::ByteSize => parse_byte_size(raw),
+        }
+        .ok_or_else(|| {
+            let expected = match unit {
+                HumanUnit::Duration => "a duration like \"30s\" or \"1h30m\"",
+                HumanUnit::ByteSize => "a size like \"16MB\" or \"512KiB\"",
+            };
+            format!(
+                "Invalid value {:?} at {}: expected {}",
+                raw, pointer, expected
+            )
+        })?;
+        parsed.push((*pointer, number));
+    }
+
+    for (pointer, number) in &parsed {
+        if let Some(target) = value.pointer_mut(pointer) {
+            *target = number.clone();
+        }
+    }
+    Ok(parsed.len())
+}
+
+/// 解析时长为秒数；整秒返回整数
+fn parse_duration(raw: &str) -> Option<Value> {
+    let text = raw.trim().to_ascii_lowercase();
+    if let Ok(seconds) = text.parse::<f64>() {
+        return canonical_number(seconds, false);
+    }
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut rest = text.as_str();
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let factor = lookup_unit(DURATION_UNITS, rest[..unit_len].trim())?;
+        rest = rest[unit_len..].trim_start();
+        total += amount * factor;
+    }
+    canonical_number(total, false)
+}
+
+/// 解析大小为字节数（取整）
+fn parse_byte_size(raw: &str) -> Option<Value> {
+    let text = raw.trim().to_ascii_lowercase();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let amount: f64 = text[..split].parse().ok()?;
+    let unit = text[split..].trim();
+    let factor = if unit.is_empty() {
+        1.0
+    } else {
+        lookup_unit(BYTE_UNITS, unit)?
+    };
+    canonical_number(amount * factor, true)
+}
+
+fn lookup_unit(units: &[(&str, f64)], unit: &str) -> Option<f64> {
+    units
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, factor)| *factor)
+}
+
+fn canonical_number(number: f64, round: bool) -> Option<Value> {
+    if !number.is_finite() || number < 0.0 {
+        return None;
+    }
+    let number = if round { number.round() } else { number };
+    if number.fract() == 0.0 && number <= u64::MAX as f64 {
+        Some(Value::from(number as u64))
+    } else {
+        Number::from_f64(number).map(Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_human_values() {
+        let mut value = json!({
+            "timeout": "1h30m",
+            "retryDelay": "500ms",
+            "maxSize": "16MB",
+            "cache": { "limit": "1.5 KiB", "ttl": 45 },
+        });
+        let schema = [
+            ("/timeout", HumanUnit::Duration),
+            ("/retryDelay", HumanUnit::Duration),
+            ("/maxSize", HumanUnit::ByteSize),
+            ("/cache/limit", HumanUnit::ByteSize),
+            ("/cache/ttl", HumanUnit::Duration),
+            ("/missing", HumanUnit::Duration),
+        ];
+
+        assert_eq!(parse_human_values(&mut value, &schema).unwrap(), 4);
+        assert_eq!(
+            value,
+            json!({
+                "timeout": 5400,
+                "retryDelay": 0.5,
+                "maxSize": 16000000,
+                "cache": { "limit": 1536, "ttl": 45 },
+            })
+        );
+
+        let mut bad = json!({ "timeout": "30s", "maxSize": "16 parsecs" });
+        let err = parse_human_values(&mut bad, &schema).unwrap_err();
+        assert!(err.contains("/maxSize"), "{}", err);
+        assert_eq!(bad["timeout"], "30s");
+    }
+}
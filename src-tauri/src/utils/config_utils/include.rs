@@ -0,0 +1,324 @@
+//! 拆分配置的引用关系图
+//!
+//! 配置可以通过以下方式引用其他文件（相对路径相对于引用方所在目录）：
+//! - `"$include": "common.json"` 或 `"$include": ["a.json", "b.json"]`
+//! - `"$ref": "shared.json#/definitions/proxy"`（只有 `#` 之前的文件部分构成依赖，
+//!   纯本地引用 `#/...` 不算）
+//!
+//! [`config_include_graph`] 从根配置出发列出所有引用边，界面据此展示拆分配置的组成，
+//! 也方便排查"改了被引用的文件却没生效"（文件根本没被引用，或引用路径写错）。
+//! [`load_json_config_with_includes`] 展开 `$include`，得到合并后的配置。
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use super::load_json_config;
+use super::merge::deep_merge;
+use super::paths::canonical_config_path;
+
+/// 引用其他配置文件的键
+pub const INCLUDE_KEY: &str = "$include";
+
+/// 计算配置的引用关系图
+///
+/// # 参数
+/// - `root_path`: 根配置文件
+///
+/// # 返回值
+/// - `Ok(edges)`: `(引用方, 被引用文件)` 列表，路径均为规范路径，按发现顺序排列。
+///   被引用但不存在的文件同样列出（不再继续展开），便于发现写错的路径
+/// - `Err(String)`: 根配置或某个被引用文件无法解析
+///
+/// # 特性
+/// - ✅ 每个文件只展开一次，循环引用不会导致死循环（见 [`find_include_cycle`]）
+/// - ✅ 符号链接等别名按同一文件处理
+pub fn config_include_graph(
+    root_path: impl AsRef<Path>,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let root = canonical_config_path(root_path.as_ref());
+    let mut edges = Vec::new();
+    let mut visited = HashSet::new();
+    let mut queue = vec![root];
+
+    while let Some(file) = queue.pop() {
+        if !visited.insert(file.clone()) || !file.is_file() {
+            continue;
+        }
+        let value: Value = load_json_config(&file)?;
+        let base_dir = file.parent().unwrap_or(Path::new(""));
+
+        let mut targets = Vec::new();
+        collect_references(&value, &mut targets);
+        let mut seen = BTreeSet::new();
+        let mut children = Vec::new();
+        for target in targets {
+            let included = canonical_config_path(&base_dir.join(target));
+            if seen.insert(included.clone()) {
+                edges.push((file.clone(), included.clone()));
+                children.push(included);
+            }
+        }
+        // 逆序入栈，使展开顺序与文件中的引用顺序一致
+        queue.extend(children.into_iter().rev());
+    }
+
+    Ok(edges)
+}
+
+/// 加载配置并展开其中的 `$include`
+///
+/// 对象中的 `$include` 按列出的顺序深度合并被引用的文件，再以该对象自身的其他键覆盖；
+/// 被引用的文件中的 `$include` 同样展开。`$ref` 保持原样
+///
+/// # 返回值
+/// - `Ok((value, files))`: 展开后的配置，以及参与合并的文件（规范路径，按首次读取的顺序）
+/// - `Err(String)`: 某个文件无法解析、被引用的文件不存在、`$include` 不是字符串或字符串数组，
+///   或存在循环引用
+pub fn load_json_config_with_includes(
+    config_path: impl AsRef<Path>,
+) -> Result<(Value, Vec<PathBuf>), String> {
+    let mut stack = Vec::new();
+    let mut files = Vec::new();
+    let value = resolve_file(config_path.as_ref(), &mut stack, &mut files)?;
+    Ok((value, files))
+}
+
+fn resolve_file(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    let file = canonical_config_path(path);
+    if let Some(index) = stack.iter().position(|entry| *entry == file) {
+        let cycle: Vec<String> = stack[index..]
+            .iter()
+            .chain(std::iter::once(&file))
+            .map(|entry| format!("{:?}", entry))
+            .collect();
+        return Err(format!("Include cycle detected: {}", cycle.join(" -> ")));
+    }
+    if !file.is_file() {
+        return Err(format!("Included config not found at {:?}", file));
+    }
+
+    let value: Value = load_json_config(&file)?;
+    if !files.contains(&file) {
+        files.push(file.clone());
+    }
+    let base_dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    stack.push(file);
+    let resolved = resolve_value(value, &base_dir, stack, files)?;
+    stack.pop();
+    Ok(resolved)
+}
+
+fn resolve_value(
+    value: Value,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    match value {
+        Value::Object(mut map) => {
+            let targets = match map.remove(INCLUDE_KEY) {
+                None => Vec::new(),
+                Some(Value::String(target)) => vec![target],
+                Some(Value::Array(items)) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::String(target) => Ok(target),
+                        other => Err(format!("Invalid {} entry: {}", INCLUDE_KEY, other)),
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(other) => return Err(format!("Invalid {} value: {}", INCLUDE_KEY, other)),
+            };
+
+            let mut resolved = Value::Object(Map::new());
+            for target in targets {
+                let included = resolve_file(&base_dir.join(target), stack, files)?;
+                deep_merge(&mut resolved, included);
+            }
+            let mut own = Map::new();
+            for (key, child) in map {
+                own.insert(key, resolve_value(child, base_dir, stack, files)?);
+            }
+            deep_merge(&mut resolved, Value::Object(own));
+            Ok(resolved)
+        }
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| resolve_value(item, base_dir, stack, files))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        other => Ok(other),
+    }
+}
+
+/// 查找引用关系图中的循环
+///
+/// # 返回值
+/// - `Some(cycle)`: 循环上的文件，首尾为同一文件（如 `[a, b, a]`）
+/// - `None`: 无循环
+pub fn find_include_cycle(edges: &[(PathBuf, PathBuf)]) -> Option<Vec<PathBuf>> {
+    let mut children: HashMap<&Path, Vec<&Path>> = HashMap::new();
+    for (from, to) in edges {
+        children
+            .entry(from.as_path())
+            .or_default()
+            .push(to.as_path());
+    }
+
+    let mut finished = HashSet::new();
+    for (start, _) in edges {
+        let mut stack = vec![start.as_path()];
+        if let Some(cycle) = visit(start, &children, &mut stack, &mut finished) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+fn visit<'a>(
+    node: &'a Path,
+    children: &HashMap<&'a Path, Vec<&'a Path>>,
+    stack: &mut Vec<&'a Path>,
+    finished: &mut HashSet<&'a Path>,
+) -> Option<Vec<PathBuf>> {
+    if finished.contains(node) {
+        return None;
+    }
+    for &child in children.get(node).into_iter().flatten() {
+        if let Some(index) = stack.iter().position(|&entry| entry == child) {
+            let mut cycle: Vec<PathBuf> = stack[index..].iter().map(|p| p.to_path_buf()).collect();
+            cycle.push(child.to_path_buf());
+            return Some(cycle);
+        }
+        stack.push(child);
+        if let Some(cycle) = visit(child, children, stack, finished) {
+            return Some(cycle);
+        }
+        stack.pop();
+    }
+    finished.insert(node);
+    None
+}
+
+fn collect_references<'a>(value: &'a Value, targets: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match (key.as_str(), child) {
+                    (INCLUDE_KEY, Value::String(target)) => targets.push(target),
+                    (INCLUDE_KEY, Value::Array(items)) => {
+                        targets.extend(items.iter().filter_map(Value::as_str))
+                    }
+                    ("$ref", Value::String(reference)) => {
+                        let file = reference.split('#').next().unwrap_or_default();
+                        if !file.is_empty() {
+                            targets.push(file);
+                        }
+                    }
+                    _ => collect_references(child, targets),
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_references(item, targets)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_config_include_graph_with_cycle() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = canonical_config_path(temp.path());
+        fs::create_dir(dir.join("parts")).unwrap();
+        fs::write(
+            dir.join("settings.json"),
+            r##"{"$include": ["parts/env.json", "missing.json"], "proxy": {"$ref": "parts/shared.json#/proxy"}, "local": {"$ref": "#/proxy"}}"##,
+        )
+        .unwrap();
+        fs::write(dir.join("parts/env.json"), r#"{"$include": "shared.json"}"#).unwrap();
+        fs::write(
+            dir.join("parts/shared.json"),
+            r#"{"$include": "../settings.json"}"#,
+        )
+        .unwrap();
+
+        let edges = config_include_graph(dir.join("settings.json")).unwrap();
+        let settings = dir.join("settings.json");
+        let env = dir.join("parts/env.json");
+        let shared = dir.join("parts/shared.json");
+        assert_eq!(
+            edges,
+            vec![
+                (settings.clone(), env.clone()),
+                (settings.clone(), dir.join("missing.json")),
+                (settings.clone(), shared.clone()),
+                (env.clone(), shared.clone()),
+                (shared.clone(), settings.clone()),
+            ]
+        );
+        assert_eq!(
+            find_include_cycle(&edges),
+            Some(vec![settings.clone(), env, shared, settings])
+        );
+        assert_eq!(find_include_cycle(&edges[..4]), None);
+    }
+
+    #[test]
+    fn test_load_json_config_with_includes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = canonical_config_path(temp.path());
+        fs::create_dir(dir.join("parts")).unwrap();
+        fs::write(
+            dir.join("settings.json"),
+            r#"{"$include": ["parts/env.json"], "env": {"B": "own"}, "model": "opus"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("parts/env.json"),
+            r#"{"$include": "base.json", "env": {"A": "1", "B": "2"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("parts/base.json"),
+            r#"{"model": "sonnet", "theme": "dark"}"#,
+        )
+        .unwrap();
+
+        let (value, files) = load_json_config_with_includes(dir.join("settings.json")).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "model": "opus",
+                "theme": "dark",
+                "env": { "A": "1", "B": "own" },
+            })
+        );
+        assert_eq!(
+            files,
+            vec![
+                dir.join("settings.json"),
+                dir.join("parts/env.json"),
+                dir.join("parts/base.json"),
+            ]
+        );
+
+        fs::write(
+            dir.join("parts/base.json"),
+            r#"{"$include": "../settings.json"}"#,
+        )
+        .unwrap();
+        let err = load_json_config_with_includes(dir.join("settings.json")).unwrap_err();
+        assert!(err.starts_with("Include cycle detected"), "{}", err);
+    }
+}
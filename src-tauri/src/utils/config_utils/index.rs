@@ -0,0 +1,300 @@
+//! 设置搜索索引
+//!
+//! 设置页的搜索框按键名或值实时过滤设置。[`ConfigIndex`] 是由配置派生的倒排索引
+//! （词 -> 叶子 JSON Pointer）；配置变化时按 [`diff_json_configs`] 的结果只更新
+//! 变化的子树，大型配置无需每次全量重建。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::diff::{diff_json_configs, ConfigChange};
+use super::load_json_config;
+use super::pointer::escape_pointer_token;
+use super::watch::{spawn_watcher, ConfigWatcher};
+
+/// 一条搜索结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSearchHit {
+    /// 叶子值的 JSON Pointer
+    pub pointer: String,
+    /// 值的文本（字符串不带引号，其他值为 JSON 文本）
+    pub value: String,
+}
+
+/// 设置的倒排索引
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigIndex {
+    /// 叶子 JSON Pointer -> 值文本
+    entries: BTreeMap<String, String>,
+    /// 小写词 -> 包含该词的叶子
+    terms: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// 为配置建立搜索索引
+///
+/// 标量（包括数组中的标量）为叶子；键名和值都按非字母数字字符和驼峰拆分为词
+pub fn build_config_index(value: &Value) -> ConfigIndex {
+    let mut index = ConfigIndex::default();
+    index.insert_subtree("", value);
+    index
+}
+
+impl ConfigIndex {
+    /// 搜索设置
+    ///
+    /// 查询按空白拆分为词，每个词都须是某个键名片段或值中词的前缀（不区分大小写）
+    ///
+    /// # 返回值
+    /// 按 JSON Pointer 排序的结果；查询为空时返回空列表
+    pub fn search(&self, query: &str) -> Vec<ConfigSearchHit> {
+        let mut matched: Option<BTreeSet<&String>> = None;
+        for word in query.split_whitespace().map(str::to_lowercase) {
+            let hits: BTreeSet<&String> = self
+                .terms
+                .range(word.clone()..)
+                .take_while(|(term, _)| term.starts_with(&word))
+                .flat_map(|(_, pointers)| pointers)
+                .collect();
+            matched = Some(match matched {
+                Some(previous) => previous.intersection(&hits).copied().collect(),
+                None => hits,
+            });
+        }
+
+        matched
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pointer| ConfigSearchHit {
+                pointer: pointer.clone(),
+                value: self.entries[pointer].clone(),
+            })
+            .collect()
+    }
+
+    /// 按两次配置之间的差异更新索引
+    ///
+    /// # 返回值
+    /// 应用的差异数量
+    pub fn update(&mut self, old: &Value, new: &Value) -> usize {
+        let changes = diff_json_configs(old, new);
+        self.apply_changes(&changes);
+        changes.len()
+    }
+
+    /// 应用 [`diff_json_configs`] 产生的差异：移除变化位置下的旧叶子，索引新值
+    pub fn apply_changes(&mut self, changes: &[ConfigChange]) {
+        for change in changes {
+            self.remove_subtree(&change.pointer);
+            if let Some(value) = &change.new_value {
+                self.insert_subtree(&change.pointer, value);
+            }
+        }
+    }
+
+    /// 索引中的叶子数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn insert_subtree(&mut self, pointer: &str, value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+                    self.insert_subtree(&child_pointer, child);
+                }
+            }
+            Value::Array(items) => {
+                for (position, item) in items.iter().enumerate() {
+                    self.insert_subtree(&format!("{}/{}", pointer, position), item);
+                }
+            }
+            scalar => {
+                let text = match scalar {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                for term in entry_terms(pointer, &text) {
+                    self.terms
+                        .entry(term)
+                        .or_default()
+                        .insert(pointer.to_string());
+                }
+                self.entries.insert(pointer.to_string(), text);
+            }
+        }
+    }
+
+    fn remove_subtree(&mut self, pointer: &str) {
+        let prefix = format!("{}/", pointer);
+        let mut removed: Vec<String> = self
+            .entries
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| key.clone())
+            .collect();
+        if self.entries.contains_key(pointer) {
+            removed.push(pointer.to_string());
+        }
+
+        for key in removed {
+            let Some(text) = self.entries.remove(&key) else {
+                continue;
+            };
+            for term in entry_terms(&key, &text) {
+                if let Some(pointers) = self.terms.get_mut(&term) {
+                    pointers.remove(&key);
+                    if pointers.is_empty() {
+                        self.terms.remove(&term);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 监听配置文件，在后台维护其搜索索引
+///
+/// 启动后立即在监听线程中建立索引，之后每次变化只更新差异部分；
+/// 加载失败（如文件正被写入）时保留现有索引，下次变化时重试
+///
+/// # 返回值
+/// `(共享的索引, 监听器)`；监听器 drop 时停止更新
+pub fn watch_config_index(
+    config_path: impl AsRef<Path>,
+    interval: Duration,
+) -> (Arc<Mutex<ConfigIndex>>, ConfigWatcher) {
+    let index = Arc::new(Mutex::new(ConfigIndex::default()));
+    let shared = Arc::clone(&index);
+    let mut last = Value::Null;
+
+    let watcher = spawn_watcher(
+        config_path.as_ref().to_path_buf(),
+        interval,
+        true,
+        move |path| match load_json_config::<Value>(path) {
+            Ok(value) => {
+                let changes = shared
+                    .lock()
+                    .map(|mut index| index.update(&last, &value))
+                    .unwrap_or_default();
+                log::debug!("Updated config index for {:?}: {} change(s)", path, changes);
+                last = value;
+            }
+            Err(e) => log::warn!("Failed to update config index for {:?}: {}", path, e),
+        },
+    );
+    (index, watcher)
+}
+
+/// 键名片段和值中的词（小写），驼峰词同时保留整体和各部分
+fn entry_terms(pointer: &str, text: &str) -> BTreeSet<String> {
+    let mut terms = BTreeSet::new();
+    let segments = pointer
+        .split('/')
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"));
+    for source in segments.chain(std::iter::once(text.to_string())) {
+        for word in source.split(|c: char| !c.is_alphanumeric()) {
+            if word.is_empty() {
+                continue;
+            }
+            terms.insert(word.to_lowercase());
+            terms.extend(camel_case_parts(word));
+        }
+    }
+    terms
+}
+
+fn camel_case_parts(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in word.chars() {
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            parts.push(current.to_lowercase());
+            current.clear();
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        parts.push(current.to_lowercase());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pointers(hits: Vec<ConfigSearchHit>) -> Vec<String> {
+        hits.into_iter().map(|hit| hit.pointer).collect()
+    }
+
+    #[test]
+    fn test_build_config_index_and_search() {
+        assert!(build_config_index(&json!({})).is_empty());
+        let index = build_config_index(&json!({
+            "model": "claude-opus",
+            "git": { "autoFetch": true, "remote": "origin" },
+            "permissions": { "allow": ["Bash(git status)", "Read"] },
+        }));
+        assert_eq!(index.len(), 5);
+
+        assert_eq!(pointers(index.search("fetch")), vec!["/git/autoFetch"]);
+        assert_eq!(
+            pointers(index.search("GIT")),
+            vec!["/git/autoFetch", "/git/remote", "/permissions/allow/0"]
+        );
+        assert_eq!(pointers(index.search("git ori")), vec!["/git/remote"]);
+        assert_eq!(index.search("opus")[0].value, "claude-opus");
+        assert!(index.search("").is_empty());
+    }
+
+    #[test]
+    fn test_config_index_incremental_update() {
+        let old = json!({ "git": { "remote": "origin", "branch": "main" }, "tools": ["a", "b"] });
+        let new = json!({ "git": { "remote": "upstream" }, "tools": ["a"], "theme": "dark" });
+
+        let mut index = build_config_index(&old);
+        assert!(index.update(&old, &new) > 0);
+        assert_eq!(index, build_config_index(&new));
+        assert!(index.search("origin").is_empty());
+        assert_eq!(pointers(index.search("upstream")), vec!["/git/remote"]);
+    }
+
+    #[test]
+    fn test_watch_config_index() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "opus"}"#).unwrap();
+
+        let (index, watcher) = watch_config_index(&path, Duration::from_millis(10));
+        let wait_for = |pointer: &str, term: &str| {
+            for _ in 0..500 {
+                if pointers(index.lock().unwrap().search(term)) == vec![pointer.to_string()] {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            panic!("index never found {:?} at {}", term, pointer);
+        };
+        wait_for("/model", "opus");
+
+        std::fs::write(&path, r#"{"theme": "dark-mode"}"#).unwrap();
+        wait_for("/theme", "dark");
+        assert!(index.lock().unwrap().search("opus").is_empty());
+        watcher.stop();
+    }
+}
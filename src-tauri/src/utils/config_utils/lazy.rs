@@ -0,0 +1,123 @@
+//! 按需反序列化的大型配置
+//!
+//! 只需要大文件中少数几个顶层键时，完整反序列化为大结构体很浪费。
+//! [`LazyConfig`] 只扫描一次顶层对象，把每个键的值保留为原始 JSON 文本（`RawValue`），
+//! 读取某个键时才把它反序列化为目标类型。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use super::decode_config_bytes;
+
+/// 顶层键按需反序列化的配置
+#[derive(Debug)]
+pub struct LazyConfig {
+    path: PathBuf,
+    fields: BTreeMap<String, Box<RawValue>>,
+}
+
+impl LazyConfig {
+    /// 读取配置文件并拆分顶层键（不解析各键的值）
+    ///
+    /// # 返回值
+    /// - `Ok(LazyConfig)`: 文件不存在时为空配置
+    /// - `Err(String)`: 读取失败，或内容不是 JSON 对象
+    pub fn load(config_path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = config_path.as_ref().to_path_buf();
+        if !path.exists() {
+            return Ok(LazyConfig {
+                path,
+                fields: BTreeMap::new(),
+            });
+        }
+
+        let bytes =
+            fs::read(&path).map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+        let (content, _) = decode_config_bytes(&bytes)
+            .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+        let fields = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+        Ok(LazyConfig { path, fields })
+    }
+
+    /// 配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 所有顶层键（按字典序）
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(String::as_str)
+    }
+
+    /// 是否包含顶层键
+    pub fn contains(&self, key: &str) -> bool {
+        self.fields.contains_key(key)
+    }
+
+    /// 顶层键的原始 JSON 文本
+    pub fn raw(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|raw| raw.get())
+    }
+
+    /// 将顶层键反序列化为指定类型
+    ///
+    /// # 返回值
+    /// - `Ok(Some(T))`: 键存在且类型匹配
+    /// - `Ok(None)`: 键不存在
+    /// - `Err(String)`: 键的值无法解析为 `T`
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>, String>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.fields
+            .get(key)
+            .map(|raw| {
+                serde_json::from_str(raw.get()).map_err(|e| {
+                    format!(
+                        "Failed to parse {:?} from config {:?}: {}",
+                        key, self.path, e
+                    )
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_config_reads_single_fields() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"model": "opus", "history": [{"a": 1}, {"b": [2, 3]}], "count": "nope"}"#,
+        )
+        .unwrap();
+
+        let config = LazyConfig::load(&path).unwrap();
+        assert_eq!(config.path(), path);
+        assert_eq!(
+            config.keys().collect::<Vec<_>>(),
+            ["count", "history", "model"]
+        );
+        assert_eq!(
+            config.get::<String>("model").unwrap().as_deref(),
+            Some("opus")
+        );
+        assert_eq!(config.raw("history"), Some(r#"[{"a": 1}, {"b": [2, 3]}]"#));
+        assert_eq!(config.get::<u32>("missing").unwrap(), None);
+        assert!(config.get::<u32>("count").is_err());
+
+        assert!(!LazyConfig::load(temp.path().join("none.json"))
+            .unwrap()
+            .contains("model"));
+    }
+}
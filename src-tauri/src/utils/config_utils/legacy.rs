@@ -0,0 +1,86 @@
+//! 旧版配置文件名迁移
+//!
+//! 旧版本使用 `config.json`，新版本改为 `settings.json`。
+//! 主文件缺失而旧文件存在时，读取旧文件并以新文件名保存，升级后设置不会丢失。
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{load_json_config, save_json_config};
+
+/// 加载配置，主文件不存在时回退到旧文件名并迁移
+///
+/// # 参数
+/// - `config_path`: 新的配置文件路径
+/// - `legacy_names`: 旧文件名列表（与主文件同目录，按顺序查找）
+/// - `remove_legacy`: 迁移成功后是否删除旧文件
+///
+/// # 特性
+/// - ✅ 以原始 JSON 迁移，结构体未声明的键不会丢失
+/// - ✅ 主文件和旧文件都不存在时返回 `T::default()`
+pub fn load_json_config_with_legacy<T>(
+    config_path: impl AsRef<Path>,
+    legacy_names: &[&str],
+    remove_legacy: bool,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    if path.exists() {
+        return load_json_config(path);
+    }
+
+    let Some(dir) = path.parent() else {
+        return load_json_config(path);
+    };
+
+    for name in legacy_names {
+        let legacy_path = dir.join(name);
+        if !legacy_path.is_file() {
+            continue;
+        }
+
+        let raw: Value = load_json_config(&legacy_path)?;
+        save_json_config(&raw, path)?;
+        log::info!("Migrated legacy config {:?} to {:?}", legacy_path, path);
+
+        if remove_legacy {
+            if let Err(e) = fs::remove_file(&legacy_path) {
+                log::warn!("Failed to remove legacy config {:?}: {}", legacy_path, e);
+            }
+        }
+
+        return serde_json::from_value(raw)
+            .map_err(|e| format!("Failed to parse config from {:?}: {}", legacy_path, e));
+    }
+
+    Ok(T::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_legacy_config_is_migrated() {
+        let temp = tempfile::tempdir().unwrap();
+        let legacy = temp.path().join("config.json");
+        let path = temp.path().join("settings.json");
+        fs::write(&legacy, r#"{"model": "opus", "extra": 1}"#).unwrap();
+
+        let loaded: Value = load_json_config_with_legacy(&path, &["config.json"], true).unwrap();
+        assert_eq!(loaded, json!({ "model": "opus", "extra": 1 }));
+        assert!(path.exists());
+        assert!(!legacy.exists());
+
+        let missing: Value =
+            load_json_config_with_legacy(temp.path().join("none.json"), &["nope.json"], false)
+                .unwrap();
+        assert!(missing.is_null());
+    }
+}
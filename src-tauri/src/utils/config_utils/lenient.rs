@@ -0,0 +1,504 @@
+//! 宽松加载：纠正手动编辑常见的类型错误
+//!
+//! 用户经常把布尔值写成 `"true"`、把数字写成 `"30"`，严格的 serde 会直接拒绝。
+//! 宽松加载在反序列化前修正这些明显的类型不匹配；严格的 [`load_json_config`]
+//! 仍是默认行为。[`validate_enum_fields`] 则把枚举字段的拼写错误变成带建议的错误信息。
+//!
+//! [`load_json_config`]: super::load_json_config
+
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Number, Value};
+
+use super::load_json_config;
+use super::pointer::escape_pointer_token;
+
+/// 宽松加载配置
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `schema`: 可选的 JSON Schema（如 [`export_config_schema`] 的输出）
+///
+/// # 特性
+/// - ✅ 提供 Schema 时，只在 Schema 要求数字/布尔值的位置转换字符串
+/// - ✅ 未提供 Schema 时先严格解析，失败后只转换 serde 报错位置上的字符串并重试，
+///   其他字符串（如 `"label": "42"`、`"version": "1.0"`）保持不变
+/// - ✅ 重试仍失败时返回原始的解析错误
+///
+/// [`export_config_schema`]: super::export_config_schema
+pub fn load_json_config_lenient<T>(
+    config_path: impl AsRef<Path>,
+    schema: Option<&Value>,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let mut raw: Value = load_json_config(path)?;
+    let original = raw.clone();
+    let (result, coerced) = match schema {
+        Some(schema) => {
+            let coerced = coerce_config_types(&mut raw, schema);
+            (serde_json::from_value::<T>(raw).map_err(|_| ()), coerced)
+        }
+        None => coerce_failing_paths(&mut raw),
+    };
+    if coerced > 0 {
+        log::info!("Coerced {} mistyped value(s) in {:?}", coerced, path);
+    }
+
+    result.map_err(|_| {
+        let err = serde_json::from_value::<T>(original).err();
+        format!(
+            "Failed to parse config from {:?}: {}",
+            path,
+            err.map(|e| e.to_string()).unwrap_or_default()
+        )
+    })
+}
+
+/// 按 Schema 原地修正类型不匹配的值，返回修正的数量
+///
+/// 只转换 Schema 要求数字/布尔值（且不接受字符串）位置上的字符串。
+pub fn coerce_config_types(value: &mut Value, schema: &Value) -> usize {
+    coerce_with_schema(value, schema, schema)
+}
+
+/// 将指定字段的各种布尔写法统一为 JSON 布尔值
+///
+/// # 参数
+/// - `value`: 配置
+/// - `pointers`: 布尔设置的 JSON Pointer 列表；不存在或为 `null` 的字段被忽略
+///
+/// # 返回值
+/// - `Ok(count)`: 转换的值数量（已是布尔值的不计）
+/// - `Err(Vec<String>)`: 每个无法识别的字段一条错误；此时 `value` 未被修改
+///
+/// # 识别的写法（不区分大小写）
+/// - 真：`true`、`"true"`、`"yes"`、`"y"`、`"on"`、`"1"`、`1`
+/// - 假：`false`、`"false"`、`"no"`、`"n"`、`"off"`、`"0"`、`0`
+pub fn normalize_booleans(value: &mut Value, pointers: &[&str]) -> Result<usize, Vec<String>> {
+    let mut converted = Vec::new();
+    let mut errors = Vec::new();
+    for pointer in pointers {
+        let flag = match value.pointer(pointer) {
+            None | Some(Value::Null) | Some(Value::Bool(_)) => continue,
+            Some(Value::String(raw)) => match raw.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "y" | "on" | "1" => Some(true),
+                "false" | "no" | "n" | "off" | "0" => Some(false),
+                _ => None,
+            },
+            Some(Value::Number(number)) => match number.as_i64() {
+                Some(1) => Some(true),
+                Some(0) => Some(false),
+                _ => None,
+            },
+            Some(_) => None,
+        };
+        match flag {
+            Some(flag) => converted.push((*pointer, flag)),
+            None => errors.push(format!(
+                "Cannot interpret {} at {} as a boolean",
+                value.pointer(pointer).unwrap_or(&Value::Null),
+                pointer
+            )),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    for (pointer, flag) in &converted {
+        if let Some(target) = value.pointer_mut(pointer) {
+            *target = Value::Bool(*flag);
+        }
+    }
+    Ok(converted.len())
+}
+
+/// 检查枚举字段的取值，并统一大小写
+///
+/// # 参数
+/// - `value`: 配置
+/// - `fields`: `(JSON Pointer, 允许的取值)` 列表；不存在或为 `null` 的字段被忽略
+///
+/// # 返回值
+/// - `Ok(count)`: 被统一为规范写法的值数量（如 `" OPUS"` -> `"opus"`）
+/// - `Err(Vec<String>)`: 每个取值无效的字段一条错误，拼写接近某个取值时给出建议
+///   （`did you mean "model"?`）；此时 `value` 未被修改
+pub fn validate_enum_fields(
+    value: &mut Value,
+    fields: &[(&str, &[&str])],
+) -> Result<usize, Vec<String>> {
+    let mut normalized = Vec::new();
+    let mut errors = Vec::new();
+    for (pointer, allowed) in fields {
+        let raw = match value.pointer(pointer) {
+            None | Some(Value::Null) => continue,
+            Some(Value::String(raw)) => raw,
+            Some(other) => {
+                errors.push(format!(
+                    "Invalid value {} at {}: expected one of {}",
+                    other,
+                    pointer,
+                    quote_all(allowed)
+                ));
+                continue;
+            }
+        };
+        if allowed.contains(&raw.as_str()) {
+            continue;
+        }
+
+        let wanted = raw.trim().to_lowercase();
+        match allowed
+            .iter()
+            .find(|candidate| candidate.to_lowercase() == wanted)
+        {
+            Some(canonical) => normalized.push((*pointer, *canonical)),
+            None => {
+                let mut message = format!(
+                    "Invalid value {:?} at {}: expected one of {}",
+                    raw,
+                    pointer,
+                    quote_all(allowed)
+                );
+                if let Some(suggestion) = closest_value(&wanted, allowed) {
+                    message.push_str(&format!("; did you mean {:?}?", suggestion));
+                }
+                errors.push(message);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    for (pointer, canonical) in &normalized {
+        if let Some(target) = value.pointer_mut(pointer) {
+            *target = Value::String(canonical.to_string());
+        }
+    }
+    Ok(normalized.len())
+}
+
+fn quote_all(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|value| format!("{:?}", value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 编辑距离最小且足够接近的取值（距离不超过 2 或取值长度的三分之一）
+fn closest_value<'a>(wanted: &str, allowed: &[&'a str]) -> Option<&'a str> {
+    allowed
+        .iter()
+        .map(|candidate| (edit_distance(wanted, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.chars().count() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein 距离
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn coerce_with_schema(value: &mut Value, schema: &Value, root: &Value) -> usize {
+    let schema = resolve_ref(schema, root);
+
+    // schemars 为带注释的引用和 Option 生成 allOf/anyOf 包装
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            return variants
+                .iter()
+                .map(|variant| coerce_with_schema(value, variant, root))
+                .find(|count| *count > 0)
+                .unwrap_or(0);
+        }
+    }
+
+    match value {
+        Value::String(raw) => {
+            let coerced = schema_types(schema).into_iter().find_map(|ty| match ty {
+                "boolean" => parse_bool(raw),
+                "integer" => raw.trim().parse::<i64>().ok().map(Value::from),
+                "number" => parse_number(raw),
+                _ => None,
+            });
+            match coerced {
+                Some(coerced) if !schema_types(schema).contains(&"string") => {
+                    *value = coerced;
+                    1
+                }
+                _ => 0,
+            }
+        }
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+            map.iter_mut()
+                .map(|(key, child)| {
+                    match properties.and_then(|props| props.get(key)).or(additional) {
+                        Some(child_schema) => coerce_with_schema(child, child_schema, root),
+                        None => 0,
+                    }
+                })
+                .sum()
+        }
+        Value::Array(items) => match schema.get("items") {
+            Some(item_schema) => items
+                .iter_mut()
+                .map(|item| coerce_with_schema(item, item_schema, root))
+                .sum(),
+            None => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// 反复反序列化，每次只转换 serde 报错位置上的数字/布尔字符串
+///
+/// # 返回值
+/// `(反序列化结果, 转换的数量)`；报错位置不是可转换的字符串时停止
+fn coerce_failing_paths<T>(value: &mut Value) -> (Result<T, ()>, usize)
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let mut coerced = 0;
+    loop {
+        let err = match serde_path_to_error::deserialize::<_, T>(&*value) {
+            Ok(config) => return (Ok(config), coerced),
+            Err(err) => err,
+        };
+        let fixed = error_pointer(err.path())
+            .and_then(|pointer| value.pointer_mut(&pointer))
+            .and_then(|target| {
+                let replacement = target
+                    .as_str()
+                    .and_then(|raw| parse_bool(raw).or_else(|| parse_number(raw)))?;
+                *target = replacement;
+                Some(())
+            });
+        if fixed.is_none() {
+            return (Err(()), coerced);
+        }
+        coerced += 1;
+    }
+}
+
+/// 把 serde 的错误路径转换为 JSON Pointer；路径无法定位到具体值时返回 `None`
+fn error_pointer(path: &serde_path_to_error::Path) -> Option<String> {
+    use serde_path_to_error::Segment;
+
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        pointer.push('/');
+        match segment {
+            Segment::Seq { index } => pointer.push_str(&index.to_string()),
+            Segment::Map { key } => pointer.push_str(&escape_pointer_token(key)),
+            Segment::Enum { .. } | Segment::Unknown => return None,
+        }
+    }
+    Some(pointer)
+}
+
+/// 解析本地 `$ref`（如 `#/definitions/ProxyConfig`）
+fn resolve_ref<'a>(schema: &'a Value, root: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+        .unwrap_or(schema)
+}
+
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_bool(raw: &str) -> Option<Value> {
+    match raw.trim() {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+fn parse_number(raw: &str) -> Option<Value> {
+    let trimmed = raw.trim();
+    if let Ok(int) = trimmed.parse::<i64>() {
+        return Some(Value::from(int));
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .and_then(Number::from_f64)
+        .map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::export_config_schema;
+    use super::*;
+    use schemars::JsonSchema;
+    use std::fs;
+
+    #[derive(Debug, Default, Deserialize, JsonSchema, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct LenientConfig {
+        enabled: bool,
+        timeout_seconds: Option<u32>,
+        label: String,
+        #[serde(default)]
+        proxy: LenientProxy,
+    }
+
+    #[derive(Debug, Default, Deserialize, JsonSchema, PartialEq)]
+    struct LenientProxy {
+        port: u16,
+    }
+
+    #[test]
+    fn test_lenient_load_with_schema() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"enabled": "true", "timeoutSeconds": "30", "label": "42", "proxy": {"port": "8080"}}"#,
+        )
+        .unwrap();
+
+        assert!(load_json_config::<LenientConfig>(&path).is_err());
+
+        let schema = export_config_schema::<LenientConfig>();
+        let config: LenientConfig = load_json_config_lenient(&path, Some(&schema)).unwrap();
+        assert_eq!(
+            config,
+            LenientConfig {
+                enabled: true,
+                timeout_seconds: Some(30),
+                label: "42".to_string(),
+                proxy: LenientProxy { port: 8080 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_lenient_load_without_schema() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, r#"{"enabled": "false", "label": "ok"}"#).unwrap();
+
+        let config: LenientConfig = load_json_config_lenient(&path, None).unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.label, "ok");
+
+        // 只转换报错的字段，本就是字符串的数字保持原样
+        fs::write(
+            &path,
+            r#"{"enabled": "true", "timeoutSeconds": "30", "label": "42"}"#,
+        )
+        .unwrap();
+        let config: LenientConfig = load_json_config_lenient(&path, None).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.timeout_seconds, Some(30));
+        assert_eq!(config.label, "42");
+
+        fs::write(&path, r#"{"enabled": "maybe", "label": "ok"}"#).unwrap();
+        let err = load_json_config_lenient::<LenientConfig>(&path, None).unwrap_err();
+        assert!(err.contains("invalid type"), "{}", err);
+    }
+
+    #[test]
+    fn test_normalize_booleans() {
+        let mut value = serde_json::json!({
+            "verbose": "Yes",
+            "autoUpdate": 0,
+            "telemetry": false,
+            "proxy": { "enabled": " on " },
+        });
+        let pointers = [
+            "/verbose",
+            "/autoUpdate",
+            "/telemetry",
+            "/proxy/enabled",
+            "/missing",
+        ];
+        assert_eq!(normalize_booleans(&mut value, &pointers).unwrap(), 3);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "verbose": true,
+                "autoUpdate": false,
+                "telemetry": false,
+                "proxy": { "enabled": true },
+            })
+        );
+
+        let mut bad =
+            serde_json::json!({ "verbose": "sometimes", "autoUpdate": "no", "telemetry": 2 });
+        assert_eq!(
+            normalize_booleans(&mut bad, &pointers).unwrap_err(),
+            vec![
+                "Cannot interpret \"sometimes\" at /verbose as a boolean",
+                "Cannot interpret 2 at /telemetry as a boolean",
+            ]
+        );
+        assert_eq!(bad["autoUpdate"], "no");
+    }
+
+    #[test]
+    fn test_validate_enum_fields() {
+        const MODELS: &[&str] = &["opus", "sonnet", "haiku"];
+        const MODES: &[&str] = &["default", "acceptEdits", "plan"];
+        let fields = [
+            ("/model", MODELS),
+            ("/permissions/defaultMode", MODES),
+            ("/missing", MODES),
+        ];
+
+        let mut value =
+            serde_json::json!({ "model": " Opus", "permissions": { "defaultMode": "plan" } });
+        assert_eq!(validate_enum_fields(&mut value, &fields), Ok(1));
+        assert_eq!(value["model"], "opus");
+
+        let mut mixed_case =
+            serde_json::json!({ "model": "OPUS", "permissions": { "defaultMode": "acceptedits" } });
+        assert_eq!(validate_enum_fields(&mut mixed_case, &fields), Ok(2));
+        assert_eq!(mixed_case["permissions"]["defaultMode"], "acceptEdits");
+
+        let mut bad =
+            serde_json::json!({ "model": "sonet", "permissions": { "defaultMode": "yolo" } });
+        let errors = validate_enum_fields(&mut bad, &fields).unwrap_err();
+        assert_eq!(
+            errors[0],
+            r#"Invalid value "sonet" at /model: expected one of "opus", "sonnet", "haiku"; did you mean "sonnet"?"#
+        );
+        assert!(!errors[1].contains("did you mean"), "{}", errors[1]);
+
+        let mut typed = serde_json::json!({ "model": 3 });
+        assert!(validate_enum_fields(&mut typed, &fields).is_err());
+        assert_eq!(edit_distance("modle", "model"), 2);
+    }
+}
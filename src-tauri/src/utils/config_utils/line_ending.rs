@@ -0,0 +1,94 @@
+//! 配置文件的换行符
+//!
+//! 混合系统的团队共享同一份 settings.json 时，Windows 上编辑过的文件会变成 CRLF，
+//! 带来校验和与差异噪音。保存时统一使用 [`config_line_ending`]（默认 LF，与平台无关）；
+//! 加载时两种换行符都能正常解析。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 保存配置时使用的换行符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`（默认）
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    #[cfg(test)]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+static USE_CRLF: AtomicBool = AtomicBool::new(false);
+
+/// 设置保存配置时使用的换行符
+#[cfg(test)]
+pub fn set_config_line_ending(ending: LineEnding) {
+    USE_CRLF.store(ending == LineEnding::CrLf, Ordering::Relaxed);
+}
+
+/// 获取保存配置时使用的换行符
+pub fn config_line_ending() -> LineEnding {
+    if USE_CRLF.load(Ordering::Relaxed) {
+        LineEnding::CrLf
+    } else {
+        LineEnding::Lf
+    }
+}
+
+/// 将文本中的 `\r\n`、`\r`、`\n` 统一为指定换行符
+pub fn normalize_line_endings(text: &str, ending: LineEnding) -> String {
+    let unified = text.replace("\r\n", "\n").replace('\r', "\n");
+    match ending {
+        LineEnding::Lf => unified,
+        LineEnding::CrLf => unified.replace('\n', "\r\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_normalize_line_endings() {
+        let mixed = "{\r\n  \"a\": 1,\r  \"b\": 2\n}";
+        assert_eq!(
+            normalize_line_endings(mixed, LineEnding::Lf),
+            "{\n  \"a\": 1,\n  \"b\": 2\n}"
+        );
+        assert_eq!(
+            normalize_line_endings(mixed, LineEnding::CrLf),
+            "{\r\n  \"a\": 1,\r\n  \"b\": 2\r\n}"
+        );
+    }
+
+    #[test]
+    fn test_save_uses_lf_and_load_accepts_crlf() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, "{\r\n  \"model\": \"opus\"\r\n}\r\n").unwrap();
+
+        let loaded: Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(loaded, json!({ "model": "opus" }));
+
+        super::super::save_json_config(&loaded, &path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains('\r'));
+
+        set_config_line_ending(LineEnding::CrLf);
+        let saved = super::super::save_json_config(&loaded, &path);
+        set_config_line_ending(LineEnding::Lf);
+        saved.unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(LineEnding::CrLf.as_str()));
+        assert_eq!(config_line_ending(), LineEnding::Lf);
+    }
+}
@@ -0,0 +1,339 @@
+//! 配置文件检查（lint）
+//!
+//! serde_json 遇到重复键时会静默保留最后一个值，手动编辑的用户往往改的是第一个。
+//! 这里用一个只关心结构的轻量扫描器找出重复键及其所在行号。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::encoding::decode_config_bytes;
+use super::pointer::escape_pointer_token;
+use super::save_json_config;
+use super::snapshot::config_files;
+
+/// 重复键信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateKey {
+    /// 重复键的 JSON Pointer（如 `/proxy/url`）
+    pub pointer: String,
+    /// 每次出现所在的行号（从 1 开始）
+    pub lines: Vec<usize>,
+}
+
+/// 查找 JSON 文本中的重复键
+///
+/// # 返回值
+/// - `Ok(Vec<DuplicateKey>)`: 按首次出现顺序排列的重复键（无重复时为空）
+/// - `Err(String)`: 文本不是合法 JSON
+pub fn find_duplicate_keys(content: &str) -> Result<Vec<DuplicateKey>, String> {
+    let mut scanner = Scanner {
+        chars: content.chars().collect(),
+        pos: 0,
+        line: 1,
+        duplicates: Vec::new(),
+    };
+
+    scanner.skip_whitespace();
+    scanner.scan_value("")?;
+    scanner.skip_whitespace();
+    if scanner.pos < scanner.chars.len() {
+        return Err(format!(
+            "Unexpected trailing content at line {}",
+            scanner.line
+        ));
+    }
+
+    Ok(scanner.duplicates)
+}
+
+/// 检查配置文件中的重复键
+pub fn lint_duplicate_keys(config_path: impl AsRef<Path>) -> Result<Vec<DuplicateKey>, String> {
+    let path = config_path.as_ref();
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    find_duplicate_keys(&content).map_err(|e| format!("Failed to scan {:?}: {}", path, e))
+}
+
+/// 修复配置文件中的重复键
+///
+/// 保留每个键最后一次出现的值（与 serde_json 的行为一致）并重新写入文件。
+/// 没有重复键时不改动文件。
+///
+/// # 返回值
+/// 被修复的重复键列表
+pub fn repair_duplicate_keys(config_path: impl AsRef<Path>) -> Result<Vec<DuplicateKey>, String> {
+    let path = config_path.as_ref();
+    let duplicates = lint_duplicate_keys(path)?;
+    if duplicates.is_empty() {
+        return Ok(duplicates);
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    let value: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+    save_json_config(&value, path)?;
+
+    log::info!(
+        "Removed {} duplicate key(s) from {:?}",
+        duplicates.len(),
+        path
+    );
+    Ok(duplicates)
+}
+
+/// 扫描目录下所有无法解析的 JSON 配置
+///
+/// 递归检查 `base_dir` 下的 `.json` 文件（跳过快照、备份等内部目录），
+/// 不会在第一个错误处停止，一次性列出所有需要修复的文件
+///
+/// # 返回值
+/// `(文件路径, 解析错误)` 列表，按路径排序；目录本身无法扫描时包含一条 `base_dir` 的错误
+pub fn scan_invalid_configs(base_dir: &Path) -> Vec<(PathBuf, String)> {
+    let files = match config_files(base_dir) {
+        Ok(files) => files,
+        Err(e) => return vec![(base_dir.to_path_buf(), e)],
+    };
+
+    files
+        .into_iter()
+        .map(|relative| base_dir.join(relative))
+        .filter_map(|path| {
+            let error = fs::read(&path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|bytes| decode_config_bytes(&bytes).map(|(content, _)| content))
+                .and_then(|content| {
+                    serde_json::from_str::<Value>(&content)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .err()?;
+            Some((path, error))
+        })
+        .collect()
+}
+
+/// 仅跟踪结构、键名和行号的 JSON 扫描器
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    duplicates: Vec<DuplicateKey>,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!(
+                "Expected {:?} but found {:?} at line {}",
+                expected, c, self.line
+            )),
+            None => Err(format!("Expected {:?} but reached end of input", expected)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.bump();
+        }
+    }
+
+    fn scan_value(&mut self, pointer: &str) -> Result<(), String> {
+        match self.peek() {
+            Some('{') => self.scan_object(pointer),
+            Some('[') => self.scan_array(pointer),
+            Some('"') => self.scan_string().map(|_| ()),
+            Some(c) if is_scalar_char(c) => {
+                while self.peek().is_some_and(is_scalar_char) {
+                    self.bump();
+                }
+                Ok(())
+            }
+            Some(c) => Err(format!("Unexpected {:?} at line {}", c, self.line)),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+
+    fn scan_object(&mut self, pointer: &str) -> Result<(), String> {
+        self.expect('{')?;
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut local: Vec<DuplicateKey> = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let line = self.line;
+            let key = self.scan_string()?;
+            let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+
+            match seen.get(&key) {
+                Some(&first_line) => match local.iter_mut().find(|d| d.pointer == child) {
+                    Some(duplicate) => duplicate.lines.push(line),
+                    None => local.push(DuplicateKey {
+                        pointer: child.clone(),
+                        lines: vec![first_line, line],
+                    }),
+                },
+                None => {
+                    seen.insert(key, line);
+                }
+            }
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            self.scan_value(&child)?;
+            self.skip_whitespace();
+
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => break,
+                Some(c) => return Err(format!("Unexpected {:?} at line {}", c, self.line)),
+                None => return Err("Unterminated object".to_string()),
+            }
+        }
+
+        self.duplicates.extend(local);
+        Ok(())
+    }
+
+    fn scan_array(&mut self, pointer: &str) -> Result<(), String> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(());
+        }
+
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            self.scan_value(&format!("{}/{}", pointer, index))?;
+            self.skip_whitespace();
+            index += 1;
+
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(()),
+                Some(c) => return Err(format!("Unexpected {:?} at line {}", c, self.line)),
+                None => return Err("Unterminated array".to_string()),
+            }
+        }
+    }
+
+    fn scan_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                        let code = u32::from_str_radix(&hex, 16)
+                            .map_err(|_| format!("Invalid unicode escape at line {}", self.line))?;
+                        out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some(c) => out.push(c),
+                    None => return Err("Unterminated string".to_string()),
+                },
+                Some(c) => out.push(c),
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+}
+
+/// 数字、`true`/`false`/`null` 可能包含的字符
+fn is_scalar_char(c: char) -> bool {
+    c == '-' || c == '+' || c == '.' || c.is_ascii_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_duplicate_keys_reports_lines() {
+        let content = "{\n  \"model\": \"a\",\n  \"proxy\": { \"url\": 1,\n    \"url\": 2 },\n  \"model\": \"b\"\n}";
+        let duplicates = find_duplicate_keys(content).unwrap();
+
+        assert_eq!(
+            duplicates,
+            vec![
+                DuplicateKey {
+                    pointer: "/proxy/url".to_string(),
+                    lines: vec![3, 4],
+                },
+                DuplicateKey {
+                    pointer: "/model".to_string(),
+                    lines: vec![2, 5],
+                },
+            ]
+        );
+        assert!(find_duplicate_keys(r#"{"a": [1, {"b": 2}]}"#)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_repair_duplicate_keys_keeps_last() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, r#"{"model": "first", "model": "last"}"#).unwrap();
+
+        assert_eq!(repair_duplicate_keys(&path).unwrap().len(), 1);
+        assert!(lint_duplicate_keys(&path).unwrap().is_empty());
+
+        let value: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["model"], "last");
+    }
+
+    #[test]
+    fn test_scan_invalid_configs_lists_every_broken_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path();
+        fs::create_dir_all(base.join("agents")).unwrap();
+        fs::write(base.join("settings.json"), r#"{"model": "a"}"#).unwrap();
+        fs::write(base.join("broken.json"), r#"{"model": }"#).unwrap();
+        fs::write(base.join("agents/trailing.json"), r#"{"a": 1,}"#).unwrap();
+        fs::write(base.join("notes.txt"), "not json").unwrap();
+
+        let invalid = scan_invalid_configs(base);
+        let paths: Vec<_> = invalid.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![base.join("agents/trailing.json"), base.join("broken.json")]
+        );
+        assert!(invalid[1].1.contains("line 1"), "{}", invalid[1].1);
+    }
+}
@@ -0,0 +1,601 @@
+//! 配置文件写锁
+//!
+//! 原子写入会替换目标文件的 inode，无法直接锁配置文件本身，
+//! 因此使用同目录的 `<filename>.lock` 旁路文件加建议锁（advisory lock）。
+//! 锁文件平时保留在磁盘上；[`clean_stale_locks`] 只在持有锁时删除锁文件，
+//! 获取锁后还会确认锁文件没有在等待期间被删除或替换，避免删除锁文件带来的竞态。
+//! 目录只读、磁盘已满等原因导致无法创建锁文件时，保存不加锁继续进行，
+//! 由 [`atomic_write`](super::atomic_write) 决定能否写入（包括退回到原地写入）。
+//!
+//! 导入、快照恢复、布局迁移等批量操作会修改多个文件，此时在配置目录下持有
+//! `.global.lock` 排他锁（见 [`with_config_dir_lock`]）；单文件写锁会先获取所在目录及
+//! 各级父目录中已有 `.global.lock` 的共享锁，批量操作期间的单文件保存因此会等待其完成。
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+#[cfg(test)]
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
+
+use super::paths::canonical_config_path;
+
+/// 锁文件后缀
+pub const LOCK_SUFFIX: &str = ".lock";
+
+/// 目录锁文件名
+pub const GLOBAL_LOCK_FILE: &str = ".global.lock";
+
+/// 单文件保存等待批量操作完成的最长时间
+pub const GLOBAL_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+const GLOBAL_LOCK_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+thread_local! {
+    /// 当前线程持有排他锁的目录锁文件，批量操作内部的保存不再等待自身
+    static HELD_DIR_LOCKS: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
+/// 已持有的配置写锁，drop 时释放
+#[derive(Debug)]
+pub struct ConfigLock {
+    /// 无法创建锁文件时为 `None`，此时不持有文件锁
+    file: Option<File>,
+    path: PathBuf,
+    /// 目录锁的共享锁，在文件锁之后释放
+    _dir_locks: Vec<File>,
+}
+
+impl ConfigLock {
+    /// 锁文件路径
+    #[cfg(test)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 是否实际持有文件锁（无法创建锁文件时为 `false`）
+    #[cfg(test)]
+    pub fn is_held(&self) -> bool {
+        self.file.is_some()
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        if let Some(Err(e)) = self.file.as_ref().map(File::unlock) {
+            log::warn!("Failed to release config lock {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// 获取配置文件对应的锁文件路径
+pub fn lock_path(config_path: &Path) -> PathBuf {
+    let mut name = config_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(LOCK_SUFFIX);
+    config_path.with_file_name(name)
+}
+
+/// 获取配置写锁（阻塞直到可用）
+///
+/// 配置所在目录正在进行批量操作时，最多等待 [`GLOBAL_LOCK_TIMEOUT`]；
+/// 无法创建锁文件时返回不持有文件锁的 [`ConfigLock`]（见 [`ConfigLock::is_held`]）
+pub fn lock_config(config_path: &Path) -> Result<ConfigLock, String> {
+    let dir_locks =
+        lock_config_dirs_shared(config_path, Some(GLOBAL_LOCK_TIMEOUT))?.unwrap_or_default();
+    let Some((file, path)) = acquire_lock_file(config_path, true)? else {
+        unreachable!("blocking lock never returns WouldBlock");
+    };
+    Ok(ConfigLock {
+        file,
+        path,
+        _dir_locks: dir_locks,
+    })
+}
+
+/// 尝试获取配置写锁（不阻塞）
+///
+/// # 返回值
+/// - `Ok(Some(lock))`: 成功获取
+/// - `Ok(None)`: 锁正被其他写入者持有，或所在目录正在进行批量操作
+#[cfg(test)]
+pub fn try_lock_config(config_path: &Path) -> Result<Option<ConfigLock>, String> {
+    let Some(dir_locks) = lock_config_dirs_shared(config_path, None)? else {
+        return Ok(None);
+    };
+    Ok(
+        acquire_lock_file(config_path, false)?.map(|(file, path)| ConfigLock {
+            file,
+            path,
+            _dir_locks: dir_locks,
+        }),
+    )
+}
+
+/// 列出可以安全删除的残留锁文件
+///
+/// 进程崩溃后操作系统会释放建议锁，但锁文件会留在磁盘上。满足以下条件的锁文件视为残留：
+/// - 文件名以 [`LOCK_SUFFIX`] 结尾（不含目录锁 [`GLOBAL_LOCK_FILE`]）且为空文件，
+///   `Cargo.lock` 等同名的非锁文件不会被误判
+/// - 最后修改时间早于 `max_age`
+/// - 当前没有任何进程持有该锁（通过非阻塞获取锁确认）
+#[cfg(test)]
+pub fn list_stale_locks(base_dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>, String> {
+    Ok(stale_lock_candidates(base_dir, max_age)?
+        .into_iter()
+        .filter(|path| hold_stale_lock(path).is_some())
+        .collect())
+}
+
+/// 删除残留的锁文件（判断条件见 [`list_stale_locks`]），供启动时调用
+///
+/// # 返回值
+/// - `Ok(removed)`: 已删除的锁文件
+/// - `Err(String)`: 扫描目录失败
+///
+/// # 特性
+/// - ✅ 删除时持有该锁，正在使用的锁不会被删除
+/// - ✅ 等待中的写入者获取锁后会发现锁文件已被删除并重新打开（见 [`lock_config`]）
+#[cfg(test)]
+pub fn clean_stale_locks(base_dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>, String> {
+    let mut removed = Vec::new();
+    for path in stale_lock_candidates(base_dir, max_age)? {
+        let Some(_held) = hold_stale_lock(&path) else {
+            continue;
+        };
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(e) => log::warn!("Failed to remove stale lock {:?}: {}", path, e),
+        }
+    }
+    if !removed.is_empty() {
+        log::info!(
+            "Removed {} stale config lock file(s) under {:?}",
+            removed.len(),
+            base_dir
+        );
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+fn stale_lock_candidates(base_dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>, String> {
+    if !base_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let now = SystemTime::now();
+    let mut candidates = Vec::new();
+    for entry in walkdir::WalkDir::new(base_dir) {
+        let entry = entry.map_err(|e| format!("Failed to scan {:?}: {}", base_dir, e))?;
+        let is_lock = entry.file_type().is_file()
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.ends_with(LOCK_SUFFIX) && name != GLOBAL_LOCK_FILE);
+        if !is_lock {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let old_enough = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= max_age);
+        if metadata.len() == 0 && old_enough {
+            candidates.push(entry.into_path());
+        }
+    }
+    Ok(candidates)
+}
+
+/// 非阻塞地获取已有锁文件的锁；锁被占用或文件已不存在时返回 `None`
+#[cfg(test)]
+fn hold_stale_lock(path: &Path) -> Option<File> {
+    let file = OpenOptions::new().read(true).write(true).open(path).ok()?;
+    file.try_lock().ok()?;
+    lock_file_is_current(&file, path).then_some(file)
+}
+
+/// 打开锁文件并加锁；`blocking` 为 `false` 时锁被占用返回 `Ok(None)`
+///
+/// 无法创建锁文件时不加锁，返回的文件为 `None`
+fn acquire_lock_file(
+    config_path: &Path,
+    blocking: bool,
+) -> Result<Option<(Option<File>, PathBuf)>, String> {
+    loop {
+        // 通过符号链接等别名访问同一文件时必须使用同一把锁
+        let path = lock_path(&canonical_config_path(config_path));
+        let file = match open_file(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("{}; saving without the lock", e);
+                return Ok(Some((None, path)));
+            }
+        };
+        if blocking {
+            file.lock()
+                .map_err(|e| format!("Failed to lock {:?}: {}", path, e))?;
+        } else {
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => return Ok(None),
+                Err(TryLockError::Error(e)) => {
+                    return Err(format!("Failed to lock {:?}: {}", path, e))
+                }
+            }
+        }
+        if lock_file_is_current(&file, &path) {
+            return Ok(Some((Some(file), path)));
+        }
+        // 等待期间锁文件被 clean_stale_locks 删除，锁住的是已删除的文件，重新打开
+        log::debug!("Lock file {:?} was removed while waiting, retrying", path);
+    }
+}
+
+/// 持有的锁文件是否仍是路径上的那个文件
+#[cfg(unix)]
+fn lock_file_is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(held), Ok(current)) => held.dev() == current.dev() && held.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// 持有的锁文件是否仍是路径上的那个文件（Windows 上打开中的文件无法被删除）
+#[cfg(not(unix))]
+fn lock_file_is_current(_file: &File, path: &Path) -> bool {
+    path.is_file()
+}
+
+/// 实际尝试以写方式打开配置文件并获取写锁，随后立即释放
+///
+/// 与 [`super::access::can_write_config`] 的权限推断不同，这里执行真实的打开操作，
+/// 避免"检查通过但保存失败"的检查/使用时间差问题
+///
+/// # 返回值
+/// - `Ok(())`: 文件可写且当前没有其他写入者
+/// - `Err(String)`: 不可写或锁被占用的具体原因
+///
+/// # 特性
+/// - ✅ 不截断已有文件，也从不创建或删除配置文件本身
+/// - ✅ 不留下任何文件：配置不存在时在同目录创建并删除临时文件，也不创建 `<filename>.lock` 锁文件
+#[cfg(test)]
+pub fn try_lock_for_write(config_path: impl AsRef<Path>) -> Result<(), String> {
+    let path = config_path.as_ref();
+
+    // 父目录不存在时无法在不留下目录的情况下探测，退回到权限推断
+    if path
+        .parent()
+        .is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.exists())
+    {
+        return super::access::check_config_writable(path);
+    }
+
+    let probe = match OpenOptions::new().write(true).open(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let dir = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            tempfile::NamedTempFile::new_in(dir).map(drop)
+        }
+        opened => opened.map(drop),
+    };
+    probe.map_err(|e| format!("Config file {:?} is not writable: {}", path, e))?;
+
+    let locked = || format!("Config file {:?} is locked by another writer", path);
+    let Some(_dir_locks) = lock_config_dirs_shared(path, None)? else {
+        return Err(locked());
+    };
+    // 锁文件尚不存在时没有写入者持有它，无需创建
+    let sidecar = lock_path(&canonical_config_path(path));
+    let file = match OpenOptions::new().read(true).write(true).open(&sidecar) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to open lock file {:?}: {}", sidecar, e)),
+    };
+    match file.try_lock() {
+        Ok(()) => Ok(()),
+        Err(TryLockError::WouldBlock) => Err(locked()),
+        Err(TryLockError::Error(e)) => Err(format!("Failed to lock {:?}: {}", sidecar, e)),
+    }
+}
+
+/// 持有配置目录排他锁执行批量操作
+///
+/// # 参数
+/// - `base_dir`: 配置目录（如 `~/.claude`），锁文件为 `<base_dir>/.global.lock`
+/// - `operation`: 批量操作；在其中（同一线程内）调用的保存函数不会等待该锁
+///
+/// # 返回值
+/// - `Ok(R)`: `operation` 的返回值
+/// - `Err(String)`: 加锁失败或 `operation` 返回的错误
+///
+/// # 特性
+/// - ✅ 等待进行中的单文件保存完成后才开始
+/// - ✅ 其他线程和进程的单文件保存会等待批量操作结束（见 [`GLOBAL_LOCK_TIMEOUT`]）
+/// - ✅ 同一线程内可重入
+#[cfg(test)]
+pub fn with_config_dir_lock<R, F>(base_dir: impl AsRef<Path>, operation: F) -> Result<R, String>
+where
+    F: FnOnce() -> Result<R, String>,
+{
+    let base_dir = base_dir.as_ref();
+    fs::create_dir_all(base_dir)
+        .map_err(|e| format!("Failed to create config directory {:?}: {}", base_dir, e))?;
+
+    let path = base_dir.join(GLOBAL_LOCK_FILE);
+    let file = open_file(&path)?;
+    let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    if HELD_DIR_LOCKS.with(|held| held.borrow().contains(&key)) {
+        return operation();
+    }
+
+    file.lock()
+        .map_err(|e| format!("Failed to lock {:?}: {}", path, e))?;
+    HELD_DIR_LOCKS.with(|held| held.borrow_mut().insert(key.clone()));
+    let _guard = HeldDirLock { file, path, key };
+    operation()
+}
+
+/// 已持有的目录排他锁；drop 时（包括 `operation` panic 时）移除登记并释放锁
+#[cfg(test)]
+struct HeldDirLock {
+    file: File,
+    path: PathBuf,
+    key: PathBuf,
+}
+
+#[cfg(test)]
+impl Drop for HeldDirLock {
+    fn drop(&mut self) {
+        HELD_DIR_LOCKS.with(|held| held.borrow_mut().remove(&self.key));
+        if let Err(e) = self.file.unlock() {
+            log::warn!(
+                "Failed to release config directory lock {:?}: {}",
+                self.path,
+                e
+            );
+        }
+    }
+}
+
+/// 获取配置文件各级父目录中已有目录锁的共享锁
+///
+/// `timeout` 为 `None` 时不等待，目录锁被占用则返回 `Ok(None)`
+fn lock_config_dirs_shared(
+    config_path: &Path,
+    timeout: Option<Duration>,
+) -> Result<Option<Vec<File>>, String> {
+    let mut locks = Vec::new();
+    for dir in config_path.ancestors().skip(1) {
+        let path = dir.join(GLOBAL_LOCK_FILE);
+        if !path.is_file() {
+            continue;
+        }
+        let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if HELD_DIR_LOCKS.with(|held| held.borrow().contains(&key)) {
+            continue;
+        }
+
+        // 共享锁只需读权限；只读目录中的目录锁同样生效
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Failed to open lock file {:?}: {}; ignoring it", path, e);
+                continue;
+            }
+        };
+        let started = Instant::now();
+        loop {
+            match file.try_lock_shared() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => match timeout {
+                    Some(timeout) if started.elapsed() < timeout => {
+                        std::thread::sleep(GLOBAL_LOCK_POLL_INTERVAL)
+                    }
+                    Some(_) => {
+                        return Err(format!(
+                            "Timed out waiting for bulk operation on {:?} to finish",
+                            dir
+                        ))
+                    }
+                    None => return Ok(None),
+                },
+                Err(TryLockError::Error(e)) => {
+                    return Err(format!("Failed to lock {:?}: {}", path, e))
+                }
+            }
+        }
+        locks.push(file);
+    }
+    Ok(Some(locks))
+}
+
+fn open_file(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("Failed to open lock file {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_lock_for_write_leaves_no_config_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        try_lock_for_write(&path).unwrap();
+        assert!(!path.exists());
+        assert!(!lock_path(&path).exists());
+
+        // 其他线程的批量操作持有目录锁
+        with_config_dir_lock(temp.path(), || {
+            let probe = path.clone();
+            let err = std::thread::spawn(move || try_lock_for_write(&probe).unwrap_err())
+                .join()
+                .unwrap();
+            assert!(err.contains("locked"), "{}", err);
+            Ok(())
+        })
+        .unwrap();
+        assert!(!lock_path(&path).exists());
+
+        let held = lock_config(&path).unwrap();
+        assert!(held.is_held());
+        assert!(try_lock_config(&path).unwrap().is_none());
+        assert!(try_lock_for_write(&path).unwrap_err().contains("locked"));
+
+        drop(held);
+        assert!(try_lock_config(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_with_config_dir_lock_blocks_other_saves() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let nested = base.join("projects").join("settings.json");
+        fs::create_dir_all(nested.parent().unwrap()).unwrap();
+
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        let bulk_dir = base.clone();
+        let bulk = std::thread::spawn(move || {
+            with_config_dir_lock(&bulk_dir, || {
+                // 批量操作内部的保存不会等待自身持有的目录锁
+                super::super::save_json_config(&1, bulk_dir.join("a.json"))?;
+                ready_tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(200));
+                super::super::save_json_config(&2, bulk_dir.join("a.json"))
+            })
+        });
+
+        ready_rx.recv().unwrap();
+        assert!(try_lock_config(&nested).unwrap().is_none());
+
+        let started = Instant::now();
+        let lock = lock_config(&nested).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        bulk.join().unwrap().unwrap();
+        drop(lock);
+
+        let saved: i32 = super::super::load_json_config(base.join("a.json")).unwrap();
+        assert_eq!(saved, 2);
+    }
+
+    #[test]
+    fn test_with_config_dir_lock_released_on_panic() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let result = std::panic::catch_unwind(|| {
+            with_config_dir_lock::<(), _>(&base, || panic!("bulk operation failed"))
+        });
+        assert!(result.is_err());
+
+        let key = fs::canonicalize(base.join(GLOBAL_LOCK_FILE)).unwrap();
+        assert!(!HELD_DIR_LOCKS.with(|held| held.borrow().contains(&key)));
+        assert!(try_lock_config(&base.join("settings.json"))
+            .unwrap()
+            .is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_without_lock_in_read_only_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        // root 不受目录权限限制，测试没有意义
+        if rustix::process::geteuid().is_root() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("readonly");
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join("settings.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let lock = lock_config(&path).unwrap();
+        assert!(!lock.is_held());
+        drop(lock);
+        let saved = super::super::save_json_config(&serde_json::json!({ "v": 1 }), &path);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        saved.unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("\"v\""));
+        assert!(!lock_path(&path).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_alias_shares_the_lock() {
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("dotfiles.json");
+        let alias = temp.path().join("settings.json");
+        fs::write(&real, "{}").unwrap();
+        std::os::unix::fs::symlink(&real, &alias).unwrap();
+
+        let _lock = lock_config(&alias).unwrap();
+        assert!(try_lock_config(&real).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clean_stale_locks() {
+        let temp = tempfile::tempdir().unwrap();
+        let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        let age = |path: &Path| {
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(old)
+                .unwrap()
+        };
+
+        let stale = temp.path().join("settings.json");
+        drop(lock_config(&stale).unwrap());
+        age(&lock_path(&stale));
+
+        let held_config = temp.path().join("agents.json");
+        let held = lock_config(&held_config).unwrap();
+        age(held.path());
+
+        let fresh = temp.path().join("mcp.json");
+        drop(lock_config(&fresh).unwrap());
+
+        let cargo_lock = temp.path().join("Cargo.lock");
+        fs::write(&cargo_lock, "# generated").unwrap();
+        age(&cargo_lock);
+
+        let max_age = Duration::from_secs(60 * 60);
+        let stale_lock = canonical_config_path(&lock_path(&stale));
+        let listed: Vec<PathBuf> = list_stale_locks(temp.path(), max_age)
+            .unwrap()
+            .iter()
+            .map(|path| canonical_config_path(path))
+            .collect();
+        assert_eq!(listed, vec![stale_lock.clone()]);
+
+        let removed = clean_stale_locks(temp.path(), max_age).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(!stale_lock.exists());
+        assert!(held.path().exists());
+        assert!(lock_path(&fresh).exists());
+        assert!(cargo_lock.exists());
+
+        drop(held);
+        let relocked = lock_config(&stale).unwrap();
+        assert!(relocked.path().exists());
+    }
+}
@@ -0,0 +1,128 @@
+//! 应用托管配置键
+//!
+//! 部分键（如 `lastOpenedProject`）只应由应用写入，其余键归用户所有。
+//! 保存时以磁盘上的文件为基础保留用户对非托管键的修改，
+//! 同时用应用的值重新覆盖托管键（应用侧不存在的托管键会被删除）。
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::pointer::{remove_pointer, set_pointer};
+use super::update_json_config;
+
+/// 将应用的托管键合并到磁盘上的配置
+///
+/// # 参数
+/// - `on_disk`: 当前文件内容（包含用户修改）
+/// - `app`: 应用侧的配置值
+/// - `managed`: 托管键的 JSON Pointer 列表
+///
+/// # 返回值
+/// 合并后的配置：非托管键取自 `on_disk`，托管键取自 `app`
+pub fn merge_managed_keys(on_disk: &Value, app: &Value, managed: &[&str]) -> Result<Value, String> {
+    let mut merged = on_disk.clone();
+    for pointer in managed {
+        match app.pointer(pointer) {
+            Some(value) => set_pointer(&mut merged, pointer, value.clone())?,
+            None => {
+                remove_pointer(&mut merged, pointer);
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// 保存配置，仅覆盖托管键并保留用户对其余键的修改
+///
+/// 读取、合并与写入在同一把写锁内完成（见 [`update_json_config`]），
+/// 与 [`load_json_config`](super::load_json_config) 一样接受 BOM、非 UTF-8 编码和宽松语法。
+/// 文件不存在时直接写入完整的应用配置；文件无法解析时返回错误，不覆盖用户的文件
+///
+/// # 参数
+/// - `config`: 应用侧的配置对象
+/// - `config_path`: 配置文件路径
+/// - `managed`: 托管键的 JSON Pointer 列表
+pub fn save_json_config_managed<T>(
+    config: &T,
+    config_path: impl AsRef<Path>,
+    managed: &[&str],
+) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let app_value =
+        serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    update_json_config(path, |live| {
+        *live = if path.exists() {
+            merge_managed_keys(live, &app_value, managed)?
+        } else {
+            log::debug!("No config at {:?}, writing full app config", path);
+            app_value
+        };
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_merge_managed_keys_preserves_user_edits() {
+        let on_disk = json!({
+            "theme": "dark",
+            "lastOpenedProject": "/user/edited",
+            "internal": { "cursor": 1 }
+        });
+        let app = json!({
+            "theme": "light",
+            "lastOpenedProject": "/app/value"
+        });
+
+        let merged =
+            merge_managed_keys(&on_disk, &app, &["/lastOpenedProject", "/internal/cursor"])
+                .unwrap();
+
+        assert_eq!(
+            merged,
+            json!({ "theme": "dark", "lastOpenedProject": "/app/value", "internal": {} })
+        );
+    }
+
+    #[test]
+    fn test_save_managed_writes_full_config_when_missing() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        save_json_config_managed(&json!({ "a": 1 }), &path, &["/a"]).unwrap();
+        fs::write(&path, r#"{ "a": 5, "b": "user" }"#).unwrap();
+        save_json_config_managed(&json!({ "a": 2, "b": "app" }), &path, &["/a"]).unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, json!({ "a": 2, "b": "user" }));
+    }
+
+    #[test]
+    fn test_save_managed_keeps_lenient_user_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice(b"{ \"a\": 5, // note\n \"b\": \"user\", }");
+        fs::write(&path, bytes).unwrap();
+        save_json_config_managed(&json!({ "a": 2, "b": "app" }), &path, &["/a"]).unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, json!({ "a": 2, "b": "user" }));
+
+        fs::write(&path, "{ \"b\": ").unwrap();
+        assert!(save_json_config_managed(&json!({ "a": 3 }), &path, &["/a"]).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ \"b\": ");
+    }
+}
@@ -0,0 +1,230 @@
+//! JSON 值的深度合并
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::pointer::escape_pointer_token;
+
+/// 将 `overlay` 深度合并到 `base`
+///
+/// - 双方都是对象时逐键递归合并，`base` 中独有的键保留
+/// - 其他情况（包括数组）由 `overlay` 整体替换
+pub fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// 深度合并，指定的数组按元素的标识键逐项合并
+///
+/// # 参数
+/// - `base`: 被合并的值（原地修改）
+/// - `overlay`: 覆盖值
+/// - `keyed_arrays`: `(数组的 JSON Pointer, 标识键)` 列表，如 `("/mcpServers", "name")`
+///
+/// # 合并规则
+/// - 指定的数组中，标识键相同的元素深度合并，新元素追加到末尾
+/// - 缺少标识键的覆盖元素直接追加
+/// - 其余规则与 [`deep_merge`] 相同
+pub fn deep_merge_keyed(base: &mut Value, overlay: Value, keyed_arrays: &[(&str, &str)]) {
+    merge_at(base, overlay, keyed_arrays, "");
+}
+
+fn merge_at(base: &mut Value, overlay: Value, keyed_arrays: &[(&str, &str)], pointer: &str) {
+    let identity = keyed_arrays
+        .iter()
+        .find(|(array_pointer, _)| *array_pointer == pointer)
+        .map(|(_, key)| *key);
+
+    match (base, overlay, identity) {
+        (Value::Object(base_map), Value::Object(overlay_map), _) => {
+            for (key, value) in overlay_map {
+                let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_at(existing, value, keyed_arrays, &child),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base_items), Value::Array(overlay_items), Some(key)) => {
+            for item in overlay_items {
+                let position = item.get(key).and_then(|id| {
+                    base_items
+                        .iter()
+                        .position(|existing| existing.get(key) == Some(id))
+                });
+                match position {
+                    Some(index) => {
+                        let child = format!("{}/{}", pointer, index);
+                        merge_at(&mut base_items[index], item, keyed_arrays, &child);
+                    }
+                    None => base_items.push(item),
+                }
+            }
+        }
+        (base, overlay, _) => *base = overlay,
+    }
+}
+
+/// 按顺序深度合并多个配置层，并记录每个叶子值来自哪一层
+///
+/// # 参数
+/// - `layers`: `(层名称, 配置)` 列表，靠后的层优先级更高（如默认值、配置档、用户、本地）
+///
+/// # 返回值
+/// `(合并结果, 叶子 JSON Pointer -> 提供最终值的层名称)`；
+/// 数组、空对象和标量都视为叶子，合并规则与 [`deep_merge`] 相同
+pub fn merge_with_provenance(layers: &[(&str, Value)]) -> (Value, BTreeMap<String, String>) {
+    let mut merged = Value::Object(Default::default());
+    let mut provenance = BTreeMap::new();
+    for (name, layer) in layers {
+        merge_tracking(&mut merged, layer.clone(), "", name, &mut provenance);
+    }
+    (merged, provenance)
+}
+
+fn merge_tracking(
+    base: &mut Value,
+    overlay: Value,
+    pointer: &str,
+    layer: &str,
+    provenance: &mut BTreeMap<String, String>,
+) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            if !overlay_map.is_empty() {
+                // 原来的空对象叶子被展开为子键
+                provenance.remove(pointer);
+            }
+            for (key, value) in overlay_map {
+                let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_tracking(existing, value, &child, layer, provenance),
+                    None => {
+                        record_leaves(&value, &child, layer, provenance);
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            let prefix = format!("{}/", pointer);
+            provenance.retain(|key, _| key != pointer && !key.starts_with(&prefix));
+            record_leaves(&overlay, pointer, layer, provenance);
+            *base = overlay;
+        }
+    }
+}
+
+fn record_leaves(
+    value: &Value,
+    pointer: &str,
+    layer: &str,
+    provenance: &mut BTreeMap<String, String>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+                record_leaves(child, &child_pointer, layer, provenance);
+            }
+        }
+        _ => {
+            provenance.insert(pointer.to_string(), layer.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deep_merge_keeps_unknown_keys_and_replaces_arrays() {
+        let mut base = json!({ "a": { "x": 1, "y": 2 }, "list": [1, 2], "extra": true });
+        deep_merge(&mut base, json!({ "a": { "y": 3 }, "list": [3] }));
+
+        assert_eq!(
+            base,
+            json!({ "a": { "x": 1, "y": 3 }, "list": [3], "extra": true })
+        );
+    }
+
+    #[test]
+    fn test_deep_merge_keyed_merges_array_items_by_key() {
+        let mut base = json!({
+            "mcpServers": [
+                { "name": "fs", "command": "fs-server", "args": ["--root", "/"] },
+                { "name": "git", "command": "git-server" }
+            ],
+            "tags": ["a"]
+        });
+        deep_merge_keyed(
+            &mut base,
+            json!({
+                "mcpServers": [
+                    { "name": "git", "enabled": false },
+                    { "name": "web", "command": "web-server" }
+                ],
+                "tags": ["b"]
+            }),
+            &[("/mcpServers", "name")],
+        );
+
+        assert_eq!(
+            base,
+            json!({
+                "mcpServers": [
+                    { "name": "fs", "command": "fs-server", "args": ["--root", "/"] },
+                    { "name": "git", "command": "git-server", "enabled": false },
+                    { "name": "web", "command": "web-server" }
+                ],
+                "tags": ["b"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_with_provenance() {
+        let layers = [
+            (
+                "defaults",
+                json!({ "model": "sonnet", "proxy": { "url": "", "port": 80 }, "hooks": {} }),
+            ),
+            (
+                "profile",
+                json!({ "proxy": { "url": "http://work" }, "hooks": { "pre": "a" } }),
+            ),
+            ("user", json!({ "model": "opus", "proxy": null })),
+            ("local", json!({ "proxy": { "port": 8080 } })),
+        ];
+
+        let (merged, provenance) = merge_with_provenance(&layers);
+        assert_eq!(
+            merged,
+            json!({ "model": "opus", "proxy": { "port": 8080 }, "hooks": { "pre": "a" } })
+        );
+        assert_eq!(
+            provenance,
+            BTreeMap::from([
+                ("/hooks/pre".to_string(), "profile".to_string()),
+                ("/model".to_string(), "user".to_string()),
+                ("/proxy/port".to_string(), "local".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! 配置保存耗时观测
+//!
+//! 网络挂载的主目录上保存配置可能耗时数秒，用户容易误以为应用卡死。
+//! 这里统计每次保存的耗时，超过阈值时记录警告，并通过可注册的回调通知上层（例如显示"保存较慢"提示）。
+//! 仅做观测，不改变保存语义。
+//!
+//! 同样用于观测的还有 [`config_size_warning`]：配置文件超过预期大小时给出警告，
+//! 在意外膨胀（如 settings.json 累积了数 MB 数据）拖慢加载之前发现问题。
+
+#[cfg(test)]
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 默认慢保存阈值（毫秒）
+pub const DEFAULT_SLOW_SAVE_THRESHOLD_MS: u64 = 1000;
+
+/// 保存耗时回调，参数为配置文件路径和本次保存耗时
+pub type SaveMetricsHook = Arc<dyn Fn(&Path, Duration) + Send + Sync>;
+
+static SLOW_SAVE_THRESHOLD_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_SAVE_THRESHOLD_MS);
+static SAVE_METRICS_HOOK: RwLock<Option<SaveMetricsHook>> = RwLock::new(None);
+
+/// 设置慢保存警告阈值
+#[cfg(test)]
+pub fn set_slow_save_threshold(threshold: Duration) {
+    SLOW_SAVE_THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// 获取当前慢保存警告阈值
+pub fn slow_save_threshold() -> Duration {
+    Duration::from_millis(SLOW_SAVE_THRESHOLD_MS.load(Ordering::Relaxed))
+}
+
+/// 注册（或传入 `None` 清除）保存耗时回调
+///
+/// 回调在每次保存成功后调用，不论是否超过阈值
+#[cfg(test)]
+pub fn set_save_metrics_hook(hook: Option<SaveMetricsHook>) {
+    match SAVE_METRICS_HOOK.write() {
+        Ok(mut guard) => *guard = hook,
+        Err(poisoned) => *poisoned.into_inner() = hook,
+    }
+}
+
+/// 记录一次保存耗时：超过阈值时输出警告，并调用已注册的回调
+pub(crate) fn observe_save(path: &Path, elapsed: Duration) {
+    if elapsed >= slow_save_threshold() {
+        log::warn!(
+            "Slow config save: {:?} took {} ms (threshold {} ms)",
+            path,
+            elapsed.as_millis(),
+            slow_save_threshold().as_millis()
+        );
+    }
+
+    // 先克隆回调再调用，避免回调内部再次注册时死锁
+    let hook = SAVE_METRICS_HOOK
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone());
+    if let Some(hook) = hook {
+        hook(path, elapsed);
+    }
+}
+
+/// 检查配置文件是否超过预期大小
+///
+/// 只做观测：超过时记录警告并返回警告内容，不影响加载或保存
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `expected_max`: 该文件的预期最大字节数
+///
+/// # 返回值
+/// - `Some(warning)`: 文件超过预期大小
+/// - `None`: 未超过，或文件不存在
+#[cfg(test)]
+pub fn config_size_warning(config_path: impl AsRef<Path>, expected_max: u64) -> Option<String> {
+    let path = config_path.as_ref();
+    let size = fs::metadata(path).ok()?.len();
+    if size <= expected_max {
+        return None;
+    }
+
+    let warning = format!(
+        "Config {:?} is {} bytes, larger than the expected {} bytes; it may have accumulated unintended data",
+        path, size, expected_max
+    );
+    log::warn!("{}", warning);
+    Some(warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_size_warning() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        assert!(config_size_warning(&path, 10).is_none());
+
+        fs::write(&path, "{\"a\": 1}").unwrap();
+        assert!(config_size_warning(&path, 64).is_none());
+        let warning = config_size_warning(&path, 4).unwrap();
+        assert!(warning.contains("8 bytes"), "{}", warning);
+    }
+
+    #[test]
+    fn test_set_slow_save_threshold() {
+        set_slow_save_threshold(Duration::from_millis(250));
+        assert_eq!(slow_save_threshold(), Duration::from_millis(250));
+        set_slow_save_threshold(Duration::from_millis(DEFAULT_SLOW_SAVE_THRESHOLD_MS));
+        assert_eq!(
+            slow_save_threshold(),
+            Duration::from_millis(DEFAULT_SLOW_SAVE_THRESHOLD_MS)
+        );
+    }
+}
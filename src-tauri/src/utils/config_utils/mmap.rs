@@ -0,0 +1,100 @@
+//! 内存映射方式加载大型配置
+//!
+//! 会话索引等只读为主的大文件反复读入 `String` 较慢。启用 `mmap-config` 特性后，
+//! 超过 [`MMAP_MIN_SIZE`] 的文件直接映射到内存并用 `serde_json::from_slice` 反序列化，
+//! 省去读入 `String` 的那次拷贝；小文件或映射失败时回退到 [`load_json_config`]。
+//!
+//! 映射期间文件被其他程序原地截断会触发 SIGBUS。Claude CLI、外部编辑器等都会原地改写
+//! `settings.json` 之类的用户配置，因此这里只用于应用自己维护、只经 [`atomic_write`]
+//! 替换的索引文件，调用方须保证这一点（见 [`load_json_config_mmap`] 的 Safety 一节）。
+//!
+//! [`atomic_write`]: super::atomic_write
+
+use std::fs::File;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::load_json_config;
+
+/// 小于该大小的文件直接读取，映射的固定开销不划算
+pub const MMAP_MIN_SIZE: u64 = 1024 * 1024;
+
+/// 使用内存映射加载应用自有的索引文件
+///
+/// # Safety
+/// 调用方须保证映射期间（即本函数返回前）没有任何进程原地修改或截断该文件：
+/// 文件只由应用经 [`atomic_write`](super::atomic_write) / [`save_json_config`](super::save_json_config)
+/// 整体替换。用户或 CLI 会编辑的配置文件不满足此条件，应使用 [`load_json_config`]
+///
+/// # 返回值
+/// - `Ok(T)`: 成功加载的配置对象（文件不存在时返回 `T::default()`）
+/// - `Err(String)`: 与 [`load_json_config`] 相同的错误信息
+///
+/// # 特性
+/// - ✅ 大文件直接从映射的字节反序列化，不先读入 `String`（结果仍是拥有所有权的 `T`）
+/// - ✅ 映射失败、非 UTF-8 编码或宽松 JSON 时回退到 [`load_json_config`]
+pub unsafe fn load_json_config_mmap<T>(config_path: impl AsRef<Path>) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let Ok(file) = File::open(path) else {
+        return load_json_config(path);
+    };
+    if file
+        .metadata()
+        .map_or(true, |metadata| metadata.len() < MMAP_MIN_SIZE)
+    {
+        return load_json_config(path);
+    }
+
+    // SAFETY: 调用方保证该文件只会被原子替换（新 inode），已映射的内容不会被原地修改或截断
+    let map = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(map) => map,
+        Err(e) => {
+            log::debug!("Failed to mmap {:?}, reading instead: {}", path, e);
+            return load_json_config(path);
+        }
+    };
+
+    let bytes = map.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&map);
+    match serde_json::from_slice(bytes) {
+        Ok(config) => Ok(config),
+        Err(e) if e.is_syntax() || e.is_eof() => load_json_config(path),
+        Err(e) => Err(format!("Failed to parse config from {:?}: {}", path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::fs;
+
+    #[test]
+    fn test_load_json_config_mmap() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("index.json");
+        let sessions: Vec<Value> = (0..40_000)
+            .map(|i| json!({ "id": i, "title": format!("session {}", i) }))
+            .collect();
+        fs::write(&path, serde_json::to_vec(&sessions).unwrap()).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() >= MMAP_MIN_SIZE);
+
+        // SAFETY: 测试中的临时文件只由本测试写入
+        let loaded: Vec<Value> = unsafe { load_json_config_mmap(&path) }.unwrap();
+        assert_eq!(loaded, sessions);
+
+        let small = temp.path().join("small.json");
+        fs::write(&small, "{\"a\": 1,}").unwrap();
+        assert_eq!(
+            unsafe { load_json_config_mmap::<Value>(&small) }.unwrap(),
+            json!({ "a": 1 })
+        );
+        assert_eq!(
+            unsafe { load_json_config_mmap::<Value>(temp.path().join("missing.json")) }.unwrap(),
+            Value::Null
+        );
+    }
+}
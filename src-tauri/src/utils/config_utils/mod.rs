@@ -0,0 +1,851 @@
+/// 通用配置管理工具模块
+///
+/// 提供统一的JSON配置文件加载和保存功能，消除重复代码
+///
+/// # 使用示例
+///
+/// ```rust
+/// use crate::utils::config_utils::{load_json_config, save_json_config};
+///
+/// #[derive(Serialize, Deserialize, Default)]
+/// struct MyConfig {
+///     name: String,
+/// }
+///
+/// // 加载配置（如不存在则返回默认值）
+/// let config: MyConfig = load_json_config(&path)?;
+///
+/// // 保存配置（自动创建父目录）
+/// save_json_config(&config, &path)?;
+/// ```
+
+use std::fs;
+#[cfg(test)]
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+// 尚未接入应用的辅助模块只在测试中编译，接入时去掉对应的 `#[cfg(test)]`
+#[cfg(test)]
+mod access;
+mod atomic;
+#[cfg(test)]
+mod batch;
+#[cfg(test)]
+mod bundle;
+#[cfg(test)]
+mod cache;
+#[cfg(test)]
+mod changelog;
+#[cfg(test)]
+mod compat;
+mod copy;
+#[cfg(test)]
+mod dedupe;
+#[cfg(test)]
+mod defaults;
+#[cfg(test)]
+mod deprecation;
+#[cfg(test)]
+mod derived;
+#[cfg(test)]
+mod diff;
+mod digest;
+#[cfg(test)]
+mod display;
+#[cfg(test)]
+mod document;
+#[cfg(test)]
+mod drift;
+#[cfg(test)]
+mod effective;
+mod encoding;
+#[cfg(test)]
+mod fingerprint;
+#[cfg(test)]
+mod flatten;
+#[cfg(test)]
+mod health;
+mod history;
+#[cfg(test)]
+mod human;
+#[cfg(test)]
+mod include;
+#[cfg(test)]
+mod index;
+#[cfg(test)]
+mod lazy;
+#[cfg(test)]
+mod legacy;
+#[cfg(test)]
+mod lenient;
+mod line_ending;
+#[cfg(test)]
+mod lint;
+mod lock;
+#[cfg(test)]
+mod managed;
+#[cfg(test)]
+mod merge;
+mod metrics;
+#[cfg(feature = "mmap-config")]
+mod mmap;
+#[cfg(test)]
+mod ops;
+#[cfg(test)]
+mod overrides;
+#[cfg(test)]
+mod partial;
+#[cfg(test)]
+mod patch;
+mod paths;
+#[cfg(test)]
+mod pointer;
+#[cfg(test)]
+mod policy;
+#[cfg(test)]
+mod profile;
+mod rate_limit;
+#[cfg(test)]
+mod readonly;
+#[cfg(test)]
+mod references;
+mod relaxed;
+#[cfg(test)]
+mod reload;
+#[cfg(test)]
+mod remote_schema;
+#[cfg(test)]
+mod rename;
+#[cfg(test)]
+mod repair;
+#[cfg(test)]
+mod rules;
+mod schema;
+#[cfg(feature = "config-http")]
+mod server;
+#[cfg(test)]
+mod session;
+#[cfg(test)]
+mod signature;
+#[cfg(test)]
+mod snapshot;
+#[cfg(test)]
+mod strict;
+#[cfg(test)]
+mod subset;
+#[cfg(test)]
+mod summary;
+#[cfg(test)]
+mod template;
+#[cfg(test)]
+mod timeout;
+#[cfg(test)]
+mod tools;
+#[cfg(test)]
+mod typed;
+#[cfg(test)]
+mod undo;
+#[cfg(test)]
+mod verified;
+mod watch;
+#[cfg(test)]
+mod wizard;
+
+pub use self::atomic::atomic_write;
+pub use self::encoding::decode_config_bytes;
+pub use self::line_ending::{config_line_ending, normalize_line_endings, LineEnding};
+pub use self::lock::lock_config;
+#[cfg(feature = "mmap-config")]
+pub use self::mmap::{load_json_config_mmap, MMAP_MIN_SIZE};
+pub use self::relaxed::sanitize_lenient_json;
+pub use self::schema::export_config_schema;
+#[cfg(feature = "config-http")]
+pub use self::server::{
+    resolve_config_name, start_config_server, ConfigServer, CONFIG_HTTP_PREFIX,
+};
+pub use self::watch::{watch_config_events, ConfigWatcher};
+
+/// 通用配置加载函数
+///
+/// 从JSON文件加载配置，如果文件不存在则返回默认值
+///
+/// # 泛型参数
+/// - `T`: 配置类型，必须实现 `Deserialize + Default`
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+///
+/// # 返回值
+/// - `Ok(T)`: 成功加载的配置对象
+/// - `Err(String)`: 错误信息（包含文件路径和具体错误）
+///
+/// # 特性
+/// - ✅ 文件不存在时返回 `T::default()`
+/// - ✅ 自动反序列化JSON
+/// - ✅ 自动识别 BOM 和常见的非 UTF-8 编码并转码
+/// - ✅ 兼容注释和尾随逗号（见 [`sanitize_lenient_json`]）
+/// - ✅ 详细的错误信息
+/// - ✅ 支持任意实现 Deserialize + Default 的类型
+pub fn load_json_config<T>(config_path: impl AsRef<Path>) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+
+    // 文件不存在时返回默认值
+    if !path.exists() {
+        log::debug!("Config file not found at {:?}, using default", path);
+        return Ok(T::default());
+    }
+
+    // 读取文件内容（非 UTF-8 文件按检测到的编码转码）
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    let (content, source_encoding) = decode_config_bytes(&bytes)
+        .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    if let Some(encoding) = source_encoding {
+        log::warn!(
+            "Config {:?} is encoded as {}, it will be rewritten as UTF-8 on next save",
+            path,
+            encoding
+        );
+    }
+
+    // 反序列化JSON；语法错误时尝试去掉注释和尾随逗号后再解析
+    match parse_config_str(&content) {
+        Ok(config) => Ok(config),
+        Err(e) if e.is_syntax() => {
+            let config = parse_config_str(&sanitize_lenient_json(&content))
+                .map_err(|_| format!("Failed to parse config from {:?}: {}", path, e))?;
+            log::warn!(
+                "Config {:?} contains comments or trailing commas; use rewrite_config_strict to fix it",
+                path
+            );
+            Ok(config)
+        }
+        Err(e) => Err(format!("Failed to parse config from {:?}: {}", path, e)),
+    }
+}
+
+/// 从任意 `Read` 来源加载配置
+///
+/// 用于测试、压缩包内嵌配置或通过 IPC 接收的配置，
+/// 与 [`load_json_config`] 共享相同的反序列化逻辑
+///
+/// # 返回值
+/// - `Ok(T)`: 成功加载的配置对象
+/// - `Err(String)`: 读取或解析失败（来源为空时同样视为解析失败）
+#[cfg(test)]
+pub fn load_json_config_from_reader<T, R>(mut reader: R) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de>,
+    R: Read,
+{
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+
+    parse_config_str(&content).map_err(|e| format!("Failed to parse config: {}", e))
+}
+
+/// 从标准输入加载配置
+///
+/// 用于脚本和管道（如 `cat new.json | workbench apply`），读取到 EOF 后反序列化
+///
+/// # 返回值
+/// - `Ok(T)`: 成功加载的配置对象
+/// - `Err(String)`: 标准输入为空、读取失败或解析失败
+#[cfg(test)]
+pub fn load_json_config_from_stdin<T>() -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    load_piped_config(std::io::stdin().lock())
+}
+
+/// 读取管道输入，空输入给出明确提示而不是 JSON 解析错误
+#[cfg(test)]
+fn load_piped_config<T, R>(mut reader: R) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de>,
+    R: Read,
+{
+    let mut content = Vec::new();
+    reader
+        .read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read config from stdin: {}", e))?;
+    if content.iter().all(u8::is_ascii_whitespace) {
+        return Err("No config received on stdin (input was empty)".to_string());
+    }
+    load_json_config_from_reader(content.as_slice())
+}
+
+/// 将配置写入任意 `Write` 目标
+///
+/// 与 [`save_json_config`] 使用相同的序列化格式（美化格式）
+#[cfg(test)]
+pub fn save_json_config_to_writer<T, W>(config: &T, mut writer: W) -> Result<(), String>
+where
+    T: Serialize,
+    W: Write,
+{
+    let content = serialize_config(config)?;
+    writer
+        .write_all(content.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write config: {}", e))
+}
+
+/// 反序列化核心：路径版与 reader 版共用
+fn parse_config_str<T>(content: &str) -> Result<T, serde_json::Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    serde_json::from_str(content)
+}
+
+/// 序列化核心：路径版与 writer 版共用
+fn serialize_config<T>(config: &T) -> Result<String, String>
+where
+    T: Serialize,
+{
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    Ok(match config_line_ending() {
+        LineEnding::Lf => content,
+        ending => normalize_line_endings(&content, ending),
+    })
+}
+
+/// 通用配置保存函数
+///
+/// 将配置对象序列化为JSON并保存到文件
+///
+/// # 泛型参数
+/// - `T`: 配置类型，必须实现 `Serialize`
+///
+/// # 参数
+/// - `config`: 要保存的配置对象引用
+/// - `config_path`: 配置文件路径
+///
+/// # 返回值
+/// - `Ok(())`: 保存成功
+/// - `Err(String)`: 错误信息（包含文件路径和具体错误）
+///
+/// # 特性
+/// - ✅ 自动创建父目录（如果不存在）
+/// - ✅ 原子写入，写入中断不会留下半截文件（见 [`atomic_write`]）
+/// - ✅ 写入期间持有 `<filename>.lock` 写锁（见 [`lock_config`]；无法创建锁文件时不加锁继续保存）
+/// - ✅ 配置目录正在进行批量操作时等待其完成（见 [`with_config_dir_lock`]）
+/// - ✅ 使用美化格式（pretty print）
+/// - ✅ 换行符与平台无关（默认 LF，见 [`set_config_line_ending`]）
+/// - ✅ 详细的错误信息
+/// - ✅ 支持任意实现 Serialize 的类型
+/// - ✅ 统计保存耗时，超过阈值时记录警告（见 [`set_slow_save_threshold`]、[`set_save_metrics_hook`]）
+/// - ✅ 可选的写入频率上限，拒绝异常的写入风暴（见 [`set_config_write_limit`]）
+/// - ✅ 可选的自动备份、版本与日志记录（见 [`set_config_history`]）
+pub fn save_json_config<T>(config: &T, config_path: impl AsRef<Path>) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let started = Instant::now();
+
+    // 确保父目录存在
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory {:?}: {}", parent, e))?;
+    }
+
+    // 持有写锁直到写入完成，避免并发保存互相覆盖
+    let _lock = lock_config(path)?;
+    rate_limit::admit_write(path)?;
+
+    // 序列化配置对象为JSON（美化格式）
+    let content = serialize_config(config)?;
+
+    // 原子写入文件（同目录临时文件 + 重命名）
+    history::before_save(path);
+    atomic_write(path, content.as_bytes())?;
+    history::after_save(path);
+
+    metrics::observe_save(path, started.elapsed());
+    log::debug!("Config saved successfully to {:?}", path);
+    Ok(())
+}
+
+/// 仅在配置文件不存在时保存
+///
+/// 用于首次运行时写入默认配置，避免覆盖用户已有的修改。
+/// 存在性检查与写入在同一把写锁内完成，并发调用时只有一个会创建文件
+///
+/// # 返回值
+/// - `Ok(true)`: 文件不存在，已创建
+/// - `Ok(false)`: 文件已存在，未做任何修改
+/// - `Err(String)`: 创建目录、加锁或写入失败
+#[cfg(test)]
+pub fn save_json_config_if_absent<T>(
+    config: &T,
+    config_path: impl AsRef<Path>,
+) -> Result<bool, String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let started = Instant::now();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory {:?}: {}", parent, e))?;
+    }
+
+    let _lock = lock_config(path)?;
+    if path.exists() {
+        log::debug!("Config already exists at {:?}, not overwriting", path);
+        return Ok(false);
+    }
+    rate_limit::admit_write(path)?;
+
+    let content = serialize_config(config)?;
+    atomic_write(path, content.as_bytes())?;
+    history::after_save(path);
+
+    metrics::observe_save(path, started.elapsed());
+    log::debug!("Config created at {:?}", path);
+    Ok(true)
+}
+
+/// 在写锁内读取、修改并保存原始配置
+///
+/// 读取与写回之间始终持有 `<filename>.lock`，并发的修改不会互相覆盖。
+/// 文件不存在时从空对象开始；`edit` 返回错误时不写入
+///
+/// # 返回值
+/// - `Ok(R)`: `edit` 的返回值
+/// - `Err(String)`: 读取、解析、`edit` 或写入失败
+#[cfg(test)]
+pub fn update_json_config<R, F>(config_path: impl AsRef<Path>, edit: F) -> Result<R, String>
+where
+    F: FnOnce(&mut serde_json::Value) -> Result<R, String>,
+{
+    let path = config_path.as_ref();
+    let started = Instant::now();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory {:?}: {}", parent, e))?;
+    }
+
+    let _lock = lock_config(path)?;
+    let mut value: serde_json::Value = load_json_config(path)?;
+    if value.is_null() {
+        value = serde_json::Value::Object(Default::default());
+    }
+
+    let result = edit(&mut value)?;
+    rate_limit::admit_write(path)?;
+    let content = serialize_config(&value)?;
+    history::before_save(path);
+    atomic_write(path, content.as_bytes())?;
+    history::after_save(path);
+
+    metrics::observe_save(path, started.elapsed());
+    log::debug!("Config updated at {:?}", path);
+    Ok(result)
+}
+
+/// 切换配置中的布尔开关
+///
+/// 缺失或不是布尔值时视为 `false`，切换后其他键保持不变
+///
+/// # 返回值
+/// - `Ok(bool)`: 切换后的新值
+#[cfg(test)]
+pub fn toggle_config_flag(config_path: impl AsRef<Path>, pointer: &str) -> Result<bool, String> {
+    update_json_config(config_path, |value| {
+        let enabled = !value
+            .pointer(pointer)
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        pointer::set_pointer(value, pointer, serde_json::Value::Bool(enabled))?;
+        Ok(enabled)
+    })
+}
+
+/// 校验通过后再保存配置
+///
+/// 校验在任何写入之前执行，校验失败时磁盘上的配置保持原样，
+/// 保证"应用设置"流程不会留下无效的配置文件
+///
+/// # 参数
+/// - `config`: 待保存的新配置
+/// - `config_path`: 配置文件路径
+/// - `validator`: 校验函数，返回所有校验错误
+///
+/// # 返回值
+/// - `Ok(())`: 校验通过且已保存
+/// - `Err(Vec<String>)`: 校验错误列表，或保存失败时的单条错误
+#[cfg(test)]
+pub fn validate_and_save<T, F>(
+    config: &T,
+    config_path: impl AsRef<Path>,
+    validator: F,
+) -> Result<(), Vec<String>>
+where
+    T: Serialize,
+    F: FnOnce(&T) -> Result<(), Vec<String>>,
+{
+    validator(config)?;
+    save_json_config(config, config_path).map_err(|e| vec![e])
+}
+
+/// 配置路径构建助手
+///
+/// 用于构建标准配置文件路径，支持链式调用
+///
+/// # 使用示例
+///
+/// ```rust
+/// // 从HOME/.claude目录构建
+/// let builder = ConfigPathBuilder::from_home_subdir(".claude")?;
+/// let config_path = builder.build("settings.json");
+///
+/// // 自定义基础目录
+/// let builder = ConfigPathBuilder::new(my_dir);
+/// let config_path = builder.build("config.json");
+/// ```
+pub struct ConfigPathBuilder {
+    base_dir: PathBuf,
+}
+
+impl ConfigPathBuilder {
+    /// 创建新的路径构建器
+    ///
+    /// # 参数
+    /// - `base_dir`: 基础目录路径
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self { base_dir }
+    }
+
+    /// 构建配置文件路径
+    ///
+    /// # 参数
+    /// - `filename`: 配置文件名
+    ///
+    /// # 返回值
+    /// 完整的配置文件路径
+    pub fn build(&self, filename: &str) -> PathBuf {
+        self.base_dir.join(filename)
+    }
+
+    /// 构建配置文件路径，并拒绝平台上无法创建的名称
+    ///
+    /// 在 Windows 上，`con`、`nul`、`com1` 等设备名（含带扩展名的形式）
+    /// 无法作为文件或目录名，直接构建会在保存时得到难以理解的 IO 错误
+    ///
+    /// # 返回值
+    /// - `Ok(PathBuf)`: 完整的配置文件路径
+    /// - `Err(String)`: 路径中包含保留名称
+    #[cfg(test)]
+    pub fn build_safe(&self, filename: &str) -> Result<PathBuf, String> {
+        paths::check_portable_name(filename)?;
+        Ok(self.build(filename))
+    }
+
+    /// 获取基础目录
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// 从用户主目录的子目录构建
+    ///
+    /// # 参数
+    /// - `subdir`: 主目录下的子目录名（如 ".claude", ".codex"）
+    ///
+    /// # 返回值
+    /// - `Ok(ConfigPathBuilder)`: 成功创建的构建器
+    /// - `Err(String)`: 如果无法获取主目录
+    ///
+    /// # 示例
+    /// ```rust
+    /// let builder = ConfigPathBuilder::from_home_subdir(".claude")?;
+    /// let path = builder.build("settings.json");
+    /// // 结果: ~/.claude/settings.json
+    /// ```
+    pub fn from_home_subdir(subdir: &str) -> Result<Self, String> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| "Failed to get home directory".to_string())?;
+        Ok(Self::new(home.join(subdir)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::metrics::set_save_metrics_hook;
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+    struct TestConfig {
+        name: String,
+        value: i32,
+    }
+
+    #[test]
+    fn test_load_nonexistent_returns_default() {
+        let path = PathBuf::from("/tmp/nonexistent_config.json");
+        let config: TestConfig = load_json_config(&path).unwrap();
+        assert_eq!(config, TestConfig::default());
+    }
+
+    #[test]
+    fn test_save_and_load_config() {
+        let temp_dir = std::env::temp_dir();
+        let config_path = temp_dir.join("test_config.json");
+
+        let test_config = TestConfig {
+            name: "test".to_string(),
+            value: 42,
+        };
+
+        // 保存配置
+        save_json_config(&test_config, &config_path).unwrap();
+        assert!(config_path.exists());
+
+        // 加载配置
+        let loaded_config: TestConfig = load_json_config(&config_path).unwrap();
+        assert_eq!(loaded_config, test_config);
+
+        // 清理
+        fs::remove_file(config_path).ok();
+    }
+
+    #[test]
+    fn test_save_reports_duration_to_metrics_hook() {
+        use std::sync::{Arc, Mutex};
+
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("metrics.json");
+
+        let seen: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        set_save_metrics_hook(Some(Arc::new(move |path, _elapsed| {
+            sink.lock().unwrap().push(path.to_path_buf());
+        })));
+
+        save_json_config(&TestConfig::default(), &config_path).unwrap();
+        set_save_metrics_hook(None);
+
+        assert!(seen.lock().unwrap().contains(&config_path));
+    }
+
+    #[test]
+    fn test_reader_writer_round_trip() {
+        let test_config = TestConfig {
+            name: "ipc".to_string(),
+            value: 7,
+        };
+
+        let mut buffer = Vec::new();
+        save_json_config_to_writer(&test_config, &mut buffer).unwrap();
+
+        let loaded: TestConfig = load_json_config_from_reader(buffer.as_slice()).unwrap();
+        assert_eq!(loaded, test_config);
+
+        let empty: Result<TestConfig, String> = load_json_config_from_reader(&b""[..]);
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn test_load_piped_config() {
+        let loaded: TestConfig =
+            load_piped_config(&br#"{"name": "piped", "value": 7}"#[..]).unwrap();
+        assert_eq!(loaded.name, "piped");
+
+        let empty: Result<TestConfig, String> = load_piped_config(&b" \n"[..]);
+        assert!(empty.unwrap_err().starts_with("No config received on stdin"));
+
+        let invalid: Result<TestConfig, String> = load_piped_config(&b"{"[..]);
+        assert!(invalid.unwrap_err().starts_with("Failed to parse config"));
+    }
+
+    #[test]
+    fn test_load_json_config_from_stdin() {
+        use std::process::{Command, Stdio};
+
+        // 子进程中从管道读取标准输入
+        if std::env::var_os("CONFIG_STDIN_TEST_CHILD").is_some() {
+            let loaded: TestConfig = load_json_config_from_stdin().unwrap();
+            assert_eq!(loaded.name, "piped");
+            return;
+        }
+
+        let (_, module) = module_path!().split_once("::").unwrap();
+        let mut child = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", &format!("{}::test_load_json_config_from_stdin", module)])
+            .env("CONFIG_STDIN_TEST_CHILD", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(br#"{"name": "piped", "value": 7}"#)
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", stdout);
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+    }
+
+    #[test]
+    fn test_concurrent_saves_never_tear() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use std::thread;
+
+        const WRITERS: i32 = 8;
+        const ITERATIONS: i32 = 50;
+
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("stress.json");
+        // 较长的内容更容易暴露写入交错
+        let payload = |writer: i32, i: i32| TestConfig {
+            name: format!("writer-{}-", writer).repeat(200),
+            value: writer * 1000 + i,
+        };
+        save_json_config(&payload(0, 0), &config_path).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let path = config_path.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    let loaded: TestConfig = load_json_config(&path).unwrap();
+                    let writer = loaded.value / 1000;
+                    assert_eq!(loaded, payload(writer, loaded.value % 1000));
+                }
+            })
+        };
+
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|writer| {
+                let path = config_path.clone();
+                thread::spawn(move || {
+                    for i in 0..ITERATIONS {
+                        save_json_config(&payload(writer, i), &path).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in writers {
+            handle.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        reader.join().unwrap();
+
+        let last: TestConfig = load_json_config(&config_path).unwrap();
+        let writer = last.value / 1000;
+        assert!((0..WRITERS).contains(&writer));
+        // 最后一次写入必然是某个写入者的最后一轮
+        assert_eq!(last, payload(writer, ITERATIONS - 1));
+    }
+
+    #[test]
+    fn test_config_path_builder() {
+        let builder = ConfigPathBuilder::new(PathBuf::from("/test/dir"));
+        let path = builder.build("config.json");
+
+        #[cfg(windows)]
+        assert_eq!(path, PathBuf::from("\\test\\dir\\config.json"));
+
+        #[cfg(not(windows))]
+        assert_eq!(path, PathBuf::from("/test/dir/config.json"));
+    }
+
+    #[test]
+    fn test_build_safe() {
+        let builder = ConfigPathBuilder::new(PathBuf::from("base"));
+        assert_eq!(
+            builder.build_safe("profiles/work/settings.json").unwrap(),
+            PathBuf::from("base").join("profiles/work/settings.json")
+        );
+        assert_eq!(builder.build_safe("con.json").is_err(), cfg!(windows));
+    }
+
+    #[test]
+    fn test_save_json_config_if_absent() {
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("nested").join("seed.json");
+        let defaults = TestConfig {
+            name: "default".to_string(),
+            value: 1,
+        };
+
+        assert!(save_json_config_if_absent(&defaults, &config_path).unwrap());
+
+        let edited = TestConfig {
+            name: "edited".to_string(),
+            value: 2,
+        };
+        save_json_config(&edited, &config_path).unwrap();
+        assert!(!save_json_config_if_absent(&defaults, &config_path).unwrap());
+
+        let loaded: TestConfig = load_json_config(&config_path).unwrap();
+        assert_eq!(loaded, edited);
+    }
+
+    #[test]
+    fn test_validate_and_save() {
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("validated.json");
+        let valid = TestConfig {
+            name: "ok".to_string(),
+            value: 1,
+        };
+        let non_negative = |config: &TestConfig| {
+            if config.value < 0 {
+                Err(vec!["value must not be negative".to_string()])
+            } else {
+                Ok(())
+            }
+        };
+
+        validate_and_save(&valid, &config_path, non_negative).unwrap();
+
+        let invalid = TestConfig {
+            name: "bad".to_string(),
+            value: -1,
+        };
+        let errors = validate_and_save(&invalid, &config_path, non_negative).unwrap_err();
+        assert_eq!(errors, vec!["value must not be negative".to_string()]);
+
+        let loaded: TestConfig = load_json_config(&config_path).unwrap();
+        assert_eq!(loaded, valid);
+    }
+
+    #[test]
+    fn test_toggle_config_flag() {
+        let temp = tempfile::tempdir().unwrap();
+        let config_path = temp.path().join("flags.json");
+        fs::write(&config_path, r#"{"name": "x", "features": {"beta": "yes"}}"#).unwrap();
+
+        assert!(toggle_config_flag(&config_path, "/features/beta").unwrap());
+        assert!(!toggle_config_flag(&config_path, "/features/beta").unwrap());
+        assert!(toggle_config_flag(&config_path, "/autoUpdate").unwrap());
+
+        let loaded: serde_json::Value = load_json_config(&config_path).unwrap();
+        assert_eq!(
+            loaded,
+            serde_json::json!({ "name": "x", "features": { "beta": false }, "autoUpdate": true })
+        );
+    }
+}
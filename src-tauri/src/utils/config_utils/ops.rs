@@ -0,0 +1,274 @@
+//! 批量配置操作
+//!
+//! 界面中一次修改多个字段时，所有操作在内存中依次应用后只写入一次，
+//! 任一操作失败则整体放弃，文件保持不变。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::overrides::coerce_cli_value;
+use super::pointer::{remove_pointer, set_pointer, split_pointer};
+use super::update_json_config;
+
+/// 导出命令使用的程序名
+const CLI_NAME: &str = "workbench";
+
+/// 按 JSON Pointer 执行的配置操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum ConfigOp {
+    /// 写入值，缺失的中间对象会被创建
+    Set { pointer: String, value: Value },
+    /// 删除值，不存在时忽略
+    Delete { pointer: String },
+    /// 切换布尔开关，缺失或不是布尔值时视为 `false`
+    Toggle { pointer: String },
+}
+
+/// 在一次读写中应用一组配置操作
+///
+/// # 参数
+/// - `config_path`: 配置文件路径（不存在时从空对象开始）
+/// - `ops`: 按顺序应用的操作
+///
+/// # 返回值
+/// - `Ok(())`: 所有操作已应用并保存
+/// - `Err(String)`: 第一个失败的操作及原因（形如 `Operation #2 failed: ...`），此时文件保持不变
+///
+/// # 特性
+/// - ✅ 只读取和写入一次，写入期间持有写锁（见 [`update_json_config`]）
+/// - ✅ 全部成功或全部不生效
+pub fn apply_config_ops(config_path: impl AsRef<Path>, ops: &[ConfigOp]) -> Result<(), String> {
+    update_json_config(config_path, |value| {
+        for (index, op) in ops.iter().enumerate() {
+            apply_op(value, op).map_err(|e| format!("Operation #{} failed: {}", index, e))?;
+        }
+        Ok(())
+    })
+}
+
+/// 将配置操作导出为可复制粘贴的命令
+///
+/// - `Set` 输出 `workbench config set <键> <值>`
+/// - `Delete` 输出 `workbench config unset <键>`
+/// - `Toggle` 输出 `workbench config toggle <键>`
+///
+/// 键和值按命令行覆盖参数的规则书写（见 [`apply_cli_overrides`]），解析后还原为同样的操作：
+/// 键使用点分形式（如 `proxy.url`），无法用点分表示时改用 JSON Pointer 形式；
+/// 能按 [`coerce_cli_value`] 原样还原的字符串直接输出，其余值输出为 JSON。
+/// 需要时按 POSIX shell 规则加单引号
+///
+/// [`apply_cli_overrides`]: super::overrides::apply_cli_overrides
+pub fn export_ops_as_commands(ops: &[ConfigOp]) -> Vec<String> {
+    ops.iter()
+        .map(|op| match op {
+            ConfigOp::Set { pointer, value } => format!(
+                "{} config set {} {}",
+                CLI_NAME,
+                shell_quote(&command_key(pointer)),
+                shell_quote(&command_value(value))
+            ),
+            ConfigOp::Delete { pointer } => format!(
+                "{} config unset {}",
+                CLI_NAME,
+                shell_quote(&command_key(pointer))
+            ),
+            ConfigOp::Toggle { pointer } => format!(
+                "{} config toggle {}",
+                CLI_NAME,
+                shell_quote(&command_key(pointer))
+            ),
+        })
+        .collect()
+}
+
+fn command_key(pointer: &str) -> String {
+    match split_pointer(pointer) {
+        Ok(tokens)
+            if !tokens.is_empty()
+                && !tokens[0].starts_with('/')
+                && tokens.iter().all(|t| !t.is_empty() && !t.contains('.')) =>
+        {
+            tokens.join(".")
+        }
+        _ => pointer.to_string(),
+    }
+}
+
+fn command_value(value: &Value) -> String {
+    match value {
+        Value::String(text) if coerce_cli_value(text) == *value => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn shell_quote(text: &str) -> String {
+    let safe = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c));
+    if safe {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+
+pub(crate) fn apply_op(value: &mut Value, op: &ConfigOp) -> Result<(), String> {
+    match op {
+        ConfigOp::Set {
+            pointer,
+            value: new_value,
+        } => set_pointer(value, pointer, new_value.clone()),
+        ConfigOp::Delete { pointer } => {
+            remove_pointer(value, pointer);
+            Ok(())
+        }
+        ConfigOp::Toggle { pointer } => {
+            let enabled = !value
+                .pointer(pointer)
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            set_pointer(value, pointer, Value::Bool(enabled))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::load_json_config;
+    use super::super::overrides::{apply_cli_overrides, dotted_key_to_pointer};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_config_ops() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "sonnet", "verbose": true, "old": 1}"#).unwrap();
+
+        let ops: Vec<ConfigOp> = serde_json::from_value(json!([
+            { "op": "set", "pointer": "/proxy/url", "value": "http://localhost" },
+            { "op": "delete", "pointer": "/old" },
+            { "op": "toggle", "pointer": "/verbose" }
+        ]))
+        .unwrap();
+        apply_config_ops(&path, &ops).unwrap();
+        assert_eq!(
+            load_json_config::<Value>(&path).unwrap(),
+            json!({ "model": "sonnet", "verbose": false, "proxy": { "url": "http://localhost" } })
+        );
+
+        let failing = [
+            ConfigOp::Set {
+                pointer: "/model".to_string(),
+                value: json!("opus"),
+            },
+            ConfigOp::Set {
+                pointer: "/model/name".to_string(),
+                value: json!("x"),
+            },
+        ];
+        let err = apply_config_ops(&path, &failing).unwrap_err();
+        assert!(err.starts_with("Operation #1 failed"), "{}", err);
+        assert_eq!(
+            load_json_config::<Value>(&path).unwrap()["model"],
+            json!("sonnet")
+        );
+    }
+
+    #[test]
+    fn test_export_ops_as_commands() {
+        let ops: Vec<ConfigOp> = serde_json::from_value(json!([
+            { "op": "set", "pointer": "/proxy/url", "value": "http://localhost:8080" },
+            { "op": "set", "pointer": "/timeout", "value": 30 },
+            { "op": "set", "pointer": "/label", "value": "true" },
+            { "op": "set", "pointer": "/note", "value": "it's here" },
+            { "op": "set", "pointer": "/env/a.b", "value": ["x"] },
+            { "op": "set", "pointer": "/~1tmp", "value": null },
+            { "op": "delete", "pointer": "/old" },
+            { "op": "toggle", "pointer": "/verbose" }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            export_ops_as_commands(&ops),
+            vec![
+                "workbench config set proxy.url http://localhost:8080",
+                "workbench config set timeout 30",
+                r#"workbench config set label '"true"'"#,
+                r#"workbench config set note 'it'\''s here'"#,
+                r#"workbench config set /env/a.b '["x"]'"#,
+                "workbench config set '/~1tmp' null",
+                "workbench config unset old",
+                "workbench config toggle verbose",
+            ]
+        );
+    }
+
+    /// 按 POSIX shell 规则拆分单引号引用的参数
+    fn shell_words(command: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut quoted = false;
+        let mut chars = command.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => quoted = !quoted,
+                '\\' if !quoted => word.extend(chars.next()),
+                ' ' if !quoted => words.push(std::mem::take(&mut word)),
+                _ => word.push(c),
+            }
+        }
+        words.push(word);
+        words
+    }
+
+    #[test]
+    fn test_exported_commands_round_trip_through_overrides() {
+        let ops: Vec<ConfigOp> = serde_json::from_value(json!([
+            { "op": "set", "pointer": "/proxy/url", "value": "http://localhost:8080" },
+            { "op": "set", "pointer": "/timeout", "value": 30 },
+            { "op": "set", "pointer": "/ratio", "value": 0.5 },
+            { "op": "set", "pointer": "/label", "value": "true" },
+            { "op": "set", "pointer": "/count", "value": "42" },
+            { "op": "set", "pointer": "/empty", "value": "" },
+            { "op": "set", "pointer": "/note", "value": "it's [here]" },
+            { "op": "set", "pointer": "/quoted", "value": "\"x\"" },
+            { "op": "set", "pointer": "/env/a.b", "value": ["x", { "y": null }] },
+            { "op": "set", "pointer": "/~1tmp", "value": null },
+            { "op": "set", "pointer": "/a~1b", "value": false },
+            { "op": "delete", "pointer": "/old" },
+            { "op": "toggle", "pointer": "/verbose" }
+        ]))
+        .unwrap();
+        let original = json!({ "old": 1, "verbose": false, "env": {} });
+
+        let mut expected = original.clone();
+        for op in &ops {
+            apply_op(&mut expected, op).unwrap();
+        }
+
+        let mut replayed = original;
+        for command in export_ops_as_commands(&ops) {
+            let words = shell_words(&command);
+            assert_eq!(words[..2], ["workbench", "config"], "{}", command);
+            match (words[2].as_str(), &words[3..]) {
+                ("set", [key, raw]) => {
+                    apply_cli_overrides(&mut replayed, &[(key.clone(), raw.clone())]).unwrap()
+                }
+                ("unset", [key]) => {
+                    remove_pointer(&mut replayed, &dotted_key_to_pointer(key).unwrap());
+                }
+                ("toggle", [key]) => {
+                    let pointer = dotted_key_to_pointer(key).unwrap();
+                    apply_op(&mut replayed, &ConfigOp::Toggle { pointer }).unwrap();
+                }
+                _ => panic!("unexpected command {}", command),
+            }
+        }
+        assert_eq!(replayed, expected);
+    }
+}
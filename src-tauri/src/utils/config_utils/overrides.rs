@@ -0,0 +1,154 @@
+//! 命令行覆盖参数
+//!
+//! 支持 `--set key=value` 形式的一次性覆盖：键为点分路径（以 `/` 开头时按 JSON Pointer 处理），
+//! 值自动转换为布尔值、数字、JSON 或字符串后写入配置。
+
+use serde_json::{Number, Value};
+
+use super::pointer::{escape_pointer_token, set_pointer};
+
+/// 解析单个 `key=value` 参数
+pub fn parse_cli_override(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid override {:?}: expected key=value", arg))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("Invalid override {:?}: key must not be empty", arg));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// 将命令行覆盖应用到配置
+///
+/// # 参数
+/// - `value`: 已加载的配置值（原地修改）
+/// - `overrides`: `(键, 原始值)` 列表，如 `("proxy.url", "http://localhost")`；
+///   键中某一段本身包含 `.` 时使用 JSON Pointer 形式，如 `("/env/a.b", "1")`
+///
+/// # 返回值
+/// - `Ok(())`: 全部覆盖已应用
+/// - `Err(Vec<String>)`: 每个非法覆盖对应一条错误；有任何错误时不修改配置
+///
+/// # 值转换规则
+/// - `true` / `false` → 布尔值，`null` → 空值
+/// - 整数或浮点数 → 数字
+/// - 以 `"`、`[` 或 `{` 开头的合法 JSON → 对应的字符串、数组或对象（如 `'"true"'` 为字符串）
+/// - 其他 → 字符串
+pub fn apply_cli_overrides(
+    value: &mut Value,
+    overrides: &[(String, String)],
+) -> Result<(), Vec<String>> {
+    let mut updated = value.clone();
+    let mut errors = Vec::new();
+
+    for (key, raw) in overrides {
+        let pointer = match dotted_key_to_pointer(key) {
+            Ok(pointer) => pointer,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if let Err(e) = set_pointer(&mut updated, &pointer, coerce_cli_value(raw)) {
+            errors.push(format!("Invalid override {:?}: {}", key, e));
+        }
+    }
+
+    if errors.is_empty() {
+        *value = updated;
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 按布尔值/空值 → 数字 → JSON → 字符串的顺序转换命令行值
+pub fn coerce_cli_value(raw: &str) -> Value {
+    match raw {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        "null" => return Value::Null,
+        _ => {}
+    }
+    if let Ok(int) = raw.parse::<i64>() {
+        return Value::Number(int.into());
+    }
+    if let Ok(int) = raw.parse::<u64>() {
+        return Value::Number(int.into());
+    }
+    if let Some(number) = raw.parse::<f64>().ok().and_then(Number::from_f64) {
+        return Value::Number(number);
+    }
+    if raw.starts_with(['"', '[', '{']) {
+        if let Ok(value) = serde_json::from_str(raw) {
+            return value;
+        }
+    }
+    Value::String(raw.to_string())
+}
+
+pub(crate) fn dotted_key_to_pointer(key: &str) -> Result<String, String> {
+    if key.starts_with('/') {
+        return Ok(key.to_string());
+    }
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(format!("Invalid override key {:?}", key));
+    }
+    Ok(key
+        .split('.')
+        .map(|segment| format!("/{}", escape_pointer_token(segment)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_cli_overrides() {
+        let mut value = json!({ "proxy": { "enabled": false }, "tools": ["a", "b"] });
+        let overrides: Vec<(String, String)> = [
+            "proxy.enabled=true",
+            "timeout=30",
+            "model=opus",
+            "tools.1=c",
+            r#"label="true""#,
+            r#"/env/a.b=["x"]"#,
+            "note=[draft",
+        ]
+        .iter()
+        .map(|arg| parse_cli_override(arg).unwrap())
+        .collect();
+
+        apply_cli_overrides(&mut value, &overrides).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "proxy": { "enabled": true },
+                "timeout": 30,
+                "model": "opus",
+                "tools": ["a", "c"],
+                "label": "true",
+                "env": { "a.b": ["x"] },
+                "note": "[draft",
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_overrides_are_rejected() {
+        assert!(parse_cli_override("novalue").is_err());
+        assert!(parse_cli_override("=1").is_err());
+
+        let mut value = json!({ "model": "a" });
+        let overrides = vec![
+            ("a..b".to_string(), "1".to_string()),
+            ("model.name".to_string(), "x".to_string()),
+        ];
+        let errors = apply_cli_overrides(&mut value, &overrides).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(value, json!({ "model": "a" }));
+    }
+}
@@ -0,0 +1,226 @@
+//! 部分解析恢复
+//!
+//! 大型配置中只有一段写错时，整体加载失败会让用户丢失全部设置。
+//! [`load_json_config_partial`] 按顶层键逐段解析，丢弃无法解析的部分并报告被丢弃的键。
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::relaxed::sanitize_lenient_json;
+use super::{decode_config_bytes, load_json_config};
+
+/// 尽力加载配置，跳过无法解析的顶层键
+///
+/// # 返回值
+/// - `Ok((T, dropped))`: 加载到的配置，以及被丢弃的顶层键（为空表示完整加载）；
+///   被丢弃的字段使用 `T::default()` 中的值
+/// - `Err(String)`: 文件无法读取，或不是 JSON 对象且无法解析
+///
+/// # 恢复规则
+/// - 语法错误：按顶层键切分，逐段解析；出错的段被丢弃
+/// - 类型错误：逐个键与默认配置组合后反序列化，不符合字段类型的键被丢弃
+pub fn load_json_config_partial<T>(
+    config_path: impl AsRef<Path>,
+) -> Result<(T, Vec<String>), String>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Default,
+{
+    let path = config_path.as_ref();
+    if let Ok(config) = load_json_config::<T>(path) {
+        return Ok((config, Vec::new()));
+    }
+
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    let (content, _) = decode_config_bytes(&bytes)
+        .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+
+    let (map, mut dropped) = match parse_lenient(&content) {
+        Some(Value::Object(map)) => (map, Vec::new()),
+        Some(_) => return load_json_config(path),
+        None => recover_members(&content)
+            .ok_or_else(|| format!("Failed to parse config from {:?}: not a JSON object", path))?,
+    };
+
+    let defaults = serde_json::to_value(T::default())
+        .map_err(|e| format!("Failed to serialize default config: {}", e))?;
+    let mut merged = match defaults {
+        Value::Object(defaults) => defaults,
+        _ => Map::new(),
+    };
+    for (key, value) in map {
+        let mut candidate = merged.clone();
+        candidate.insert(key.clone(), value);
+        if serde_json::from_value::<T>(Value::Object(candidate.clone())).is_ok() {
+            merged = candidate;
+        } else {
+            dropped.push(key);
+        }
+    }
+
+    let config = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+    if !dropped.is_empty() {
+        log::warn!(
+            "Config {:?} partially loaded, dropped keys: {}",
+            path,
+            dropped.join(", ")
+        );
+    }
+    Ok((config, dropped))
+}
+
+fn parse_lenient(content: &str) -> Option<Value> {
+    serde_json::from_str(content)
+        .or_else(|_| serde_json::from_str(&sanitize_lenient_json(content)))
+        .ok()
+}
+
+/// 按顶层键切分对象并逐段解析；不是对象时返回 `None`
+fn recover_members(content: &str) -> Option<(Map<String, Value>, Vec<String>)> {
+    let body = content.trim_start().strip_prefix('{')?;
+    let mut map = Map::new();
+    let mut dropped = Vec::new();
+
+    let mut rest = body;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() || rest.starts_with('}') {
+            break;
+        }
+
+        let (segment, remaining, at_end) = split_member(rest);
+        rest = remaining;
+        match parse_member(segment) {
+            Ok((key, value)) => {
+                map.insert(key, value);
+            }
+            Err(Some(key)) => dropped.push(key),
+            Err(None) => dropped.push(segment.trim().chars().take(40).collect()),
+        }
+        if at_end {
+            break;
+        }
+    }
+    Some((map, dropped))
+}
+
+/// 取出下一个顶层成员（到深度为 0 的 `,` 或对象结束的 `}` 为止）
+fn split_member(input: &str) -> (&str, &str, bool) {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in input.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' if depth == 0 => return (&input[..index], &input[index..], true),
+            '}' | ']' => depth -= 1,
+            ',' if depth == 0 => return (&input[..index], &input[index + 1..], false),
+            _ => {}
+        }
+    }
+    (input, "", true)
+}
+
+/// 解析 `"key": value`；失败时返回能识别出的键名
+fn parse_member(segment: &str) -> Result<(String, Value), Option<String>> {
+    let segment = segment.trim();
+    let (key_literal, value) = split_key(segment).ok_or(None)?;
+    let key: String = serde_json::from_str(key_literal).map_err(|_| None)?;
+    match value.and_then(parse_lenient) {
+        Some(value) => Ok((key, value)),
+        None => Err(Some(key)),
+    }
+}
+
+/// 拆分键的字符串字面量与冒号后的值
+fn split_key(segment: &str) -> Option<(&str, Option<&str>)> {
+    if !segment.starts_with('"') {
+        return None;
+    }
+    let mut escaped = false;
+    for (index, c) in segment.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+                let (literal, rest) = segment.split_at(index + 1);
+                return Some((literal, rest.trim_start().strip_prefix(':')));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct PartialConfig {
+        model: String,
+        timeout: u32,
+        tools: Vec<String>,
+    }
+
+    #[test]
+    fn test_load_json_config_partial_recovers_from_syntax_errors() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{
+  "model": "opus",
+  "hooks": { "pre": "a" "post": "b" },
+  "tools": ["Read", "Edit"]
+}"#,
+        )
+        .unwrap();
+
+        let (config, dropped): (PartialConfig, _) = load_json_config_partial(&path).unwrap();
+        assert_eq!(config.model, "opus");
+        assert_eq!(config.tools, vec!["Read", "Edit"]);
+        assert_eq!(dropped, vec!["hooks"]);
+    }
+
+    #[test]
+    fn test_load_json_config_partial_drops_mistyped_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"model": "opus", "timeout": "soon", "tools": ["Read"]}"#,
+        )
+        .unwrap();
+
+        let (config, dropped): (PartialConfig, _) = load_json_config_partial(&path).unwrap();
+        assert_eq!(
+            config,
+            PartialConfig {
+                model: "opus".to_string(),
+                timeout: 0,
+                tools: vec!["Read".to_string()],
+            }
+        );
+        assert_eq!(dropped, vec!["timeout"]);
+
+        fs::write(&path, r#"{"model": "sonnet"}"#).unwrap();
+        let (_, dropped): (PartialConfig, _) = load_json_config_partial(&path).unwrap();
+        assert!(dropped.is_empty());
+    }
+}
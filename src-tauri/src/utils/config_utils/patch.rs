@@ -0,0 +1,244 @@
+//! 配置补丁（RFC 6902 JSON Patch）
+//!
+//! 团队共享设置调整时只需要传递差异（"启用这三个工具"），而不是整份配置。
+//! 导出的补丁在每个修改/删除前带有 `test` 操作记录期望的旧值，
+//! 应用时如果目标配置与补丁的基准不一致则整体拒绝。
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use super::diff::{diff_json_configs, ConfigChangeKind};
+use super::pointer::{remove_pointer, set_pointer};
+use super::{load_json_config, save_json_config, update_json_config};
+
+/// 由两个配置生成 JSON Patch
+///
+/// 修改和删除前都会插入 `test` 操作，用于应用时的冲突检测
+pub fn create_config_patch(from: &Value, to: &Value) -> Value {
+    let mut operations = Vec::new();
+    for change in diff_json_configs(from, to) {
+        match change.kind {
+            ConfigChangeKind::Added => operations.push(json!({
+                "op": "add",
+                "path": change.pointer,
+                "value": change.new_value,
+            })),
+            ConfigChangeKind::Removed => {
+                operations.push(json!({
+                    "op": "test",
+                    "path": change.pointer,
+                    "value": change.old_value,
+                }));
+                operations.push(json!({ "op": "remove", "path": change.pointer }));
+            }
+            ConfigChangeKind::Changed => {
+                operations.push(json!({
+                    "op": "test",
+                    "path": change.pointer,
+                    "value": change.old_value,
+                }));
+                operations.push(json!({
+                    "op": "replace",
+                    "path": change.pointer,
+                    "value": change.new_value,
+                }));
+            }
+        }
+    }
+    Value::Array(operations)
+}
+
+/// 将 JSON Patch 应用到配置值
+///
+/// 支持 `add`、`remove`、`replace`、`test` 操作。任何操作失败时配置保持不变。
+/// 除标准的 `test` 失败外，`add` 的目标已存在且值不同同样视为冲突，
+/// 避免补丁悄悄覆盖对方已有的设置
+pub fn apply_patch_to_value(value: &mut Value, patch: &Value) -> Result<(), String> {
+    let operations = patch
+        .as_array()
+        .ok_or_else(|| "Patch must be a JSON array of operations".to_string())?;
+
+    let mut patched = value.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .map_err(|e| format!("Patch operation #{} failed: {}", index, e))?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+/// 导出两个配置文件之间的补丁
+///
+/// # 参数
+/// - `from_path`: 基准配置
+/// - `to_path`: 修改后的配置
+/// - `out_patch`: 补丁输出路径
+///
+/// # 返回值
+/// - `Ok(count)`: 补丁中的操作数量
+pub fn export_config_patch(
+    from_path: impl AsRef<Path>,
+    to_path: impl AsRef<Path>,
+    out_patch: impl AsRef<Path>,
+) -> Result<usize, String> {
+    let from: Value = load_json_config(from_path.as_ref())?;
+    let to: Value = load_json_config(to_path.as_ref())?;
+    let patch = create_config_patch(&normalize_missing(from), &normalize_missing(to));
+    let count = patch.as_array().map_or(0, Vec::len);
+    save_json_config(&patch, out_patch)?;
+    Ok(count)
+}
+
+/// 将补丁文件应用到配置文件
+///
+/// # 返回值
+/// - `Ok(())`: 补丁已应用并保存
+/// - `Err(String)`: 补丁无效或与目标配置冲突；此时配置文件保持不变
+///
+/// # 特性
+/// - ✅ 读取、应用与写回在同一把写锁内完成（见 [`update_json_config`]），并发的保存不会被覆盖
+pub fn apply_config_patch(
+    config_path: impl AsRef<Path>,
+    patch_path: impl AsRef<Path>,
+) -> Result<(), String> {
+    let config_path = config_path.as_ref();
+    let patch_path = patch_path.as_ref();
+    if !patch_path.exists() {
+        return Err(format!("Patch file not found: {:?}", patch_path));
+    }
+
+    let patch: Value = load_json_config(patch_path)?;
+    update_json_config(config_path, |config| {
+        apply_patch_to_value(config, &patch).map_err(|e| {
+            format!(
+                "Failed to apply {:?} to {:?}: {}",
+                patch_path, config_path, e
+            )
+        })
+    })
+}
+
+fn apply_operation(value: &mut Value, operation: &Value) -> Result<(), String> {
+    let op = operation
+        .get("op")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing \"op\"".to_string())?;
+    let path = operation
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing \"path\"".to_string())?;
+    let operand = || {
+        operation
+            .get("value")
+            .cloned()
+            .ok_or_else(|| format!("{} at {} is missing \"value\"", op, path))
+    };
+
+    match op {
+        "test" => {
+            let expected = operand()?;
+            match value.pointer(path) {
+                Some(actual) if *actual == expected => Ok(()),
+                actual => Err(conflict(path, &expected, actual)),
+            }
+        }
+        "add" => {
+            let new_value = operand()?;
+            match value.pointer(path) {
+                Some(existing) if *existing != new_value && !path.ends_with("/-") => {
+                    Err(format!("conflict at {}: key already exists", path))
+                }
+                _ => set_pointer(value, path, new_value),
+            }
+        }
+        "replace" => {
+            let new_value = operand()?;
+            if value.pointer(path).is_none() {
+                return Err(format!("conflict at {}: key does not exist", path));
+            }
+            set_pointer(value, path, new_value)
+        }
+        "remove" => remove_pointer(value, path)
+            .map(|_| ())
+            .ok_or_else(|| format!("conflict at {}: key does not exist", path)),
+        other => Err(format!("unsupported operation {:?}", other)),
+    }
+}
+
+fn conflict(path: &str, expected: &Value, actual: Option<&Value>) -> String {
+    match actual {
+        Some(actual) => format!(
+            "conflict at {}: expected {}, found {}",
+            path, expected, actual
+        ),
+        None => format!("conflict at {}: expected {}, found nothing", path, expected),
+    }
+}
+
+/// 缺失的配置文件视为空对象
+fn normalize_missing(value: Value) -> Value {
+    if value.is_null() {
+        json!({})
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_export_and_apply_config_patch() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("base.json");
+        let tweaked = temp.path().join("tweaked.json");
+        let patch = temp.path().join("team.patch.json");
+        fs::write(
+            &base,
+            r#"{"model": "sonnet", "tools": {"web": false, "bash": true}}"#,
+        )
+        .unwrap();
+        fs::write(
+            &tweaked,
+            r#"{"model": "sonnet", "tools": {"web": true, "mcp": true}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(export_config_patch(&base, &tweaked, &patch).unwrap(), 5);
+
+        let teammate = temp.path().join("teammate.json");
+        fs::write(
+            &teammate,
+            r#"{"model": "opus", "tools": {"web": false, "bash": true}}"#,
+        )
+        .unwrap();
+        apply_config_patch(&teammate, &patch).unwrap();
+        assert_eq!(
+            load_json_config::<Value>(&teammate).unwrap(),
+            json!({ "model": "opus", "tools": { "web": true, "mcp": true } })
+        );
+    }
+
+    #[test]
+    fn test_apply_config_patch_refuses_mismatched_base() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("settings.json");
+        let patch = temp.path().join("p.json");
+        fs::write(&config, r#"{"tools": {"web": "custom"}}"#).unwrap();
+        let patch_value = create_config_patch(
+            &json!({ "tools": { "web": false } }),
+            &json!({ "tools": { "web": true } }),
+        );
+        save_json_config(&patch_value, &patch).unwrap();
+
+        let err = apply_config_patch(&config, &patch).unwrap_err();
+        assert!(err.contains("conflict at /tools/web"), "{}", err);
+        assert_eq!(
+            load_json_config::<Value>(&config).unwrap(),
+            json!({ "tools": { "web": "custom" } })
+        );
+    }
+}
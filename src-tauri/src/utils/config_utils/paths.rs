@@ -0,0 +1,447 @@
+//! 配置中的路径值处理
+//!
+//! 配置里常有相对路径（如 `"scriptPath": "./hooks/run.sh"`），
+//! 应当相对于配置文件所在目录解析，而不是进程当前工作目录。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+#[cfg(test)]
+use serde_json::Value;
+
+/// 将指定键中的相对路径改写为基于 `base_dir` 的绝对路径
+///
+/// # 参数
+/// - `value`: 配置值（原地修改）
+/// - `base_dir`: 配置文件所在目录
+/// - `pointers`: 路径值键的 JSON Pointer 列表
+///
+/// # 返回值
+/// - `Ok(count)`: 被改写的键数量（不存在的键、已是绝对路径或 `~` 开头的值保持不变）
+/// - `Err(String)`: 某个键存在但不是字符串
+#[cfg(test)]
+pub fn resolve_config_paths(
+    value: &mut Value,
+    base_dir: &Path,
+    pointers: &[&str],
+) -> Result<usize, String> {
+    let mut count = 0;
+    for pointer in pointers {
+        let Some(target) = value.pointer_mut(pointer) else {
+            continue;
+        };
+        let Some(raw) = target.as_str() else {
+            return Err(format!("Config path value at {} is not a string", pointer));
+        };
+
+        if raw.is_empty() || raw.starts_with('~') || Path::new(raw).is_absolute() {
+            continue;
+        }
+
+        let resolved = normalize_lexically(&base_dir.join(raw));
+        *target = Value::String(resolved.to_string_lossy().into_owned());
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 将指定键中位于主目录下的绝对路径改写为 `~/` 形式（用于导出）
+///
+/// 例如 `/home/alice/scripts/a.sh` 改写为 `~/scripts/a.sh`，导出的配置在其他用户和机器上同样可用
+///
+/// # 返回值
+/// - `Ok(count)`: 被改写的键数量（不存在的键、不在主目录下的路径保持不变）
+/// - `Err(String)`: 无法获取主目录，或某个键存在但不是字符串
+#[cfg(test)]
+pub fn relativize_home_paths(value: &mut Value, pointers: &[&str]) -> Result<usize, String> {
+    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    relativize_home_paths_in(value, pointers, &home)
+}
+
+/// 将指定键中 `~` 开头的路径展开为绝对路径（用于导入和加载）
+///
+/// # 返回值
+/// - `Ok(count)`: 被改写的键数量
+/// - `Err(String)`: 无法获取主目录，或某个键存在但不是字符串
+#[cfg(test)]
+pub fn expand_home_paths(value: &mut Value, pointers: &[&str]) -> Result<usize, String> {
+    let home = dirs::home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    expand_home_paths_in(value, pointers, &home)
+}
+
+#[cfg(test)]
+fn relativize_home_paths_in(
+    value: &mut Value,
+    pointers: &[&str],
+    home: &Path,
+) -> Result<usize, String> {
+    rewrite_path_values(value, pointers, |raw| {
+        let relative = Path::new(raw).strip_prefix(home).ok()?;
+        let parts: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some(if parts.is_empty() {
+            "~".to_string()
+        } else {
+            format!("~/{}", parts.join("/"))
+        })
+    })
+}
+
+#[cfg(test)]
+fn expand_home_paths_in(
+    value: &mut Value,
+    pointers: &[&str],
+    home: &Path,
+) -> Result<usize, String> {
+    rewrite_path_values(value, pointers, |raw| {
+        let expanded = if raw == "~" {
+            home.to_path_buf()
+        } else {
+            let rest = raw.strip_prefix("~/").or_else(|| raw.strip_prefix("~\\"))?;
+            home.join(rest)
+        };
+        Some(expanded.to_string_lossy().into_owned())
+    })
+}
+
+/// 路径分隔符风格
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathSeparatorStyle {
+    /// 当前平台的分隔符（Windows 为 `\`，其他平台为 `/`）
+    #[default]
+    Native,
+    /// 统一使用 `/`，各平台都能识别，适合需要跨平台共享的配置
+    Forward,
+}
+
+/// 将指定键中路径的分隔符统一为指定风格
+///
+/// 修复从 Windows 复制到 macOS/Linux 的配置中反斜杠路径无法解析的问题
+///
+/// # 返回值
+/// - `Ok(count)`: 被改写的键数量（不存在的键、分隔符已符合要求的值保持不变）
+/// - `Err(String)`: 某个键存在但不是字符串
+#[cfg(test)]
+pub fn normalize_config_path_separators(
+    value: &mut Value,
+    pointers: &[&str],
+    style: PathSeparatorStyle,
+) -> Result<usize, String> {
+    let separator = match style {
+        PathSeparatorStyle::Native => std::path::MAIN_SEPARATOR,
+        PathSeparatorStyle::Forward => '/',
+    };
+    rewrite_path_values(value, pointers, |raw| {
+        let rewritten: String = raw
+            .chars()
+            .map(|c| if c == '/' || c == '\\' { separator } else { c })
+            .collect();
+        (rewritten != raw).then_some(rewritten)
+    })
+}
+
+/// 对指定键的字符串值应用改写，`rewrite` 返回 `None` 时保持不变
+#[cfg(test)]
+fn rewrite_path_values<F>(value: &mut Value, pointers: &[&str], rewrite: F) -> Result<usize, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut count = 0;
+    for pointer in pointers {
+        let Some(target) = value.pointer_mut(pointer) else {
+            continue;
+        };
+        let Some(raw) = target.as_str() else {
+            return Err(format!("Config path value at {} is not a string", pointer));
+        };
+        if let Some(rewritten) = rewrite(raw) {
+            *target = Value::String(rewritten);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// 符号链接最大解析层数（与 Linux 的 `MAXSYMLINKS` 一致）
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+/// 解析配置文件路径上的符号链接，直到指向非链接的目标
+///
+/// dotfiles 管理工具常把 `settings.json` 链接到其他仓库。逐层读取链接并记录已访问的路径，
+/// 链接成环或层数过深时返回明确的错误，而不是无限循环
+///
+/// # 返回值
+/// - `Ok(PathBuf)`: 最终目标路径（目标不存在时返回最后一层链接指向的路径）
+/// - `Err(String)`: 检测到循环链接、层数超过上限或读取链接失败
+pub fn resolve_config_symlinks(path: &Path) -> Result<PathBuf, String> {
+    let mut current = path.to_path_buf();
+    let mut visited = HashSet::new();
+
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        let is_symlink = fs::symlink_metadata(&current)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_symlink {
+            return Ok(current);
+        }
+        if !visited.insert(current.clone()) {
+            return Err(format!(
+                "Symlink cycle detected while resolving {:?} (at {:?})",
+                path, current
+            ));
+        }
+
+        let target = fs::read_link(&current)
+            .map_err(|e| format!("Failed to read symlink {:?}: {}", current, e))?;
+        current = match current.parent() {
+            Some(parent) if target.is_relative() => normalize_lexically(&parent.join(target)),
+            _ => target,
+        };
+    }
+
+    Err(format!(
+        "Too many levels of symlinks while resolving {:?} (limit {})",
+        path, MAX_SYMLINK_DEPTH
+    ))
+}
+
+/// 获取配置文件的规范路径，同一文件的所有别名得到相同的结果
+///
+/// 跟随文件本身及各级父目录的符号链接；文件不存在时规范化其父目录后拼接文件名，
+/// 父目录也不存在时按字面规范化
+pub fn canonical_config_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    let target = resolve_config_symlinks(path).unwrap_or_else(|_| path.to_path_buf());
+    match (target.parent(), target.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| normalize_lexically(&target)),
+        _ => normalize_lexically(&target),
+    }
+}
+
+/// 判断两个配置路径是否指向同一个文件（例如符号链接与其目标）
+///
+/// 缓存和写锁据此把别名视为同一个文件，避免重复缓存和通过别名丢失更新
+#[cfg(test)]
+pub fn same_config_file(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    canonical_config_path(a.as_ref()) == canonical_config_path(b.as_ref())
+}
+
+/// Windows 保留的设备名（不区分大小写，带扩展名同样保留，如 `con.json`）
+#[cfg(test)]
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 查找路径中第一个 Windows 保留设备名的组成部分
+///
+/// 与平台无关，便于在任意平台上检测；是否拒绝由调用方按平台决定。
+#[cfg(test)]
+pub fn find_windows_reserved_component(path: &str) -> Option<&str> {
+    path.split(['/', '\\']).find(|component| {
+        // Windows 会忽略末尾的点和空格，`nul.` 与 `nul` 等价
+        let trimmed = component.trim_end_matches(['.', ' ']);
+        let stem = trimmed.split('.').next().unwrap_or(trimmed).trim_end();
+        WINDOWS_RESERVED_NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(stem))
+    })
+}
+
+/// 在 Windows 上拒绝包含保留设备名的路径（其他平台直接通过）
+#[cfg(test)]
+pub(crate) fn check_portable_name(path: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    if let Some(component) = find_windows_reserved_component(path) {
+        return Err(format!(
+            "{:?} is a reserved device name on Windows and cannot be used in {:?}",
+            component, path
+        ));
+    }
+    #[cfg(not(windows))]
+    let _ = path;
+    Ok(())
+}
+
+/// 去掉路径中的 `.` 并按字面折叠 `..`（不访问文件系统）
+pub(crate) fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_relativize_and_expand_home_paths() {
+        let home = std::env::temp_dir().join("home").join("alice");
+        let script = home.join("scripts").join("a.sh");
+        let mut value = json!({
+            "script": script.to_string_lossy(),
+            "other": home.with_file_name("alice2").join("b.sh").to_string_lossy(),
+            "root": home.to_string_lossy()
+        });
+
+        let pointers = ["/script", "/other", "/root", "/missing"];
+        assert_eq!(
+            relativize_home_paths_in(&mut value, &pointers, &home).unwrap(),
+            2
+        );
+        assert_eq!(value["script"], "~/scripts/a.sh");
+        assert_eq!(value["root"], "~");
+
+        assert_eq!(
+            expand_home_paths_in(&mut value, &pointers, &home).unwrap(),
+            2
+        );
+        assert_eq!(value["script"], json!(script.to_string_lossy()));
+        assert_eq!(value["root"], json!(home.to_string_lossy()));
+
+        let home = dirs::home_dir().unwrap();
+        let mut value = json!({ "root": home.to_string_lossy() });
+        assert_eq!(relativize_home_paths(&mut value, &["/root"]).unwrap(), 1);
+        assert_eq!(value["root"], "~");
+        assert_eq!(expand_home_paths(&mut value, &["/root"]).unwrap(), 1);
+        assert_eq!(value["root"], json!(home.to_string_lossy()));
+    }
+
+    #[test]
+    fn test_resolve_config_paths() {
+        let base = std::env::temp_dir().join("workbench");
+        let mut value = json!({
+            "scriptPath": "./hooks/run.sh",
+            "nested": { "bin": "../bin/tool" },
+            "home": "~/scripts/a.sh",
+            "count": 3
+        });
+
+        let count = resolve_config_paths(
+            &mut value,
+            &base,
+            &["/scriptPath", "/nested/bin", "/home", "/missing"],
+        )
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            value["scriptPath"],
+            json!(base.join("hooks").join("run.sh").to_string_lossy())
+        );
+        assert_eq!(
+            value["nested"]["bin"],
+            json!(std::env::temp_dir()
+                .join("bin")
+                .join("tool")
+                .to_string_lossy())
+        );
+        assert_eq!(value["home"], json!("~/scripts/a.sh"));
+        assert!(resolve_config_paths(&mut value, &base, &["/count"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_config_symlinks_detects_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("dotfiles.json");
+        fs::write(&real, "{}").unwrap();
+        let link = temp.path().join("settings.json");
+        symlink("dotfiles.json", &link).unwrap();
+        assert_eq!(resolve_config_symlinks(&link).unwrap(), real);
+
+        let a = temp.path().join("a.json");
+        let b = temp.path().join("b.json");
+        symlink(&b, &a).unwrap();
+        symlink(&a, &b).unwrap();
+        let err = resolve_config_symlinks(&a).unwrap_err();
+        assert!(err.contains("Symlink cycle"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_same_config_file_follows_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let claude = temp.path().join(".claude");
+        fs::create_dir_all(&dotfiles).unwrap();
+        fs::create_dir_all(&claude).unwrap();
+        fs::write(dotfiles.join("settings.json"), "{}").unwrap();
+        symlink(dotfiles.join("settings.json"), claude.join("settings.json")).unwrap();
+        symlink(&dotfiles, temp.path().join("linked")).unwrap();
+
+        let real = dotfiles.join("settings.json");
+        assert!(same_config_file(claude.join("settings.json"), &real));
+        assert!(same_config_file(
+            claude.join("../dotfiles/settings.json"),
+            &real
+        ));
+        assert!(same_config_file(
+            temp.path().join("linked/new.json"),
+            dotfiles.join("new.json")
+        ));
+        assert!(!same_config_file(claude.join("other.json"), &real));
+    }
+
+    #[test]
+    fn test_find_windows_reserved_component() {
+        assert_eq!(find_windows_reserved_component("con"), Some("con"));
+        assert_eq!(
+            find_windows_reserved_component("profiles/NUL./settings.json"),
+            Some("NUL.")
+        );
+        assert_eq!(
+            find_windows_reserved_component("Com1.json"),
+            Some("Com1.json")
+        );
+        assert_eq!(
+            find_windows_reserved_component("console/settings.json"),
+            None
+        );
+        assert_eq!(find_windows_reserved_component("lpt10.json"), None);
+    }
+
+    #[test]
+    fn test_normalize_config_path_separators() {
+        let mut value = json!({
+            "script": "C:\\Users\\alice\\hooks/run.ps1",
+            "unix": "~/scripts/a.sh",
+        });
+        let pointers = ["/script", "/unix", "/missing"];
+
+        let count =
+            normalize_config_path_separators(&mut value, &pointers, PathSeparatorStyle::Forward)
+                .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(value["script"], "C:/Users/alice/hooks/run.ps1");
+        assert_eq!(value["unix"], "~/scripts/a.sh");
+
+        normalize_config_path_separators(&mut value, &pointers, PathSeparatorStyle::Native)
+            .unwrap();
+        let native = std::path::MAIN_SEPARATOR.to_string();
+        assert_eq!(value["unix"], json!(["~", "scripts", "a.sh"].join(&native)));
+    }
+}
@@ -0,0 +1,153 @@
+//! JSON Pointer（RFC 6901）写操作辅助
+//!
+//! `serde_json::Value::pointer` 只支持读取，这里补充按指针写入和删除，
+//! 写入时会自动创建缺失的中间对象
+
+use serde_json::{Map, Value};
+
+/// 将 JSON Pointer 拆分为已反转义的路径片段
+///
+/// 空字符串表示根节点，返回空列表
+pub fn split_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(format!(
+            "Invalid JSON pointer (must start with '/'): {:?}",
+            pointer
+        ));
+    }
+
+    Ok(pointer[1..]
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// 按指针写入值
+///
+/// - 缺失的中间节点会被创建为对象
+/// - 数组节点支持现有下标和 `-`（追加）
+/// - 指向根节点时整体替换
+pub fn set_pointer(root: &mut Value, pointer: &str, new_value: Value) -> Result<(), String> {
+    let tokens = split_pointer(pointer)?;
+    let Some((last, parents)) = tokens.split_last() else {
+        *root = new_value;
+        return Ok(());
+    };
+
+    let mut current = root;
+    for token in parents {
+        if current.is_null() {
+            *current = Value::Object(Map::new());
+        }
+        current = match current {
+            Value::Object(map) => map
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(Map::new())),
+            Value::Array(items) => {
+                let index = parse_index(token, items.len(), pointer)?;
+                &mut items[index]
+            }
+            _ => {
+                return Err(format!(
+                    "Cannot descend into non-container value at {:?} in {:?}",
+                    token, pointer
+                ))
+            }
+        };
+    }
+
+    if current.is_null() {
+        *current = Value::Object(Map::new());
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last.clone(), new_value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            if last == "-" {
+                items.push(new_value);
+            } else {
+                let index = parse_index(last, items.len(), pointer)?;
+                items[index] = new_value;
+            }
+            Ok(())
+        }
+        _ => Err(format!(
+            "Cannot set {:?}: parent is not an object or array",
+            pointer
+        )),
+    }
+}
+
+/// 按指针删除值，返回被删除的值（不存在时返回 `None`）
+pub fn remove_pointer(root: &mut Value, pointer: &str) -> Option<Value> {
+    let tokens = split_pointer(pointer).ok()?;
+    let (last, parents) = tokens.split_last()?;
+
+    let mut current = root;
+    for token in parents {
+        current = match current {
+            Value::Object(map) => map.get_mut(token)?,
+            Value::Array(items) => items.get_mut(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    match current {
+        Value::Object(map) => map.remove(last),
+        Value::Array(items) => {
+            let index = last.parse::<usize>().ok()?;
+            (index < items.len()).then(|| items.remove(index))
+        }
+        _ => None,
+    }
+}
+
+/// 将路径片段转义为 JSON Pointer 片段
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn parse_index(token: &str, len: usize, pointer: &str) -> Result<usize, String> {
+    let index = token
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid array index {:?} in {:?}", token, pointer))?;
+    if index >= len {
+        return Err(format!(
+            "Array index {} out of bounds (len {}) in {:?}",
+            index, len, pointer
+        ));
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_set_pointer_creates_intermediate_objects() {
+        let mut value = json!({ "keep": 1 });
+        set_pointer(&mut value, "/proxy/url", json!("http://localhost")).unwrap();
+        set_pointer(&mut value, "/a~1b", json!(true)).unwrap();
+
+        assert_eq!(
+            value,
+            json!({ "keep": 1, "proxy": { "url": "http://localhost" }, "a/b": true })
+        );
+    }
+
+    #[test]
+    fn test_remove_pointer() {
+        let mut value = json!({ "tools": ["a", "b"], "x": { "y": 1 } });
+        assert_eq!(remove_pointer(&mut value, "/tools/0"), Some(json!("a")));
+        assert_eq!(remove_pointer(&mut value, "/x/y"), Some(json!(1)));
+        assert_eq!(remove_pointer(&mut value, "/missing/key"), None);
+        assert_eq!(value, json!({ "tools": ["b"], "x": {} }));
+    }
+}
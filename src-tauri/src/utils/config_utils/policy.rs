@@ -0,0 +1,180 @@
+//! 组织策略：限制可配置的模型与 API 端点
+//!
+//! 企业部署时管理员分发一个策略文件，声明允许的模型名称和端点主机，
+//! 加载配置时拒绝违反策略的配置。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::load_json_config;
+
+/// 配置中指定模型的位置
+pub const MODEL_POINTERS: &[&str] = &[
+    "/model",
+    "/env/ANTHROPIC_MODEL",
+    "/env/ANTHROPIC_SMALL_FAST_MODEL",
+];
+
+/// 配置中指定 API 端点的位置
+pub const ENDPOINT_POINTERS: &[&str] = &["/env/ANTHROPIC_BASE_URL"];
+
+/// 配置策略
+///
+/// 未设置的列表表示不限制；设置为空列表表示全部禁止
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPolicy {
+    /// 允许的模型名称
+    #[serde(default)]
+    pub allowed_models: Option<Vec<String>>,
+    /// 允许的端点主机，`*.example.com` 匹配所有子域名
+    #[serde(default)]
+    pub allowed_hosts: Option<Vec<String>>,
+}
+
+/// 检查配置是否符合策略
+///
+/// 检查 [`MODEL_POINTERS`] 中的模型和 [`ENDPOINT_POINTERS`] 中端点的主机，未设置的字段跳过
+///
+/// # 返回值
+/// - `Ok(())`: 符合策略
+/// - `Err(Vec<String>)`: 每个违反策略的字段一条错误，形如 `/model: ...`
+pub fn validate_against_policy(value: &Value, policy: &ConfigPolicy) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if let Some(allowed) = &policy.allowed_models {
+        for (pointer, model) in string_fields(value, MODEL_POINTERS) {
+            if !allowed.iter().any(|name| name == model) {
+                errors.push(format!(
+                    "{}: model {:?} is not allowed by policy",
+                    pointer, model
+                ));
+            }
+        }
+    }
+
+    if let Some(allowed) = &policy.allowed_hosts {
+        for (pointer, endpoint) in string_fields(value, ENDPOINT_POINTERS) {
+            let host = reqwest::Url::parse(endpoint)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase));
+            match host {
+                Some(host) if allowed.iter().any(|pattern| host_matches(pattern, &host)) => {}
+                Some(host) => errors.push(format!(
+                    "{}: endpoint host {:?} is not allowed by policy",
+                    pointer, host
+                )),
+                None => errors.push(format!("{}: {:?} is not a valid URL", pointer, endpoint)),
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 加载配置并按策略检查
+///
+/// # 返回值
+/// - `Ok(T)`: 符合策略的配置（文件不存在时返回 `T::default()`）
+/// - `Err(String)`: 加载失败，或所有违反策略的字段（以 `; ` 分隔）
+pub fn load_json_config_with_policy<T>(
+    config_path: impl AsRef<Path>,
+    policy: &ConfigPolicy,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let raw: Value = load_json_config(path)?;
+    validate_against_policy(&raw, policy)
+        .map_err(|errors| format!("Config {:?} violates policy: {}", path, errors.join("; ")))?;
+    if raw.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+fn string_fields<'a>(
+    value: &'a Value,
+    pointers: &'a [&'a str],
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    pointers.iter().filter_map(move |pointer| {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(|text| (*pointer, text))
+    })
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy() -> ConfigPolicy {
+        serde_json::from_value(json!({
+            "allowedModels": ["sonnet", "claude-sonnet-4-5"],
+            "allowedHosts": ["api.anthropic.com", "*.corp.example"]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_against_policy() {
+        let ok = json!({
+            "model": "sonnet",
+            "env": { "ANTHROPIC_BASE_URL": "https://llm.eu.corp.example/v1" }
+        });
+        assert!(validate_against_policy(&ok, &policy()).is_ok());
+        assert!(
+            validate_against_policy(&json!({ "model": "opus" }), &ConfigPolicy::default()).is_ok()
+        );
+
+        let bad = json!({
+            "model": "opus",
+            "env": {
+                "ANTHROPIC_MODEL": "claude-sonnet-4-5",
+                "ANTHROPIC_BASE_URL": "https://corp.example.evil.com"
+            }
+        });
+        assert_eq!(
+            validate_against_policy(&bad, &policy()).unwrap_err(),
+            vec![
+                "/model: model \"opus\" is not allowed by policy",
+                "/env/ANTHROPIC_BASE_URL: endpoint host \"corp.example.evil.com\" is not allowed by policy"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_json_config_with_policy() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"env": {"ANTHROPIC_BASE_URL": "not a url"}}"#).unwrap();
+
+        let err = load_json_config_with_policy::<Value>(&path, &policy()).unwrap_err();
+        assert!(err.contains("violates policy"), "{}", err);
+        assert!(err.contains("is not a valid URL"), "{}", err);
+
+        let missing: Value =
+            load_json_config_with_policy(temp.path().join("missing.json"), &policy()).unwrap();
+        assert!(missing.is_null());
+    }
+}
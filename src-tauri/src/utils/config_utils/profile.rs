@@ -0,0 +1,460 @@
+//! 配置档案（Profile）管理
+//!
+//! 档案存放在 `~/<subdir>/profiles/<name>/` 目录下，
+//! 当前激活的档案名记录在 `~/<subdir>/profiles/.active` 文件中
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::batch::ConfigBatch;
+use super::diff::{diff_json_configs, ConfigChange};
+use super::pointer::{remove_pointer, set_pointer, split_pointer};
+use super::{load_json_config, ConfigPathBuilder};
+
+/// 档案根目录名
+pub const PROFILES_DIR: &str = "profiles";
+
+/// 记录当前激活档案名的文件
+pub const ACTIVE_PROFILE_FILE: &str = ".active";
+
+/// 删除档案的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemovedProfile {
+    /// 档案名
+    pub name: String,
+    /// 被删除的档案目录
+    pub path: PathBuf,
+    /// 被删除的文件列表（删除前收集）
+    pub removed_files: Vec<PathBuf>,
+}
+
+/// 校验档案名
+///
+/// 拒绝空名称、`.`/`..` 以及包含路径分隔符的名称，
+/// 防止档案操作逃逸出 `profiles` 目录；在 Windows 上同时拒绝 `con` 等保留设备名
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Profile name must not be empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("Invalid profile name: {:?}", name));
+    }
+    if name.contains('/') || name.contains('\\') {
+        return Err(format!(
+            "Profile name must not contain path separators: {:?}",
+            name
+        ));
+    }
+    super::paths::check_portable_name(name)
+}
+
+/// 获取档案目录路径（不检查是否存在）
+pub fn profile_dir(base_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    Ok(base_dir.join(PROFILES_DIR).join(name))
+}
+
+/// 读取当前激活的档案名
+///
+/// # 返回值
+/// - `Ok(Some(name))`: 存在激活的档案
+/// - `Ok(None)`: 未设置激活档案
+pub fn active_profile(base_dir: &Path) -> Result<Option<String>, String> {
+    let marker = base_dir.join(PROFILES_DIR).join(ACTIVE_PROFILE_FILE);
+    if !marker.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&marker)
+        .map_err(|e| format!("Failed to read active profile from {:?}: {}", marker, e))?;
+    let name = content.trim();
+    if name.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(name.to_string()))
+    }
+}
+
+/// 预先检查一组档案是否都存在
+///
+/// 激活档案或合并多个档案前调用，避免因档案名拼写错误在中途失败
+///
+/// # 参数
+/// - `subdir`: 主目录下的子目录名（如 ".claude"）
+/// - `names`: 要检查的档案名
+///
+/// # 返回值
+/// - `Ok(())`: 所有档案都存在
+/// - `Err(Vec<String>)`: 每个名称非法或不存在的档案一条错误
+pub fn validate_profiles_exist(subdir: &str, names: &[&str]) -> Result<(), Vec<String>> {
+    let builder = ConfigPathBuilder::from_home_subdir(subdir).map_err(|e| vec![e])?;
+    validate_profiles_exist_in(builder.base_dir(), names)
+}
+
+/// 在指定基础目录下检查档案是否存在，见 [`validate_profiles_exist`]
+pub fn validate_profiles_exist_in(base_dir: &Path, names: &[&str]) -> Result<(), Vec<String>> {
+    let errors: Vec<String> = names
+        .iter()
+        .filter_map(|name| match profile_dir(base_dir, name) {
+            Ok(dir) if dir.is_dir() => None,
+            Ok(dir) => Some(format!("Profile {:?} not found at {:?}", name, dir)),
+            Err(e) => Some(e),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 安全删除整个档案目录
+///
+/// # 参数
+/// - `subdir`: 主目录下的子目录名（如 ".claude"）
+/// - `name`: 要删除的档案名
+/// - `confirm_token`: 确认令牌，必须与 `name` 完全一致
+///
+/// # 返回值
+/// - `Ok(RemovedProfile)`: 删除成功，包含被删除的目录和文件
+/// - `Err(String)`: 名称非法、令牌不匹配、档案处于激活状态或删除失败
+///
+/// # 特性
+/// - ✅ 要求确认令牌，防止误删其他档案
+/// - ✅ 拒绝删除当前激活的档案
+/// - ✅ 拒绝包含路径分隔符的名称
+pub fn delete_profile(
+    subdir: &str,
+    name: &str,
+    confirm_token: &str,
+) -> Result<RemovedProfile, String> {
+    let builder = ConfigPathBuilder::from_home_subdir(subdir)?;
+    delete_profile_in(builder.base_dir(), name, confirm_token)
+}
+
+/// 在指定基础目录下删除档案，见 [`delete_profile`]
+pub fn delete_profile_in(
+    base_dir: &Path,
+    name: &str,
+    confirm_token: &str,
+) -> Result<RemovedProfile, String> {
+    let dir = profile_dir(base_dir, name)?;
+
+    if confirm_token != name {
+        return Err(format!(
+            "Confirmation token does not match profile name {:?}",
+            name
+        ));
+    }
+
+    if active_profile(base_dir)?.as_deref() == Some(name) {
+        return Err(format!(
+            "Cannot delete the currently active profile {:?}",
+            name
+        ));
+    }
+
+    // 使用 symlink_metadata：档案目录若为符号链接则拒绝删除，避免误删链接目标
+    let metadata = fs::symlink_metadata(&dir)
+        .map_err(|e| format!("Profile {:?} not found at {:?}: {}", name, dir, e))?;
+    if !metadata.is_dir() {
+        return Err(format!("Profile path {:?} is not a directory", dir));
+    }
+
+    let removed_files: Vec<PathBuf> = walkdir::WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_type().is_dir())
+        .map(|entry| entry.into_path())
+        .collect();
+
+    fs::remove_dir_all(&dir)
+        .map_err(|e| format!("Failed to delete profile directory {:?}: {}", dir, e))?;
+
+    log::info!(
+        "Deleted profile {:?} ({} files) at {:?}",
+        name,
+        removed_files.len(),
+        dir
+    );
+
+    Ok(RemovedProfile {
+        name: name.to_string(),
+        path: dir,
+        removed_files,
+    })
+}
+
+/// 比较两个档案中同名配置文件的差异
+///
+/// # 参数
+/// - `subdir`: 主目录下的子目录名（如 ".claude"）
+/// - `base`: 作为基准的档案名（如团队共享的基线档案）
+/// - `other`: 要比较的档案名
+/// - `filename`: 配置文件名（如 "settings.json"）
+///
+/// # 返回值
+/// 从 `base` 到 `other` 的差异列表；文件不存在时视为空对象
+pub fn diff_profiles(
+    subdir: &str,
+    base: &str,
+    other: &str,
+    filename: &str,
+) -> Result<Vec<ConfigChange>, String> {
+    let builder = ConfigPathBuilder::from_home_subdir(subdir)?;
+    diff_profiles_in(builder.base_dir(), base, other, filename)
+}
+
+/// 在指定基础目录下比较两个档案，见 [`diff_profiles`]
+pub fn diff_profiles_in(
+    base_dir: &Path,
+    base: &str,
+    other: &str,
+    filename: &str,
+) -> Result<Vec<ConfigChange>, String> {
+    let base_value = load_profile_file(base_dir, base, filename)?;
+    let other_value = load_profile_file(base_dir, other, filename)?;
+    Ok(diff_json_configs(&base_value, &other_value))
+}
+
+/// 在多个档案的同名配置文件中移动同一个设置
+///
+/// 设置改名时用于一次性迁移用户的所有档案
+///
+/// # 参数
+/// - `subdir`: 主目录下的子目录名（如 ".claude"）
+/// - `profiles`: 档案名列表
+/// - `filename`: 配置文件名（如 "settings.json"）
+/// - `old_pointer` / `new_pointer`: 旧位置和新位置的 JSON Pointer
+///
+/// # 返回值
+/// - `Ok(changed)`: 包含 `old_pointer` 并已迁移的档案名（按 `profiles` 的顺序）
+/// - `Err(String)`: 档案不存在、某个档案的 `new_pointer` 已有值或写入失败；此时所有档案都保持不变
+///
+/// # 特性
+/// - ✅ 所有档案的修改通过 [`ConfigBatch`] 一起提交，不会只迁移一部分档案
+/// - ✅ 不包含 `old_pointer` 的档案不会被改写
+pub fn rename_key_in_profiles(
+    subdir: &str,
+    profiles: &[&str],
+    filename: &str,
+    old_pointer: &str,
+    new_pointer: &str,
+) -> Result<Vec<String>, String> {
+    let builder = ConfigPathBuilder::from_home_subdir(subdir)?;
+    rename_key_in_profiles_in(
+        builder.base_dir(),
+        profiles,
+        filename,
+        old_pointer,
+        new_pointer,
+    )
+}
+
+/// 在指定基础目录下迁移档案中的设置，见 [`rename_key_in_profiles`]
+pub fn rename_key_in_profiles_in(
+    base_dir: &Path,
+    profiles: &[&str],
+    filename: &str,
+    old_pointer: &str,
+    new_pointer: &str,
+) -> Result<Vec<String>, String> {
+    for pointer in [old_pointer, new_pointer] {
+        if split_pointer(pointer)?.is_empty() {
+            return Err("Cannot rename the root of a config".to_string());
+        }
+    }
+    if old_pointer == new_pointer {
+        return Err(format!("Old and new pointer are both {}", old_pointer));
+    }
+    validate_profiles_exist_in(base_dir, profiles).map_err(|errors| errors.join("; "))?;
+
+    let mut batch = ConfigBatch::new(base_dir);
+    let mut changed = Vec::new();
+    for name in profiles {
+        let mut value = load_profile_file(base_dir, name, filename)?;
+        let Some(moved) = remove_pointer(&mut value, old_pointer) else {
+            continue;
+        };
+        if value.pointer(new_pointer).is_some() {
+            return Err(format!(
+                "Profile {:?} already has a value at {}",
+                name, new_pointer
+            ));
+        }
+        set_pointer(&mut value, new_pointer, moved).map_err(|e| {
+            format!(
+                "Failed to move {} in profile {:?}: {}",
+                old_pointer, name, e
+            )
+        })?;
+        batch.stage(&value, profile_dir(base_dir, name)?.join(filename))?;
+        changed.push(name.to_string());
+    }
+
+    batch.commit()?;
+    log::info!(
+        "Moved {} to {} in {} profile(s)",
+        old_pointer,
+        new_pointer,
+        changed.len()
+    );
+    Ok(changed)
+}
+
+fn load_profile_file(base_dir: &Path, name: &str, filename: &str) -> Result<Value, String> {
+    let dir = profile_dir(base_dir, name)?;
+    if !dir.is_dir() {
+        return Err(format!("Profile {:?} not found at {:?}", name, dir));
+    }
+
+    let value: Value = load_json_config(dir.join(filename))?;
+    Ok(if value.is_null() {
+        Value::Object(Default::default())
+    } else {
+        value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_profile(base: &Path, name: &str) {
+        let dir = base.join(PROFILES_DIR).join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("settings.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_delete_profile_requires_matching_token() {
+        let temp = tempfile::tempdir().unwrap();
+        make_profile(temp.path(), "work");
+
+        assert!(delete_profile_in(temp.path(), "work", "Work").is_err());
+        assert!(temp.path().join(PROFILES_DIR).join("work").exists());
+
+        let removed = delete_profile_in(temp.path(), "work", "work").unwrap();
+        assert_eq!(removed.removed_files.len(), 1);
+        assert!(!removed.path.exists());
+    }
+
+    #[test]
+    fn test_delete_profile_refuses_active_and_separators() {
+        let temp = tempfile::tempdir().unwrap();
+        make_profile(temp.path(), "work");
+        fs::write(
+            temp.path().join(PROFILES_DIR).join(ACTIVE_PROFILE_FILE),
+            "work\n",
+        )
+        .unwrap();
+
+        assert!(delete_profile_in(temp.path(), "work", "work").is_err());
+        assert!(delete_profile_in(temp.path(), "../work", "../work").is_err());
+        assert!(delete_profile_in(temp.path(), "a\\b", "a\\b").is_err());
+    }
+
+    #[test]
+    fn test_diff_profiles_against_baseline() {
+        let temp = tempfile::tempdir().unwrap();
+        make_profile(temp.path(), "baseline");
+        make_profile(temp.path(), "work");
+        fs::write(
+            temp.path().join(PROFILES_DIR).join("work/settings.json"),
+            r#"{"model": "opus"}"#,
+        )
+        .unwrap();
+
+        let changes = diff_profiles_in(temp.path(), "baseline", "work", "settings.json").unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].pointer, "/model");
+        assert!(diff_profiles_in(temp.path(), "baseline", "missing", "settings.json").is_err());
+    }
+
+    #[test]
+    fn test_validate_profiles_exist() {
+        let temp = tempfile::tempdir().unwrap();
+        make_profile(temp.path(), "baseline");
+        make_profile(temp.path(), "work");
+
+        assert!(validate_profiles_exist_in(temp.path(), &["baseline", "work"]).is_ok());
+        let errors =
+            validate_profiles_exist_in(temp.path(), &["work", "wrok", "../baseline"]).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(
+            errors[0].starts_with("Profile \"wrok\" not found"),
+            "{}",
+            errors[0]
+        );
+        assert!(errors[1].contains("path separators"), "{}", errors[1]);
+    }
+
+    #[test]
+    fn test_home_profile_helpers_use_home_subdir() {
+        let subdir = format!(".claude-workbench-test-{}", uuid::Uuid::new_v4().simple());
+        assert!(!dirs::home_dir().unwrap().join(&subdir).exists());
+
+        let errors = validate_profiles_exist(&subdir, &["work"]).unwrap_err();
+        assert!(
+            errors[0].starts_with("Profile \"work\" not found"),
+            "{}",
+            errors[0]
+        );
+        assert!(delete_profile(&subdir, "work", "work").is_err());
+        assert!(diff_profiles(&subdir, "baseline", "work", "settings.json").is_err());
+        assert!(rename_key_in_profiles(&subdir, &["work"], "settings.json", "/a", "/b").is_err());
+    }
+
+    #[test]
+    fn test_rename_key_in_profiles() {
+        let temp = tempfile::tempdir().unwrap();
+        for name in ["baseline", "work", "personal"] {
+            make_profile(temp.path(), name);
+        }
+        let settings = |name: &str| {
+            temp.path()
+                .join(PROFILES_DIR)
+                .join(name)
+                .join("settings.json")
+        };
+        fs::write(settings("baseline"), r#"{"proxyUrl": "http://a"}"#).unwrap();
+        fs::write(
+            settings("work"),
+            r#"{"proxyUrl": "http://b", "model": "opus"}"#,
+        )
+        .unwrap();
+        let profiles = ["baseline", "work", "personal"];
+
+        let changed = rename_key_in_profiles_in(
+            temp.path(),
+            &profiles,
+            "settings.json",
+            "/proxyUrl",
+            "/proxy/url",
+        )
+        .unwrap();
+        assert_eq!(changed, vec!["baseline", "work"]);
+        let work: Value = load_json_config(settings("work")).unwrap();
+        assert_eq!(
+            work,
+            serde_json::json!({ "model": "opus", "proxy": { "url": "http://b" } })
+        );
+        assert_eq!(fs::read_to_string(settings("personal")).unwrap(), "{}");
+
+        // 任一档案冲突时全部保持不变
+        fs::write(settings("baseline"), r#"{"model": "haiku"}"#).unwrap();
+        let err =
+            rename_key_in_profiles_in(temp.path(), &profiles, "settings.json", "/model", "/proxy")
+                .unwrap_err();
+        assert!(err.contains("already has a value at /proxy"), "{}", err);
+        assert_eq!(
+            fs::read_to_string(settings("baseline")).unwrap(),
+            r#"{"model": "haiku"}"#
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! 配置写入频率上限
+//!
+//! 有缺陷的界面代码可能在循环中反复保存同一个配置，拖慢磁盘并掩盖问题。
+//! 设置上限后，同一文件在时间窗口内的写入次数超过上限时，多出的写入会被拒绝并记录警告。
+//! 这是防止写入风暴的安全阀，不是防抖：正常的保存频率不受影响。默认不限制。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// 写入频率上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigWriteLimit {
+    /// 时间窗口内同一文件允许的最大写入次数
+    pub max_writes: usize,
+    /// 统计写入次数的滑动时间窗口
+    pub window: Duration,
+}
+
+static WRITE_LIMIT: RwLock<Option<ConfigWriteLimit>> = RwLock::new(None);
+static RECENT_WRITES: Mutex<BTreeMap<PathBuf, VecDeque<Instant>>> = Mutex::new(BTreeMap::new());
+
+/// 设置（或传入 `None` 取消）配置写入频率上限
+#[cfg(test)]
+pub fn set_config_write_limit(limit: Option<ConfigWriteLimit>) {
+    match WRITE_LIMIT.write() {
+        Ok(mut guard) => *guard = limit,
+        Err(poisoned) => *poisoned.into_inner() = limit,
+    }
+}
+
+/// 获取当前的写入频率上限
+pub fn config_write_limit() -> Option<ConfigWriteLimit> {
+    WRITE_LIMIT.read().ok().and_then(|guard| *guard)
+}
+
+/// 当前时间窗口内对该文件的写入次数（未设置上限时为 0）
+#[cfg(test)]
+pub fn recent_config_writes(config_path: impl AsRef<Path>) -> usize {
+    let Some(limit) = config_write_limit() else {
+        return 0;
+    };
+    let path = config_path.as_ref();
+    let mut writes = recent_writes();
+    let count = writes
+        .get_mut(path)
+        .map_or(0, |history| prune(history, limit.window, Instant::now()));
+    if count == 0 {
+        writes.remove(path);
+    }
+    count
+}
+
+/// 写入前检查频率上限，允许写入时记录本次写入
+pub(crate) fn admit_write(path: &Path) -> Result<(), String> {
+    admit_writes([path])
+}
+
+/// 一次写入多个文件前检查频率上限：任一文件超限时全部拒绝，且不记录任何写入
+pub(crate) fn admit_writes<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Result<(), String> {
+    let Some(limit) = config_write_limit() else {
+        return Ok(());
+    };
+    let paths: Vec<&Path> = paths.into_iter().collect();
+    admit_in(&mut recent_writes(), &paths, limit, Instant::now())
+}
+
+fn admit_in(
+    writes: &mut BTreeMap<PathBuf, VecDeque<Instant>>,
+    paths: &[&Path],
+    limit: ConfigWriteLimit,
+    now: Instant,
+) -> Result<(), String> {
+    // 顺带清理窗口内已没有写入的文件，记录表不会随写过的路径无限增长
+    writes.retain(|_, history| prune(history, limit.window, now) > 0);
+
+    for path in paths {
+        if writes.get(*path).map_or(0, VecDeque::len) >= limit.max_writes {
+            let message = format!(
+                "Too many writes to {:?}: more than {} in {} ms, write rejected",
+                path,
+                limit.max_writes,
+                limit.window.as_millis()
+            );
+            log::warn!("{}", message);
+            return Err(message);
+        }
+    }
+    for path in paths {
+        writes.entry(path.to_path_buf()).or_default().push_back(now);
+    }
+    Ok(())
+}
+
+/// 丢弃窗口外的写入记录，返回窗口内的写入次数
+fn prune(history: &mut VecDeque<Instant>, window: Duration, now: Instant) -> usize {
+    while history
+        .front()
+        .is_some_and(|written| now.duration_since(*written) >= window)
+    {
+        history.pop_front();
+    }
+    history.len()
+}
+
+fn recent_writes() -> std::sync::MutexGuard<'static, BTreeMap<PathBuf, VecDeque<Instant>>> {
+    RECENT_WRITES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit_rejects_writes_over_the_limit() {
+        let mut writes = BTreeMap::new();
+        let limit = ConfigWriteLimit {
+            max_writes: 2,
+            window: Duration::from_secs(1),
+        };
+        let path = Path::new("settings.json");
+        let start = Instant::now();
+
+        assert!(admit_in(&mut writes, &[path], limit, start).is_ok());
+        assert!(admit_in(&mut writes, &[path], limit, start).is_ok());
+        let err = admit_in(
+            &mut writes,
+            &[path],
+            limit,
+            start + Duration::from_millis(500),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Too many writes"), "{}", err);
+
+        // 其他文件不受影响；批量写入中任一文件超限时整批拒绝
+        let other = Path::new("other.json");
+        assert!(admit_in(&mut writes, &[other], limit, start).is_ok());
+        assert!(admit_in(&mut writes, &[other, path], limit, start).is_err());
+        assert_eq!(writes[other].len(), 1);
+
+        // 窗口过去后恢复，过期的记录被清理
+        assert!(admit_in(&mut writes, &[path], limit, start + Duration::from_secs(1)).is_ok());
+        assert_eq!(writes.keys().collect::<Vec<_>>(), vec![path]);
+    }
+
+    #[test]
+    fn test_recent_config_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        assert_eq!(recent_config_writes(&path), 0);
+
+        // 上限足够大，同时运行的其他测试不会被拒绝
+        set_config_write_limit(Some(ConfigWriteLimit {
+            max_writes: 10_000,
+            window: Duration::from_secs(60),
+        }));
+        super::super::save_json_config(&serde_json::json!({ "a": 1 }), &path).unwrap();
+        super::super::save_json_config(&serde_json::json!({ "a": 2 }), &path).unwrap();
+        assert_eq!(recent_config_writes(&path), 2);
+
+        set_config_write_limit(None);
+        assert_eq!(config_write_limit(), None);
+        assert_eq!(recent_config_writes(&path), 0);
+    }
+}
@@ -0,0 +1,76 @@
+//! 只读配置视图
+//!
+//! 交给不受信任的插件代码的配置只允许读取：[`ReadOnlyConfig`] 只提供不可变访问，
+//! 既没有保存方法，也不记录来源路径，插件无法借此写回 `~/.claude`。
+//! 边界由类型保证，而不是依靠约定。
+
+use std::ops::Deref;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::load_json_config;
+
+/// 只读的配置值
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadOnlyConfig<T> {
+    value: T,
+}
+
+impl<T> ReadOnlyConfig<T> {
+    /// 包装已加载的配置
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    /// 配置的不可变引用
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> ReadOnlyConfig<T>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    /// 加载配置文件（与 [`load_json_config`] 相同，不存在时为默认值）
+    pub fn load(config_path: impl AsRef<Path>) -> Result<Self, String> {
+        load_json_config(config_path).map(Self::new)
+    }
+}
+
+impl<T> Deref for ReadOnlyConfig<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// 序列化为配置本身，便于通过 IPC 传给插件
+impl<T: Serialize> Serialize for ReadOnlyConfig<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_read_only_config() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "opus"}"#).unwrap();
+
+        let config: ReadOnlyConfig<Value> = ReadOnlyConfig::load(&path).unwrap();
+        assert_eq!(config["model"], "opus");
+        assert_eq!(config.get(), &json!({ "model": "opus" }));
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            json!({ "model": "opus" })
+        );
+    }
+}
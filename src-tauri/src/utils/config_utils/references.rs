@@ -0,0 +1,161 @@
+//! 检查配置中引用的文件是否存在
+//!
+//! 配置会引用脚本、hook 路径和 MCP 服务器程序，引用失效时只会在运行时报出难以理解的错误。
+//! 这里提前解析这些路径，供界面在对应设置旁显示警告。
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+/// 检查配置中引用的路径
+///
+/// # 参数
+/// - `value`: 配置
+/// - `base_dir`: 相对路径的基准目录
+/// - `pointers`: 路径类设置的 JSON Pointer；值可以是字符串或字符串数组
+///
+/// # 返回值
+/// 每个引用一条 `(JSON Pointer, 解析后的路径, 是否有效)`；数组中的元素使用元素自己的指针。
+/// 不存在的指针或非字符串值被忽略
+///
+/// # 解析规则
+/// - `~/` 开头的路径相对于用户主目录
+/// - 不含路径分隔符的名称（如 `npx`）视为命令，在 `PATH` 中查找
+/// - 其余相对路径相对于 `base_dir`
+/// - 有效指存在且为普通文件；Unix 上命令还需要具有可执行权限
+pub fn validate_referenced_paths(
+    value: &Value,
+    base_dir: impl AsRef<Path>,
+    pointers: &[&str],
+) -> Vec<(String, PathBuf, bool)> {
+    let base_dir = base_dir.as_ref();
+    let mut results = Vec::new();
+
+    for pointer in pointers {
+        match value.pointer(pointer) {
+            Some(Value::String(reference)) => {
+                results.push(check_reference(pointer, reference, base_dir));
+            }
+            Some(Value::Array(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    if let Some(reference) = item.as_str() {
+                        let item_pointer = format!("{}/{}", pointer, index);
+                        results.push(check_reference(&item_pointer, reference, base_dir));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    results
+}
+
+fn check_reference(pointer: &str, reference: &str, base_dir: &Path) -> (String, PathBuf, bool) {
+    let is_command = !reference.contains('/') && !reference.contains('\\') && reference != "~";
+    let (path, valid) = if is_command {
+        match find_in_path(reference) {
+            Some(path) => (path, true),
+            None => (base_dir.join(reference), false),
+        }
+    } else {
+        let path = expand_reference(reference, base_dir);
+        let valid = path.is_file();
+        (path, valid)
+    };
+    (pointer.to_string(), path, valid)
+}
+
+fn expand_reference(reference: &str, base_dir: &Path) -> PathBuf {
+    if let Some(rest) = reference.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = Path::new(reference);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
+fn find_in_path(command: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidate = dir.join(command);
+        if is_executable(&candidate) {
+            return Some(candidate);
+        }
+        #[cfg(windows)]
+        for extension in ["exe", "cmd", "bat"] {
+            let candidate = candidate.with_extension(extension);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        None
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_validate_referenced_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir(temp.path().join("scripts")).unwrap();
+        fs::write(temp.path().join("scripts/pre.sh"), "#!/bin/sh\n").unwrap();
+
+        let config = json!({
+            "hooks": ["scripts/pre.sh", "scripts/missing.sh", 3],
+            "statusLine": "./scripts",
+            "mcp": { "command": "definitely-not-a-real-command-xyz" }
+        });
+        let results = validate_referenced_paths(
+            &config,
+            temp.path(),
+            &["/hooks", "/statusLine", "/mcp/command", "/absent"],
+        );
+
+        assert_eq!(
+            results,
+            vec![
+                (
+                    "/hooks/0".to_string(),
+                    temp.path().join("scripts/pre.sh"),
+                    true
+                ),
+                (
+                    "/hooks/1".to_string(),
+                    temp.path().join("scripts/missing.sh"),
+                    false
+                ),
+                (
+                    "/statusLine".to_string(),
+                    temp.path().join("scripts"),
+                    false
+                ),
+                (
+                    "/mcp/command".to_string(),
+                    temp.path().join("definitely-not-a-real-command-xyz"),
+                    false
+                ),
+            ]
+        );
+    }
+}
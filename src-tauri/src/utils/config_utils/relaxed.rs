@@ -0,0 +1,180 @@
+//! 宽松 JSON 的兼容处理
+//!
+//! 用户常把带尾随逗号或注释的 JSON 粘贴进 settings.json。这里不引入完整的 JSON5，
+//! 只去掉注释和尾随逗号得到严格 JSON：[`load_json_config`](super::load_json_config)
+//! 严格解析失败时以此回退，[`rewrite_config_strict`] 可将文件改写为严格格式。
+
+#[cfg(test)]
+use std::fs;
+#[cfg(test)]
+use std::path::Path;
+
+#[cfg(test)]
+use super::atomic::atomic_write;
+#[cfg(test)]
+use super::decode_config_bytes;
+#[cfg(test)]
+use super::lock::lock_config;
+#[cfg(test)]
+use super::rate_limit::admit_write;
+
+/// 去掉 `//` 行注释、`/* */` 块注释和尾随逗号
+///
+/// 字符串内的内容保持不变；其余格式（缩进、换行）尽量保留
+pub fn sanitize_lenient_json(raw: &str) -> String {
+    strip_trailing_commas(&strip_comments(raw))
+}
+
+/// 将宽松 JSON 配置文件改写为严格 JSON
+///
+/// # 返回值
+/// - `Ok(true)`: 文件已改写
+/// - `Ok(false)`: 文件不存在或已是严格 JSON，未做修改
+/// - `Err(String)`: 去掉注释和尾随逗号后仍无法解析，或写入失败
+#[cfg(test)]
+pub fn rewrite_config_strict(config_path: impl AsRef<Path>) -> Result<bool, String> {
+    let path = config_path.as_ref();
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let _lock = lock_config(path)?;
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    let (content, _) = decode_config_bytes(&bytes)
+        .map_err(|e| format!("Failed to read config from {:?}: {}", path, e))?;
+    if serde_json::from_str::<serde_json::Value>(&content).is_ok() {
+        return Ok(false);
+    }
+
+    let strict = sanitize_lenient_json(&content);
+    serde_json::from_str::<serde_json::Value>(&strict)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+    admit_write(path)?;
+    atomic_write(path, strict.as_bytes())?;
+    log::info!("Rewrote {:?} as strict JSON", path);
+    Ok(true)
+}
+
+fn strip_comments(raw: &str) -> String {
+    let mut output = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                // 保留换行，错误信息中的行号不受影响
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+fn strip_trailing_commas(raw: &str) -> String {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut output = String::with_capacity(raw.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[index + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_sanitize_lenient_json() {
+        let raw = r#"{
+  // 模型
+  "model": "opus", /* inline */
+  "url": "http://example.com/a,}",
+  "note": "keeps // and /* */ in strings",
+  "list": [1, 2, 3,],
+}"#;
+        let strict: Value = serde_json::from_str(&sanitize_lenient_json(raw)).unwrap();
+        assert_eq!(
+            strict,
+            json!({
+                "model": "opus",
+                "url": "http://example.com/a,}",
+                "note": "keeps // and /* */ in strings",
+                "list": [1, 2, 3]
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_falls_back_and_rewrite_config_strict() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, "{\n  \"model\": \"opus\", // pasted\n}\n").unwrap();
+
+        let loaded: Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(loaded, json!({ "model": "opus" }));
+
+        assert!(rewrite_config_strict(&path).unwrap());
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(serde_json::from_str::<Value>(&content).is_ok());
+        assert!(!rewrite_config_strict(&path).unwrap());
+
+        fs::write(&path, "{ broken").unwrap();
+        assert!(rewrite_config_strict(&path).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! 配置变化后通知 Claude CLI 重新加载
+//!
+//! 在界面中修改 settings.json 后，向已登记的 CLI 子进程发送重新加载信号，
+//! 修改无需手动重启即可生效。Unix 上发送 `SIGHUP`；其他平台没有等价的信号，
+//! 发送时返回错误，调用方应改为重启子进程。
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::watch::{watch_config_changes, ConfigWatcher};
+
+/// 接收重新加载信号的子进程登记表
+///
+/// 可克隆，克隆之间共享登记的 PID；子进程重启后重新登记即可
+#[derive(Debug, Clone, Default)]
+pub struct ReloadTarget {
+    pid: Arc<Mutex<Option<u32>>>,
+}
+
+impl ReloadTarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记子进程 PID（替换之前的登记）
+    pub fn register(&self, pid: u32) {
+        *self.pid.lock().unwrap_or_else(|e| e.into_inner()) = Some(pid);
+    }
+
+    /// 取消登记（子进程退出后调用）
+    pub fn clear(&self) {
+        *self.pid.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// 当前登记的 PID
+    pub fn pid(&self) -> Option<u32> {
+        *self.pid.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 向进程发送重新加载信号
+///
+/// # 返回值
+/// - `Ok(())`: 信号已发送
+/// - `Err(String)`: 进程不存在、无权限，或当前平台不支持
+pub fn send_reload_signal(pid: u32) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use rustix::process::{kill_process, Pid, Signal};
+
+        let target = i32::try_from(pid)
+            .ok()
+            .and_then(Pid::from_raw)
+            .ok_or_else(|| format!("Invalid PID {}", pid))?;
+        kill_process(target, Signal::HUP)
+            .map_err(|e| format!("Failed to send SIGHUP to PID {}: {}", pid, e))
+    }
+
+    #[cfg(not(unix))]
+    {
+        Err(format!(
+            "Reload signals are not supported on this platform (PID {})",
+            pid
+        ))
+    }
+}
+
+/// 监听配置文件，变化后向登记的子进程发送重新加载信号
+///
+/// # 参数
+/// - `config_path`: 被监听的配置文件（如 `~/.claude/settings.json`）
+/// - `target`: 子进程登记表，未登记时变化被忽略
+/// - `interval`: 轮询间隔
+///
+/// # 特性
+/// - ✅ 发送失败（如子进程已退出）只记录警告，监听继续
+pub fn watch_config_and_reload(
+    config_path: impl AsRef<Path>,
+    target: ReloadTarget,
+    interval: Duration,
+) -> ConfigWatcher {
+    watch_config_changes(config_path, interval, move |path| {
+        let Some(pid) = target.pid() else {
+            return;
+        };
+        match send_reload_signal(pid) {
+            Ok(()) => log::info!("Config {:?} changed, signaled PID {} to reload", path, pid),
+            Err(e) => log::warn!("Config {:?} changed but reload failed: {}", path, e),
+        }
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_watch_config_and_reload_sends_sighup() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("settings.json");
+        let marker = temp.path().join("reloaded");
+        fs::write(&config, "{}").unwrap();
+
+        let script = format!(
+            "trap 'touch {}; exit 0' HUP; while :; do sleep 0.05; done",
+            marker.display()
+        );
+        let mut child = Command::new("sh").args(["-c", &script]).spawn().unwrap();
+
+        let target = ReloadTarget::new();
+        let watcher = watch_config_and_reload(&config, target.clone(), Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!marker.exists(), "watcher must not fire on startup");
+
+        target.register(child.id());
+        fs::write(&config, r#"{"model": "opus"}"#).unwrap();
+        for _ in 0..200 {
+            if marker.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        watcher.stop();
+        let _ = child.kill();
+        let _ = child.wait();
+        assert!(marker.exists(), "child did not receive SIGHUP");
+
+        target.clear();
+        assert_eq!(target.pid(), None);
+
+        // 已退出的进程和非法 PID 返回错误
+        assert!(send_reload_signal(child.id()).is_err());
+        assert!(send_reload_signal(0)
+            .unwrap_err()
+            .starts_with("Invalid PID"));
+    }
+}
@@ -0,0 +1,341 @@
+//! 使用团队共享的远程 JSON Schema 校验配置
+//!
+//! Schema 缓存在本地（按 URL 区分），缓存在有效期内直接使用；过期后带 ETag 发起条件请求。
+//! 离线或请求失败时回退到上次缓存的 Schema 并记录警告，不影响用户继续工作。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::atomic::atomic_write;
+use super::digest::sha256_hex;
+use super::load_json_config;
+
+/// 缓存的 Schema 在该时长内不重新请求
+pub const SCHEMA_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// 请求 Schema 的超时时间
+const SCHEMA_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 默认的 Schema 缓存目录（`<系统缓存目录>/claude-workbench/schemas`）
+pub fn default_schema_cache_dir() -> Result<PathBuf, String> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("claude-workbench").join("schemas"))
+        .ok_or_else(|| "Failed to get cache directory".to_string())
+}
+
+/// 加载配置并使用远程 Schema 校验
+///
+/// 等价于在 [`default_schema_cache_dir`] 中缓存的
+/// [`load_json_config_validated_remote_in`]
+pub async fn load_json_config_validated_remote<T>(
+    config_path: impl AsRef<Path>,
+    schema_url: &str,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let cache_dir = default_schema_cache_dir()?;
+    load_json_config_validated_remote_in(config_path, schema_url, &cache_dir).await
+}
+
+/// 加载配置并使用远程 Schema 校验（指定缓存目录）
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `schema_url`: Schema 地址
+/// - `cache_dir`: Schema 缓存目录
+///
+/// # 返回值
+/// - `Ok(T)`: 校验通过的配置（文件不存在时返回 `T::default()`，不做校验）
+/// - `Err(String)`: 无法获取 Schema 且没有缓存、配置不符合 Schema 或解析失败
+///
+/// # 特性
+/// - ✅ 缓存有效期内不发起网络请求（见 [`SCHEMA_CACHE_TTL`]）
+/// - ✅ 过期后使用 ETag 条件请求，未变化时不重新下载
+/// - ✅ 离线时回退到上次缓存的 Schema
+pub async fn load_json_config_validated_remote_in<T>(
+    config_path: impl AsRef<Path>,
+    schema_url: &str,
+    cache_dir: &Path,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let schema = fetch_schema_cached(schema_url, cache_dir).await?;
+    let raw: Value = load_json_config(path)?;
+    validate_config_value(&raw, &schema)
+        .map_err(|e| format!("Config {:?} does not match schema: {}", path, e))?;
+    serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+/// 使用 JSON Schema 校验配置值
+///
+/// # 返回值
+/// - `Ok(())`: 校验通过
+/// - `Err(String)`: Schema 无效，或所有校验错误（每条形如 `/proxy/port: ...`，以 `; ` 分隔）
+pub fn validate_config_value(value: &Value, schema: &Value) -> Result<(), String> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))?;
+    let errors: Vec<String> = validator
+        .iter_errors(value)
+        .map(|error| {
+            let location = error.instance_path().to_string();
+            let location = if location.is_empty() {
+                "/".to_string()
+            } else {
+                location
+            };
+            format!("{}: {}", location, error)
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+enum FetchedSchema {
+    NotModified,
+    Updated { schema: Value, etag: Option<String> },
+}
+
+async fn fetch_schema_cached(url: &str, cache_dir: &Path) -> Result<Value, String> {
+    let key = cache_key(url);
+    let schema_path = cache_dir.join(format!("{}.json", key));
+    let etag_path = cache_dir.join(format!("{}.etag", key));
+
+    let cached: Option<Value> = schema_path
+        .exists()
+        .then(|| load_json_config(&schema_path).ok())
+        .flatten();
+    if cached.is_some() && is_fresh(&schema_path) {
+        return Ok(cached.unwrap_or_default());
+    }
+
+    let etag = cached
+        .as_ref()
+        .and_then(|_| fs::read_to_string(&etag_path).ok());
+    match fetch_schema(url, etag.as_deref()).await {
+        Ok(FetchedSchema::NotModified) if cached.is_some() => {
+            touch(&schema_path);
+            Ok(cached.unwrap_or_default())
+        }
+        Ok(FetchedSchema::NotModified) => Err(format!(
+            "Schema server for {} returned 304 but no schema is cached",
+            url
+        )),
+        Ok(FetchedSchema::Updated { schema, etag }) => {
+            fs::create_dir_all(cache_dir)
+                .map_err(|e| format!("Failed to create schema cache {:?}: {}", cache_dir, e))?;
+            let content = serde_json::to_string_pretty(&schema)
+                .map_err(|e| format!("Failed to serialize schema: {}", e))?;
+            atomic_write(&schema_path, content.as_bytes())?;
+            match etag {
+                Some(etag) => atomic_write(&etag_path, etag.as_bytes())?,
+                None => {
+                    fs::remove_file(&etag_path).ok();
+                }
+            }
+            Ok(schema)
+        }
+        Err(e) => match cached {
+            Some(schema) => {
+                log::warn!("Failed to fetch schema {}, using cached copy: {}", url, e);
+                Ok(schema)
+            }
+            None => Err(format!("Failed to fetch schema {}: {}", url, e)),
+        },
+    }
+}
+
+async fn fetch_schema(url: &str, etag: Option<&str>) -> Result<FetchedSchema, String> {
+    let client = reqwest::Client::builder()
+        .timeout(SCHEMA_FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FetchedSchema::NotModified);
+    }
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let schema = response
+        .json::<Value>()
+        .await
+        .map_err(|e| format!("Invalid schema JSON: {}", e))?;
+    Ok(FetchedSchema::Updated { schema, etag })
+}
+
+fn cache_key(url: &str) -> String {
+    let mut key = sha256_hex(url.as_bytes());
+    key.truncate(32);
+    key
+}
+
+fn is_fresh(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < SCHEMA_CACHE_TTL)
+}
+
+/// 条件请求确认未变化后刷新缓存时间，重新开始有效期
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        file.set_modified(SystemTime::now()).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// 处理 `requests` 个请求后退出的最小 Schema 服务器
+    fn serve_schema(schema: Value, requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/schema.json", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut conditional = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line
+                        .to_ascii_lowercase()
+                        .starts_with("if-none-match: \"v1\"")
+                    {
+                        conditional = true;
+                    }
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+                let response = if conditional {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    let body = schema.to_string();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    fn expire(path: &Path) {
+        let old = SystemTime::now() - SCHEMA_CACHE_TTL * 2;
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_config_skips_remote_schema() {
+        let temp = tempfile::tempdir().unwrap();
+        assert!(default_schema_cache_dir()
+            .unwrap()
+            .ends_with(Path::new("claude-workbench").join("schemas")));
+
+        // 文件不存在时直接返回默认值，不访问 Schema 地址
+        let missing: Value = load_json_config_validated_remote(
+            temp.path().join("settings.json"),
+            "http://127.0.0.1:9/schema.json",
+        )
+        .await
+        .unwrap();
+        assert!(missing.is_null());
+    }
+
+    #[tokio::test]
+    async fn test_remote_schema_is_cached_and_used_offline() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = temp.path().join("cache");
+        let config = temp.path().join("settings.json");
+        let schema = json!({
+            "type": "object",
+            "properties": { "port": { "type": "integer", "maximum": 65535 } }
+        });
+        // 第一次下载，过期后的条件请求返回 304，之后服务器下线
+        let url = serve_schema(schema, 2);
+
+        fs::write(&config, r#"{"port": 8080}"#).unwrap();
+        let loaded: Value = load_json_config_validated_remote_in(&config, &url, &cache)
+            .await
+            .unwrap();
+        assert_eq!(loaded, json!({ "port": 8080 }));
+
+        let schema_path = cache.join(format!("{}.json", cache_key(&url)));
+        expire(&schema_path);
+        fs::write(&config, r#"{"port": 70000}"#).unwrap();
+        let err = load_json_config_validated_remote_in::<Value>(&config, &url, &cache)
+            .await
+            .unwrap_err();
+        assert!(err.contains("/port"), "{}", err);
+        assert!(is_fresh(&schema_path));
+
+        expire(&schema_path);
+        fs::write(&config, r#"{"port": 443}"#).unwrap();
+        let offline: Value = load_json_config_validated_remote_in(&config, &url, &cache)
+            .await
+            .unwrap();
+        assert_eq!(offline, json!({ "port": 443 }));
+    }
+
+    #[tokio::test]
+    async fn test_remote_schema_without_cache_fails_offline() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("settings.json");
+        fs::write(&config, "{}").unwrap();
+
+        let unreachable = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/schema.json", listener.local_addr().unwrap())
+        };
+        let err = load_json_config_validated_remote_in::<Value>(
+            &config,
+            &unreachable,
+            &temp.path().join("cache"),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with("Failed to fetch schema"), "{}", err);
+    }
+}
@@ -0,0 +1,118 @@
+//! 批量重命名配置键
+//!
+//! 迁移时常需要一次性重命名大量键（如整份文件由 camelCase 改为 snake_case），
+//! 比逐个调用指针操作更直接。
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+/// 按映射重命名配置中的键
+///
+/// # 参数
+/// - `value`: 配置值（原地修改）
+/// - `mapping`: `(旧键, 新键)` 列表
+/// - `recursive`: 是否递归处理嵌套对象（包括数组中的对象）；否则只处理顶层
+///
+/// # 返回值
+/// - `Ok(count)`: 被重命名的键数量
+/// - `Err(String)`: 新键与同一对象中的已有键冲突；此时配置保持不变
+///
+/// # 特性
+/// - ✅ 保留键原有的值
+pub fn rename_config_keys(
+    value: &mut Value,
+    mapping: &[(&str, &str)],
+    recursive: bool,
+) -> Result<usize, String> {
+    let mapping: HashMap<&str, &str> = mapping.iter().copied().collect();
+    let mut renamed = value.clone();
+    let count = rename_in(&mut renamed, &mapping, recursive, "")?;
+    *value = renamed;
+    Ok(count)
+}
+
+fn rename_in(
+    value: &mut Value,
+    mapping: &HashMap<&str, &str>,
+    recursive: bool,
+    path: &str,
+) -> Result<usize, String> {
+    let mut count = 0;
+    match value {
+        Value::Object(map) => {
+            let original = std::mem::take(map);
+            let mut result = Map::new();
+            for (key, mut child) in original {
+                let child_path = format!("{}/{}", path, key);
+                if recursive {
+                    count += rename_in(&mut child, mapping, recursive, &child_path)?;
+                }
+
+                let new_key = match mapping.get(key.as_str()) {
+                    Some(new_key) => {
+                        count += 1;
+                        new_key.to_string()
+                    }
+                    None => key,
+                };
+                if result.contains_key(&new_key) {
+                    return Err(format!(
+                        "Cannot rename {:?} to {:?}: key already exists",
+                        child_path, new_key
+                    ));
+                }
+                result.insert(new_key, child);
+            }
+            *map = result;
+        }
+        Value::Array(items) if recursive => {
+            for (index, item) in items.iter_mut().enumerate() {
+                count += rename_in(item, mapping, recursive, &format!("{}/{}", path, index))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rename_config_keys() {
+        let mut value = json!({
+            "apiKey": "k",
+            "proxy": { "apiKey": "nested", "baseUrl": "http://x" },
+            "servers": [{ "baseUrl": "http://y" }]
+        });
+        let mapping = [("apiKey", "api_key"), ("baseUrl", "base_url")];
+
+        let mut shallow = value.clone();
+        assert_eq!(
+            rename_config_keys(&mut shallow, &mapping, false).unwrap(),
+            1
+        );
+        assert_eq!(shallow["proxy"]["apiKey"], "nested");
+
+        assert_eq!(rename_config_keys(&mut value, &mapping, true).unwrap(), 4);
+        assert_eq!(
+            value,
+            json!({
+                "api_key": "k",
+                "proxy": { "api_key": "nested", "base_url": "http://x" },
+                "servers": [{ "base_url": "http://y" }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_rename_conflict_leaves_value_unchanged() {
+        let mut value = json!({ "a": 1, "b": 2 });
+        let err = rename_config_keys(&mut value, &[("a", "b")], true).unwrap_err();
+        assert!(err.contains("already exists"));
+        assert_eq!(value, json!({ "a": 1, "b": 2 }));
+    }
+}
@@ -0,0 +1,122 @@
+//! 修复合并错误造成的重复嵌套
+//!
+//! 早期的合并缺陷可能产生 `{"proxy": {"proxy": {...}}}` 这样的双层对象。
+//! [`find_misnested_keys`] 找出可疑的位置，[`unnest_config_keys`] 对已确认的键解除嵌套。
+
+use serde_json::Value;
+
+use super::merge::deep_merge;
+use super::pointer::{escape_pointer_token, split_pointer};
+
+/// 找出值为"只含一个同名子键的对象"的位置
+///
+/// # 返回值
+/// 可疑位置的 JSON Pointer（如 `/proxy`），按深度优先顺序
+pub fn find_misnested_keys(value: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_misnested(value, "", &mut found);
+    found
+}
+
+/// 对指定位置解除重复嵌套
+///
+/// # 参数
+/// - `value`: 配置（原地修改）
+/// - `pointers`: 已确认被错误嵌套的位置，如 `["/proxy"]`
+///
+/// # 返回值
+/// - `Ok(count)`: 实际解除嵌套的位置数量
+/// - `Err(String)`: 指针无效
+///
+/// # 修复规则
+/// - `/proxy` 的值为对象且含有同名子键 `proxy`（对象）时，用内层对象替换外层
+/// - 外层的其他键深度合并到内层之上（通常是之后按正确路径写入的值）
+/// - 多层重复嵌套会被逐层解除
+pub fn unnest_config_keys(value: &mut Value, pointers: &[&str]) -> Result<usize, String> {
+    let mut repaired = 0;
+    for pointer in pointers {
+        let tokens = split_pointer(pointer)?;
+        let Some(key) = tokens.last() else {
+            continue;
+        };
+        let Some(target) = value.pointer_mut(pointer) else {
+            continue;
+        };
+
+        let mut changed = false;
+        while let Some(mut inner) = take_nested(target, key) {
+            let siblings = std::mem::replace(target, Value::Null);
+            deep_merge(&mut inner, siblings);
+            *target = inner;
+            changed = true;
+        }
+        if changed {
+            repaired += 1;
+        }
+    }
+    Ok(repaired)
+}
+
+/// 取出同名的对象子键，其余键留在原处
+fn take_nested(target: &mut Value, key: &str) -> Option<Value> {
+    let map = target.as_object_mut()?;
+    if !map.get(key).is_some_and(Value::is_object) {
+        return None;
+    }
+    map.remove(key)
+}
+
+fn collect_misnested(value: &Value, pointer: &str, found: &mut Vec<String>) {
+    let Value::Object(map) = value else {
+        if let Value::Array(items) = value {
+            for (index, item) in items.iter().enumerate() {
+                collect_misnested(item, &format!("{}/{}", pointer, index), found);
+            }
+        }
+        return;
+    };
+
+    for (key, child) in map {
+        let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+        if is_single_same_named(child, key) {
+            found.push(child_pointer.clone());
+        }
+        collect_misnested(child, &child_pointer, found);
+    }
+}
+
+fn is_single_same_named(value: &Value, key: &str) -> bool {
+    value
+        .as_object()
+        .is_some_and(|map| map.len() == 1 && map.contains_key(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_and_unnest_misnested_keys() {
+        let mut config = json!({
+            "proxy": { "proxy": { "proxy": { "url": "http://a", "port": 1 } }, "port": 8080 },
+            "env": { "env": "production" },
+            "tools": { "web": true }
+        });
+
+        assert_eq!(find_misnested_keys(&config), vec!["/env", "/proxy/proxy"]);
+
+        assert_eq!(
+            unnest_config_keys(&mut config, &["/proxy", "/tools"]).unwrap(),
+            1
+        );
+        assert_eq!(
+            config,
+            json!({
+                "proxy": { "url": "http://a", "port": 8080 },
+                "env": { "env": "production" },
+                "tools": { "web": true }
+            })
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! 声明式的跨字段校验规则
+//!
+//! 大多数跨字段约束都是"必须设置"或"不能同时设置"，用规则列表声明即可，
+//! 不需要为每种配置类型单独编写校验代码。格式约束（如端点必须是 `https://` 地址）
+//! 同样用 [`ConfigRule::Pattern`] 声明，避免类型正确但格式错误的值到运行时才报出难懂的错误。
+
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::load_json_config;
+
+/// 配置校验规则（字段以 JSON Pointer 表示）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigRule<'a> {
+    /// 每个字段都必须设置
+    Required(&'a [&'a str]),
+    /// 最多只能设置其中一个字段
+    MutuallyExclusive(&'a [&'a str]),
+    /// 字段（已设置时）必须是完整匹配正则表达式的字符串：`Pattern(pointer, regex)`
+    Pattern(&'a str, &'a str),
+}
+
+/// 检查配置是否满足所有规则
+///
+/// 字段存在且不为 `null` 或 `false` 时视为"已设置"
+///
+/// # 返回值
+/// - `Ok(())`: 全部满足
+/// - `Err(Vec<String>)`: 每条违反的规则一条错误，列出相关字段
+pub fn check_config_rules(value: &Value, rules: &[ConfigRule]) -> Result<(), Vec<String>> {
+    let errors: Vec<String> = rules
+        .iter()
+        .filter_map(|rule| match rule {
+            ConfigRule::Required(pointers) => {
+                let missing: Vec<&str> = pointers
+                    .iter()
+                    .copied()
+                    .filter(|pointer| !is_set(value, pointer))
+                    .collect();
+                (!missing.is_empty())
+                    .then(|| format!("Required settings are missing: {}", missing.join(", ")))
+            }
+            ConfigRule::MutuallyExclusive(pointers) => {
+                let set: Vec<&str> = pointers
+                    .iter()
+                    .copied()
+                    .filter(|pointer| is_set(value, pointer))
+                    .collect();
+                (set.len() > 1)
+                    .then(|| format!("Settings cannot be used together: {}", set.join(", ")))
+            }
+            ConfigRule::Pattern(pointer, pattern) => check_pattern(value, pointer, pattern),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// 加载配置并检查校验规则
+///
+/// # 返回值
+/// - `Ok(T)`: 满足规则的配置（文件不存在时按空配置检查规则）
+/// - `Err(String)`: 加载失败，或所有违反的规则（以 `; ` 分隔）
+pub fn load_json_config_with_rules<T>(
+    config_path: impl AsRef<Path>,
+    rules: &[ConfigRule],
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let raw: Value = load_json_config(path)?;
+    check_config_rules(&raw, rules)
+        .map_err(|errors| format!("Invalid config {:?}: {}", path, errors.join("; ")))?;
+    if raw.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+/// 错误信息不包含字段的值，避免泄露密钥
+fn check_pattern(value: &Value, pointer: &str, pattern: &str) -> Option<String> {
+    let actual = match value.pointer(pointer) {
+        None | Some(Value::Null) => return None,
+        Some(Value::String(actual)) => actual,
+        Some(_) => return Some(format!("Setting {} must be a string", pointer)),
+    };
+    match Regex::new(&format!("^(?:{})$", pattern)) {
+        Ok(regex) if regex.is_match(actual) => None,
+        Ok(_) => Some(format!(
+            "Setting {} does not match the pattern {}",
+            pointer, pattern
+        )),
+        Err(e) => Some(format!(
+            "Invalid pattern {} for setting {}: {}",
+            pattern, pointer, e
+        )),
+    }
+}
+
+fn is_set(value: &Value, pointer: &str) -> bool {
+    !matches!(
+        value.pointer(pointer),
+        None | Some(Value::Null) | Some(Value::Bool(false))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const RULES: &[ConfigRule] = &[
+        ConfigRule::Required(&["/model"]),
+        ConfigRule::MutuallyExclusive(&["/useProxy", "/directConnection", "/proxy/url"]),
+        ConfigRule::Pattern("/env/ANTHROPIC_BASE_URL", "https://.+"),
+        ConfigRule::Pattern("/env/ANTHROPIC_API_KEY", "sk-ant-[A-Za-z0-9_-]{20,}"),
+    ];
+
+    #[test]
+    fn test_check_config_rules() {
+        let ok = json!({ "model": "opus", "useProxy": true, "directConnection": false });
+        assert!(check_config_rules(&ok, RULES).is_ok());
+
+        let bad = json!({ "useProxy": true, "proxy": { "url": "http://a" } });
+        assert_eq!(
+            check_config_rules(&bad, RULES).unwrap_err(),
+            vec![
+                "Required settings are missing: /model",
+                "Settings cannot be used together: /useProxy, /proxy/url"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_json_config_with_rules() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"model": "x", "useProxy": true, "directConnection": true}"#,
+        )
+        .unwrap();
+
+        let err = load_json_config_with_rules::<Value>(&path, RULES).unwrap_err();
+        assert!(err.contains("/useProxy, /directConnection"), "{}", err);
+    }
+
+    #[test]
+    fn test_pattern_rules() {
+        let ok = json!({
+            "model": "opus",
+            "env": { "ANTHROPIC_BASE_URL": "https://api.example.com" }
+        });
+        assert!(check_config_rules(&ok, RULES).is_ok());
+
+        let bad = json!({
+            "model": "opus",
+            "env": {
+                "ANTHROPIC_BASE_URL": "http://api.example.com",
+                "ANTHROPIC_API_KEY": "sk-ant-short"
+            }
+        });
+        let errors = check_config_rules(&bad, RULES).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "Setting /env/ANTHROPIC_BASE_URL does not match the pattern https://.+",
+                "Setting /env/ANTHROPIC_API_KEY does not match the pattern sk-ant-[A-Za-z0-9_-]{20,}"
+            ]
+        );
+        assert!(!errors.concat().contains("sk-ant-short"));
+
+        let wrong_type = json!({ "model": "opus", "env": { "ANTHROPIC_BASE_URL": 1 } });
+        assert_eq!(
+            check_config_rules(&wrong_type, RULES).unwrap_err(),
+            vec!["Setting /env/ANTHROPIC_BASE_URL must be a string"]
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! 配置结构的 JSON Schema 导出
+//!
+//! 前端根据 Schema 自动渲染设置表单（字段类型、标签、约束），
+//! 避免表单与 Rust 侧的配置定义不同步。
+
+use schemars::JsonSchema;
+#[cfg(test)]
+use serde::Serialize;
+use serde_json::Value;
+
+#[cfg(test)]
+use super::pointer::escape_pointer_token;
+
+/// 配置结构声明的一个字段
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFieldInfo {
+    /// 序列化后的键名（已应用 `serde(rename)`）
+    pub name: String,
+    /// JSON 类型（如 `["integer", "null"]`），无法确定时为空
+    pub types: Vec<String>,
+    /// 是否为必填字段
+    pub required: bool,
+    /// 字段的文档注释
+    pub description: Option<String>,
+}
+
+/// 导出配置类型的 JSON Schema
+pub fn export_config_schema<T>() -> Value
+where
+    T: JsonSchema,
+{
+    let schema = schemars::schema_for!(T);
+    serde_json::to_value(schema).unwrap_or_else(|e| {
+        log::error!("Failed to serialize config schema: {}", e);
+        Value::Null
+    })
+}
+
+/// 列出配置类型声明的顶层键名（顺序与导出的 Schema 一致）
+#[cfg(test)]
+pub fn config_field_names<T>() -> Vec<String>
+where
+    T: JsonSchema,
+{
+    config_fields::<T>()
+        .into_iter()
+        .map(|field| field.name)
+        .collect()
+}
+
+/// 列出配置类型声明的顶层字段及其类型
+#[cfg(test)]
+pub fn config_fields<T>() -> Vec<ConfigFieldInfo>
+where
+    T: JsonSchema,
+{
+    let schema = export_config_schema::<T>();
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = schema["properties"].as_object() else {
+        return Vec::new();
+    };
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut types = Vec::new();
+            collect_types(property, &schema, &mut types);
+            ConfigFieldInfo {
+                name: name.clone(),
+                types,
+                required: required.contains(&name.as_str()),
+                description: property["description"].as_str().map(str::to_string),
+            }
+        })
+        .collect()
+}
+
+/// 找出配置中未在类型声明里出现的顶层键
+///
+/// 声明了 `#[serde(flatten)]` 映射等无法确定键集合的类型时返回空列表
+#[cfg(test)]
+pub fn unknown_config_keys<T>(value: &Value) -> Vec<String>
+where
+    T: JsonSchema,
+{
+    let schema = export_config_schema::<T>();
+    if schema
+        .get("additionalProperties")
+        .is_some_and(|extra| extra != false)
+    {
+        return Vec::new();
+    }
+
+    let known = config_field_names::<T>();
+    value
+        .as_object()
+        .map(|map| {
+            map.keys()
+                .filter(|key| !known.contains(key))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 递归找出配置中当前版本不再识别的键
+///
+/// 与 [`unknown_config_keys`] 不同，会沿 Schema 进入嵌套对象和数组元素，
+/// 帮助用户清理残留的旧设置，也便于发现结构体中被误删而用户仍在使用的字段
+///
+/// # 返回值
+/// 未被声明的键的 JSON Pointer（如 `/proxy/legacyHost`、`/servers/0/oldFlag`）
+///
+/// # 特性
+/// - ✅ 跟随 `$ref`、`allOf`/`anyOf`/`oneOf`（如 `Option<T>`、带数据的枚举）
+/// - ✅ 映射类型（`HashMap`、`#[serde(flatten)]` 的映射）中的键视为已声明，其值按值类型继续检查
+/// - ✅ 类型未知的部分（如 `serde_json::Value` 字段）不报告
+#[cfg(test)]
+pub fn report_unused_keys<T>(value: &Value) -> Vec<String>
+where
+    T: JsonSchema,
+{
+    let schema = export_config_schema::<T>();
+    let mut unused = Vec::new();
+    collect_unused_keys(value, &[&schema], &schema, "", &mut unused);
+    unused
+}
+
+#[cfg(test)]
+fn collect_unused_keys(
+    value: &Value,
+    schemas: &[&Value],
+    root: &Value,
+    pointer: &str,
+    unused: &mut Vec<String>,
+) {
+    let mut resolved = Vec::new();
+    for schema in schemas {
+        resolve_subschemas(schema, root, &mut resolved);
+    }
+
+    match value {
+        Value::Object(map) => {
+            let declares_properties = resolved
+                .iter()
+                .any(|schema| schema.get("properties").is_some());
+            let extra: Vec<&Value> = resolved
+                .iter()
+                .filter_map(|schema| schema.get("additionalProperties"))
+                .filter(|extra| **extra != false)
+                .collect();
+            for (key, child) in map {
+                let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+                let declared: Vec<&Value> = resolved
+                    .iter()
+                    .filter_map(|schema| schema.get("properties")?.get(key))
+                    .collect();
+                if !declared.is_empty() {
+                    collect_unused_keys(child, &declared, root, &child_pointer, unused);
+                } else if !extra.is_empty() {
+                    collect_unused_keys(child, &extra, root, &child_pointer, unused);
+                } else if declares_properties {
+                    unused.push(child_pointer);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let item_schemas: Vec<&Value> = resolved
+                .iter()
+                .filter_map(|schema| schema.get("items"))
+                .filter(|items| items.is_object())
+                .collect();
+            if item_schemas.is_empty() {
+                return;
+            }
+            for (index, item) in items.iter().enumerate() {
+                let item_pointer = format!("{}/{}", pointer, index);
+                collect_unused_keys(item, &item_schemas, root, &item_pointer, unused);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 展开 `$ref` 与组合关键字，收集所有可能描述该值的子 Schema
+#[cfg(test)]
+fn resolve_subschemas<'a>(schema: &'a Value, root: &'a Value, resolved: &mut Vec<&'a Value>) {
+    if resolved.iter().any(|seen| std::ptr::eq(*seen, schema)) {
+        return;
+    }
+    resolved.push(schema);
+    if let Some(target) = schema["$ref"]
+        .as_str()
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+    {
+        resolve_subschemas(target, root, resolved);
+    }
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for subschema in schema[keyword].as_array().into_iter().flatten() {
+            resolve_subschemas(subschema, root, resolved);
+        }
+    }
+}
+
+#[cfg(test)]
+fn collect_types(schema: &Value, root: &Value, types: &mut Vec<String>) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        if let Some(target) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            collect_types(target, root, types);
+        }
+    }
+    match &schema["type"] {
+        Value::String(kind) => push_unique(types, kind),
+        Value::Array(kinds) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .for_each(|kind| push_unique(types, kind)),
+        _ => {}
+    }
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for subschema in schema[keyword].as_array().into_iter().flatten() {
+            collect_types(subschema, root, types);
+        }
+    }
+}
+
+#[cfg(test)]
+fn push_unique(types: &mut Vec<String>, kind: &str) {
+    if !types.iter().any(|existing| existing == kind) {
+        types.push(kind.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试配置
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct SchemaConfig {
+        /// 模型名称
+        model: String,
+        timeout_seconds: Option<u32>,
+    }
+
+    #[derive(JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct NestedConfig {
+        api_key: String,
+        proxy: Option<SchemaConfig>,
+    }
+
+    #[test]
+    fn test_export_config_schema() {
+        let schema = export_config_schema::<SchemaConfig>();
+        assert_eq!(schema["title"], "SchemaConfig");
+        assert_eq!(schema["properties"]["model"]["type"], "string");
+        assert_eq!(schema["properties"]["model"]["description"], "模型名称");
+        assert_eq!(schema["required"], serde_json::json!(["model"]));
+    }
+
+    #[test]
+    fn test_config_fields() {
+        assert_eq!(
+            config_field_names::<SchemaConfig>(),
+            vec!["model", "timeout_seconds"]
+        );
+
+        let fields = config_fields::<NestedConfig>();
+        assert_eq!(fields[0].name, "apiKey");
+        assert_eq!(fields[0].types, vec!["string"]);
+        assert!(fields[0].required);
+        assert_eq!(fields[1].name, "proxy");
+        assert_eq!(fields[1].types, vec!["object", "null"]);
+        assert!(!fields[1].required);
+    }
+
+    #[test]
+    fn test_unknown_config_keys() {
+        let value = serde_json::json!({ "apiKey": "k", "apikey": "typo", "proxy": null });
+        assert_eq!(unknown_config_keys::<NestedConfig>(&value), vec!["apikey"]);
+    }
+
+    #[derive(JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct AppConfig {
+        proxy: Option<SchemaConfig>,
+        servers: Vec<SchemaConfig>,
+        env: std::collections::HashMap<String, SchemaConfig>,
+        extra: Value,
+    }
+
+    #[test]
+    fn test_report_unused_keys() {
+        let value = serde_json::json!({
+            "proxy": { "model": "m", "legacyHost": "h" },
+            "servers": [{ "model": "a" }, { "model": "b", "old/flag": true }],
+            "env": { "ANY": { "model": "c", "stale": 1 } },
+            "extra": { "anything": true },
+            "removedSetting": 1,
+        });
+        assert_eq!(
+            report_unused_keys::<AppConfig>(&value),
+            vec![
+                "/env/ANY/stale",
+                "/proxy/legacyHost",
+                "/removedSetting",
+                "/servers/1/old~1flag",
+            ]
+        );
+    }
+}
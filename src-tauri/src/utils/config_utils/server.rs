@@ -0,0 +1,541 @@
+//! 本地 HTTP 配置接口（`config-http` 特性）
+//!
+//! 供外部脚本通过 HTTP 而不是 IPC 读写配置：
+//! - `GET /configs/<name>`: 读取 `<base_dir>/<name>`，返回 JSON
+//! - `PUT /configs/<name>`: 以请求体（JSON）覆盖写入 `<base_dir>/<name>`
+//!
+//! 每个请求都须带 `Authorization: Bearer <token>`，令牌在启动时随机生成，只通过
+//! [`ConfigServer::token`] 交给应用，由应用转交给它信任的脚本；同一台机器上的其他进程
+//! 无法读写配置（`settings.json` 的 `hooks` 会被执行）。
+//!
+//! 只监听 127.0.0.1，并校验 `Host` 头以防 DNS 重绑定；配置名只能是 `base_dir` 下的
+//! `.json` 文件，不能包含 `..`、绝对路径或以 `.` 开头的隐藏文件/目录
+//! （备份、快照等内部目录因此不可访问），经符号链接指向 `base_dir` 之外的子目录、
+//! 以及本身是符号链接的配置文件同样被拒绝。
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+
+use super::digest::to_hex;
+use super::{load_json_config, save_json_config};
+
+/// 接口路径前缀
+pub const CONFIG_HTTP_PREFIX: &str = "/configs/";
+
+/// PUT 请求体大小上限
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+/// 请求行与所有请求头的总长度上限
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// 访问令牌的随机字节数
+const TOKEN_BYTES: usize = 32;
+
+/// 单个连接的读写超时，避免不完整的请求阻塞服务
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 运行中的本地配置接口，drop 时停止
+#[derive(Debug)]
+pub struct ConfigServer {
+    addr: SocketAddr,
+    token: String,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConfigServer {
+    /// 实际监听的地址（`port` 为 0 时由系统分配端口）
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 本次启动生成的访问令牌，请求须带 `Authorization: Bearer <token>`
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// 停止服务并等待后台线程退出
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if self.stopped.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // 唤醒阻塞在 accept 上的线程
+        TcpStream::connect(self.addr).ok();
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+        log::info!("Config HTTP server on {} stopped", self.addr);
+    }
+}
+
+impl Drop for ConfigServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 启动本地配置接口
+///
+/// # 参数
+/// - `base_dir`: 可通过接口访问的配置目录
+/// - `port`: 监听端口，0 表示由系统分配
+///
+/// # 返回值
+/// - `Ok(ConfigServer)`: 已在后台线程开始监听 127.0.0.1
+/// - `Err(String)`: 端口绑定失败或无法生成访问令牌
+///
+/// # 特性
+/// - ✅ 每个请求都须带随机访问令牌（见 [`ConfigServer::token`]），否则返回 401
+/// - ✅ 只绑定回环地址，并拒绝 `Host` 不是本机的请求
+/// - ✅ 防止路径穿越：配置名限定为 `base_dir` 下的非隐藏 `.json` 文件
+/// - ✅ 读写经由 [`load_json_config`] / [`save_json_config`]，与应用内保存共享文件锁
+pub fn start_config_server(
+    base_dir: impl Into<PathBuf>,
+    port: u16,
+) -> Result<ConfigServer, String> {
+    let base_dir = base_dir.into();
+    let mut bytes = [0u8; TOKEN_BYTES];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate config HTTP server token".to_string())?;
+    let token = to_hex(&bytes);
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| format!("Failed to bind config HTTP server on port {}: {}", port, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read config HTTP server address: {}", e))?;
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stopped);
+    let expected = token.clone();
+    let handle = std::thread::Builder::new()
+        .name("config-http".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = handle_connection(stream, &base_dir, &expected) {
+                            log::debug!("Config HTTP connection error: {}", e);
+                        }
+                    }
+                    Err(e) => log::warn!("Config HTTP accept failed: {}", e),
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start config HTTP server: {}", e))?;
+
+    log::info!("Config HTTP server listening on {}", addr);
+    Ok(ConfigServer {
+        addr,
+        token,
+        stopped,
+        handle: Some(handle),
+    })
+}
+
+/// 将接口中的配置名解析为 `base_dir` 下的文件路径
+///
+/// # 返回值
+/// - `Ok(PathBuf)`: 合法的配置文件路径
+/// - `Err(String)`: 配置名为空、不是 `.json` 文件，包含 `..`、绝对路径、隐藏组件等，
+///   所在目录经符号链接解析后位于 `base_dir` 之外，或配置文件本身是符号链接
+pub fn resolve_config_name(base_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let decoded = urlencoding::decode(name)
+        .map_err(|_| format!("Invalid config name {:?}: not valid UTF-8", name))?;
+    let invalid = |reason: &str| Err(format!("Invalid config name {:?}: {}", decoded, reason));
+
+    if !decoded.ends_with(".json") {
+        return invalid("only .json files are accessible");
+    }
+    if decoded.contains('\\') || decoded.contains(':') || decoded.contains('\0') {
+        return invalid("contains a forbidden character");
+    }
+
+    let mut path = base_dir.to_path_buf();
+    for segment in decoded.split('/') {
+        if segment.is_empty() {
+            return invalid("empty path segment");
+        }
+        if segment.starts_with('.') {
+            return invalid("hidden or parent path segment");
+        }
+        path.push(segment);
+    }
+
+    // 字面检查无法发现指向外部的符号链接目录，按最近的已存在目录的真实路径再检查一次
+    if let Ok(canonical_base) = fs::canonicalize(base_dir) {
+        let existing = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(base_dir))
+            .find(|dir| dir.exists());
+        if let Some(dir) = existing {
+            let inside = fs::canonicalize(dir).is_ok_and(|dir| dir.starts_with(&canonical_base));
+            if !inside {
+                return invalid("resolves outside the config directory");
+            }
+        }
+    }
+    // 保存会写入符号链接的目标，目标可能位于任何位置
+    if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        return invalid("is a symbolic link");
+    }
+    Ok(path)
+}
+
+struct Request {
+    method: String,
+    target: String,
+    host: Option<String>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+fn handle_connection(mut stream: TcpStream, base_dir: &Path, token: &str) -> Result<(), String> {
+    stream.set_read_timeout(Some(IO_TIMEOUT)).ok();
+    stream.set_write_timeout(Some(IO_TIMEOUT)).ok();
+
+    let local = stream
+        .peer_addr()
+        .map(|addr| addr.ip().is_loopback())
+        .unwrap_or(false);
+    let (status, body) = if !local {
+        error_response(403, "Only local connections are allowed")
+    } else {
+        match read_request(&stream) {
+            Ok(request) => route(&request, base_dir, token),
+            Err((status, message)) => error_response(status, &message),
+        }
+    };
+    write_response(&mut stream, status, &body)
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, (u16, String)> {
+    let bad_request = |message: String| (400, message);
+    let mut reader = BufReader::new(stream);
+    let mut remaining = MAX_HEADER_BYTES;
+
+    let mut line = String::new();
+    read_header_line(&mut reader, &mut line, &mut remaining)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line".to_string()));
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut host = None;
+    let mut authorization = None;
+    let mut content_length = 0usize;
+    loop {
+        line.clear();
+        read_header_line(&mut reader, &mut line, &mut remaining)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request(format!("Malformed header {:?}", header)));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("host") {
+            host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|_| bad_request(format!("Invalid Content-Length {:?}", value)))?;
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return Err((
+            413,
+            format!("Request body exceeds {} bytes", MAX_BODY_BYTES),
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| bad_request(format!("Failed to read request body: {}", e)))?;
+
+    Ok(Request {
+        method,
+        target,
+        host,
+        authorization,
+        body,
+    })
+}
+
+/// 读取一行请求行或请求头，所有行的总长度不超过 [`MAX_HEADER_BYTES`]
+fn read_header_line(
+    reader: &mut BufReader<&TcpStream>,
+    line: &mut String,
+    remaining: &mut usize,
+) -> Result<(), (u16, String)> {
+    let read = reader
+        .take(*remaining as u64)
+        .read_line(line)
+        .map_err(|e| (400, format!("Failed to read request: {}", e)))?;
+    *remaining -= read;
+    if line.ends_with('\n') {
+        Ok(())
+    } else if *remaining == 0 {
+        Err((
+            431,
+            format!("Request headers exceed {} bytes", MAX_HEADER_BYTES),
+        ))
+    } else {
+        Err((400, "Incomplete request headers".to_string()))
+    }
+}
+
+fn route(request: &Request, base_dir: &Path, token: &str) -> (u16, Value) {
+    if !request.host.as_deref().is_some_and(is_local_host) {
+        return error_response(403, "Host header must refer to localhost");
+    }
+    let authorized = request
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|presented| tokens_match(presented.trim(), token));
+    if !authorized {
+        return error_response(401, "Missing or invalid bearer token");
+    }
+
+    let path = request.target.split(['?', '#']).next().unwrap_or_default();
+    let Some(name) = path.strip_prefix(CONFIG_HTTP_PREFIX) else {
+        return error_response(404, "Not found");
+    };
+    let config_path = match resolve_config_name(base_dir, name) {
+        Ok(config_path) => config_path,
+        Err(e) => return error_response(400, &e),
+    };
+
+    match request.method.as_str() {
+        "GET" => {
+            if !config_path.is_file() {
+                return error_response(404, &format!("Config {:?} does not exist", name));
+            }
+            match load_json_config::<Value>(&config_path) {
+                Ok(value) => (200, value),
+                Err(e) => error_response(500, &e),
+            }
+        }
+        "PUT" => {
+            let value: Value = match serde_json::from_slice(&request.body) {
+                Ok(value) => value,
+                Err(e) => return error_response(400, &format!("Invalid JSON body: {}", e)),
+            };
+            match save_json_config(&value, &config_path) {
+                Ok(()) => (200, json!({ "saved": name })),
+                Err(e) => error_response(500, &e),
+            }
+        }
+        _ => error_response(405, "Only GET and PUT are supported"),
+    }
+}
+
+/// `Host` 是否为本机（可带端口）
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// 逐字节比较令牌，耗时与第一个不同字节的位置无关
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn error_response(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("Failed to write response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_resolve_config_name_rejects_traversal() {
+        let base = Path::new("/base");
+        assert_eq!(
+            resolve_config_name(base, "agents/review.json").unwrap(),
+            base.join("agents").join("review.json")
+        );
+        for name in [
+            "../settings.json",
+            "%2e%2e/settings.json",
+            "/etc/passwd.json",
+            ".backups/settings.json",
+            "a//b.json",
+            "..\\settings.json",
+            "C:settings.json",
+            "settings.toml",
+        ] {
+            assert!(resolve_config_name(base, name).is_err(), "{}", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_config_name_rejects_symlinked_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("base");
+        let outside = temp.path().join("outside");
+        fs::create_dir_all(base.join("agents")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, base.join("escape")).unwrap();
+
+        assert!(resolve_config_name(&base, "agents/review.json").is_ok());
+        assert!(resolve_config_name(&base, "new/dir/settings.json").is_ok());
+        let err = resolve_config_name(&base, "escape/settings.json").unwrap_err();
+        assert!(err.contains("outside the config directory"), "{}", err);
+        assert!(resolve_config_name(&base, "escape/nested/settings.json").is_err());
+
+        fs::write(outside.join("hooks.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(outside.join("hooks.json"), base.join("settings.json")).unwrap();
+        let err = resolve_config_name(&base, "settings.json").unwrap_err();
+        assert!(err.contains("symbolic link"), "{}", err);
+    }
+
+    #[test]
+    fn test_config_server_get_and_put() {
+        let temp = tempfile::tempdir().unwrap();
+        let server = start_config_server(temp.path(), 0).unwrap();
+        let addr = server.addr();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(server.token().len(), TOKEN_BYTES * 2);
+        let auth = format!("Authorization: Bearer {}\r\n", server.token());
+
+        let body = r#"{"model":"opus"}"#;
+        let response = send(
+            addr,
+            &format!(
+                "PUT /configs/settings.json HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}",
+                auth,
+                body.len(),
+                body
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        let response = send(
+            addr,
+            &format!(
+                "GET /configs/settings.json HTTP/1.1\r\nHost: 127.0.0.1\r\n{}\r\n",
+                auth
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with(body), "{}", response);
+
+        let response = send(
+            addr,
+            &format!(
+                "GET /configs/..%2Fsecret.json HTTP/1.1\r\nHost: localhost\r\n{}\r\n",
+                auth
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+        let response = send(
+            addr,
+            &format!(
+                "GET /configs/settings.json HTTP/1.1\r\nHost: evil.example\r\n{}\r\n",
+                auth
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_config_server_requires_token_and_caps_headers() {
+        let temp = tempfile::tempdir().unwrap();
+        let server = start_config_server(temp.path(), 0).unwrap();
+        let addr = server.addr();
+        let body = r#"{"hooks":{}}"#;
+
+        for auth in ["", "Authorization: Bearer wrong\r\n"] {
+            let response = send(
+                addr,
+                &format!(
+                    "PUT /configs/settings.json HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}",
+                    auth,
+                    body.len(),
+                    body
+                ),
+            );
+            assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+        }
+        assert!(!temp.path().join("settings.json").exists());
+
+        // 恰好发送上限长度、没有结束换行的请求头，服务端读完全部数据后拒绝
+        let mut request = "GET /configs/settings.json HTTP/1.1\r\nX-Padding: ".to_string();
+        request.push_str(&"a".repeat(MAX_HEADER_BYTES - request.len()));
+        let response = send(addr, &request);
+        assert!(response.starts_with("HTTP/1.1 431"), "{}", response);
+
+        server.stop();
+    }
+}
@@ -0,0 +1,156 @@
+//! 暂存式配置编辑
+//!
+//! 类似 git 暂存区的"先做多处修改，再统一应用"：修改只作用于内存中的工作副本，
+//! 可以随时预览与磁盘文件的差异，确认后一次性写回，或直接放弃。
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::diff::{diff_json_configs, ConfigChange};
+use super::ops::{apply_op, ConfigOp};
+use super::{load_json_config, update_json_config};
+
+/// 配置编辑会话，见 [`begin_edit`]
+#[derive(Debug, Clone)]
+pub struct EditSession {
+    path: PathBuf,
+    /// 开始编辑时的文件内容，用于检测期间的外部修改
+    base: Value,
+    scratch: Value,
+}
+
+/// 开始编辑配置：将当前文件内容载入工作副本
+///
+/// 文件不存在时从空对象开始
+pub fn begin_edit(config_path: impl AsRef<Path>) -> Result<EditSession, String> {
+    let path = config_path.as_ref().to_path_buf();
+    let base = load_live(&path)?;
+    Ok(EditSession {
+        path,
+        scratch: base.clone(),
+        base,
+    })
+}
+
+impl EditSession {
+    /// 配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 工作副本的当前内容
+    pub fn scratch(&self) -> &Value {
+        &self.scratch
+    }
+
+    /// 对工作副本应用一个操作（不写入文件）
+    pub fn apply(&mut self, op: &ConfigOp) -> Result<(), String> {
+        apply_op(&mut self.scratch, op)
+    }
+
+    /// 预览工作副本相对磁盘上当前文件的差异
+    pub fn preview(&self) -> Result<Vec<ConfigChange>, String> {
+        Ok(diff_json_configs(&load_live(&self.path)?, &self.scratch))
+    }
+
+    /// 将工作副本写回配置文件
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 已在写锁内原子写入
+    /// - `Err(String)`: 开始编辑后文件已被其他方修改（为避免覆盖他人的修改，不写入），或写入失败
+    pub fn commit(self) -> Result<(), String> {
+        let EditSession {
+            path,
+            base,
+            scratch,
+        } = self;
+        update_json_config(&path, |live| {
+            if *live != base {
+                return Err(format!(
+                    "Config {:?} was modified after the edit session began",
+                    path
+                ));
+            }
+            *live = scratch;
+            Ok(())
+        })
+    }
+
+    /// 放弃工作副本中的所有修改
+    pub fn discard(self) {}
+}
+
+/// 读取文件当前内容，不存在时为空对象（与 [`update_json_config`] 一致）
+fn load_live(path: &Path) -> Result<Value, String> {
+    let value: Value = load_json_config(path)?;
+    Ok(if value.is_null() {
+        Value::Object(Default::default())
+    } else {
+        value
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_edit_session_commit_and_discard() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, r#"{"model": "sonnet"}"#).unwrap();
+
+        let mut session = begin_edit(&path).unwrap();
+        session
+            .apply(&ConfigOp::Set {
+                pointer: "/model".to_string(),
+                value: json!("opus"),
+            })
+            .unwrap();
+        session
+            .apply(&ConfigOp::Toggle {
+                pointer: "/verbose".to_string(),
+            })
+            .unwrap();
+        assert_eq!(session.path(), path);
+        assert_eq!(
+            session.scratch(),
+            &json!({ "model": "opus", "verbose": true })
+        );
+        assert_eq!(session.preview().unwrap().len(), 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"model": "sonnet"}"#);
+
+        session.clone().discard();
+        session.commit().unwrap();
+        let saved: Value = load_json_config(&path).unwrap();
+        assert_eq!(saved, json!({ "model": "opus", "verbose": true }));
+    }
+
+    #[test]
+    fn test_edit_session_rejects_concurrent_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        let mut session = begin_edit(&path).unwrap();
+        session
+            .apply(&ConfigOp::Delete {
+                pointer: "/model".to_string(),
+            })
+            .unwrap();
+        fs::write(&path, r#"{"model": "external"}"#).unwrap();
+
+        let err = session.commit().unwrap_err();
+        assert!(
+            err.contains("modified after the edit session began"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{"model": "external"}"#
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! 配置签名
+//!
+//! 需要防篡改的配置（如团队下发的策略）以 Ed25519 签名，签名存放在同目录的
+//! `<文件名>.sig` 中。签名针对配置的规范形式（键按字典序排列、无空白），
+//! 因此重新格式化文件不会使签名失效，而任何值的修改都会。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::atomic::atomic_write;
+use super::digest::{canonical_config_bytes, to_hex};
+use super::load_json_config;
+
+/// 签名文件的后缀
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// 签名存在但与配置不符时，错误信息的前缀
+///
+/// 调用方以 `err.starts_with(CONFIG_SIGNATURE_INVALID)` 区分被篡改的配置与普通的读取错误
+pub const CONFIG_SIGNATURE_INVALID: &str = "Config signature invalid";
+
+/// 生成新的签名密钥
+///
+/// # 返回值
+/// `(PKCS#8 格式的私钥, 32 字节公钥)`
+pub fn generate_config_signing_key() -> Result<(Vec<u8>, Vec<u8>), String> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| "Failed to generate signing key".to_string())?;
+    let key_pair = signing_key_pair(pkcs8.as_ref())?;
+    Ok((
+        pkcs8.as_ref().to_vec(),
+        key_pair.public_key().as_ref().to_vec(),
+    ))
+}
+
+/// 对配置签名
+///
+/// # 参数
+/// - `value`: 配置
+/// - `signing_key`: PKCS#8 格式的 Ed25519 私钥
+///
+/// # 返回值
+/// 十六进制编码的签名
+pub fn sign_config(value: &Value, signing_key: &[u8]) -> Result<String, String> {
+    let key_pair = signing_key_pair(signing_key)?;
+    let signature = key_pair.sign(&canonical_config_bytes(value));
+    Ok(to_hex(signature.as_ref()))
+}
+
+/// 对配置文件的当前内容签名，并写入签名文件
+///
+/// # 返回值
+/// 签名文件路径
+pub fn sign_config_file(
+    config_path: impl AsRef<Path>,
+    signing_key: &[u8],
+) -> Result<PathBuf, String> {
+    let path = config_path.as_ref();
+    if !path.exists() {
+        return Err(format!("Config file not found at {:?}", path));
+    }
+    let value: Value = load_json_config(path)?;
+    let signature = sign_config(&value, signing_key)?;
+
+    let sidecar = signature_path(path);
+    atomic_write(&sidecar, signature.as_bytes())?;
+    log::info!("Signed config {:?}", path);
+    Ok(sidecar)
+}
+
+/// 校验配置文件的签名
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `public_key`: 32 字节 Ed25519 公钥
+///
+/// # 返回值
+/// - `Ok(true)`: 签名有效
+/// - `Ok(false)`: 没有签名文件
+/// - `Err(String)`: 签名与配置不符（以 [`CONFIG_SIGNATURE_INVALID`] 开头），或读取失败
+pub fn verify_config_signature(
+    config_path: impl AsRef<Path>,
+    public_key: &[u8],
+) -> Result<bool, String> {
+    let path = config_path.as_ref();
+    let value: Value = load_json_config(path)?;
+    check_signature(path, &value, public_key)
+}
+
+/// 加载配置并校验签名
+///
+/// 没有签名文件的配置照常加载；要求必须签名时先调用 [`verify_config_signature`]
+///
+/// # 返回值
+/// - `Ok(T)`: 签名有效或没有签名
+/// - `Err(String)`: 签名与配置不符（以 [`CONFIG_SIGNATURE_INVALID`] 开头），或加载失败
+pub fn load_json_config_signed<T>(
+    config_path: impl AsRef<Path>,
+    public_key: &[u8],
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    // 只读取一次文件，校验与反序列化使用同一份内容
+    let value: Value = load_json_config(path)?;
+    check_signature(path, &value, public_key)?;
+    if value.is_null() && !path.exists() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))
+}
+
+/// 签名文件路径：`<配置文件名>.sig`
+pub fn signature_path(config_path: &Path) -> PathBuf {
+    let mut name = config_path.file_name().unwrap_or_default().to_os_string();
+    name.push(SIGNATURE_SUFFIX);
+    config_path.with_file_name(name)
+}
+
+/// 按已加载的配置值校验签名，返回值同 [`verify_config_signature`]
+pub(crate) fn check_signature(
+    path: &Path,
+    value: &Value,
+    public_key: &[u8],
+) -> Result<bool, String> {
+    let sidecar = signature_path(path);
+    let encoded = match fs::read_to_string(&sidecar) {
+        Ok(encoded) => encoded,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(format!("Failed to read signature {:?}: {}", sidecar, e)),
+    };
+
+    let verified = from_hex(encoded.trim()).is_some_and(|signature| {
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&canonical_config_bytes(value), &signature)
+            .is_ok()
+    });
+    if !verified {
+        log::error!("Signature check failed for config {:?}", path);
+        return Err(format!("{}: {:?}", CONFIG_SIGNATURE_INVALID, path));
+    }
+    Ok(true)
+}
+
+fn signing_key_pair(signing_key: &[u8]) -> Result<Ed25519KeyPair, String> {
+    Ed25519KeyPair::from_pkcs8_maybe_unchecked(signing_key)
+        .map_err(|e| format!("Invalid signing key: {}", e))
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => u8::from_str_radix(std::str::from_utf8(&[*high, *low]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_config_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("policy.json");
+        let (signing_key, public_key) = generate_config_signing_key().unwrap();
+
+        fs::write(&path, r#"{"allowNetwork": false, "model": "opus"}"#).unwrap();
+        assert!(!verify_config_signature(&path, &public_key).unwrap());
+        sign_config_file(&path, &signing_key).unwrap();
+        assert!(path.with_file_name("policy.json.sig").exists());
+
+        // 重新格式化不影响签名
+        fs::write(
+            &path,
+            "{\n  \"model\": \"opus\",\n  \"allowNetwork\": false\n}",
+        )
+        .unwrap();
+        assert!(verify_config_signature(&path, &public_key).unwrap());
+        let loaded: Value = load_json_config_signed(&path, &public_key).unwrap();
+        assert_eq!(loaded["model"], "opus");
+
+        fs::write(&path, r#"{"allowNetwork": true, "model": "opus"}"#).unwrap();
+        let err = load_json_config_signed::<Value>(&path, &public_key).unwrap_err();
+        assert!(err.starts_with(CONFIG_SIGNATURE_INVALID), "{}", err);
+
+        let (_, other_key) = generate_config_signing_key().unwrap();
+        fs::write(&path, r#"{"allowNetwork": false, "model": "opus"}"#).unwrap();
+        assert!(verify_config_signature(&path, &other_key).is_err());
+    }
+}
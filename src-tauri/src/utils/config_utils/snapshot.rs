@@ -0,0 +1,254 @@
+//! 整个配置目录的快照与恢复
+//!
+//! 用于"先尝试有风险的修改，出问题再整体回滚"的场景。
+//! 快照保存在 `<base_dir>/.snapshots/<id>/` 下，保留原有的相对目录结构。
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::batch::TRANSACTIONS_DIR;
+use super::copy::copy_config_file;
+use super::history::{BACKUPS_DIR, JOURNAL_DIR, VERSIONS_DIR};
+use super::lock::with_config_dir_lock;
+
+/// 快照根目录名
+pub const SNAPSHOTS_DIR: &str = ".snapshots";
+
+/// 配置工具自身使用的存储目录，遍历配置文件时跳过
+pub(crate) const INTERNAL_DIRS: &[&str] = &[
+    SNAPSHOTS_DIR,
+    BACKUPS_DIR,
+    VERSIONS_DIR,
+    JOURNAL_DIR,
+    TRANSACTIONS_DIR,
+];
+
+/// 快照标识（基于创建时间，可按字典序排序）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapshotId(pub String);
+
+impl fmt::Display for SnapshotId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// 为整个配置目录创建快照
+///
+/// 复制 `base_dir` 下（递归）所有 `.json` 配置文件，跳过内部存储目录
+///
+/// # 返回值
+/// - `Ok(SnapshotId)`: 新快照的标识
+/// - `Err(String)`: 复制失败（已创建的快照目录会被清理）
+pub fn snapshot_config_tree(base_dir: &Path) -> Result<SnapshotId, String> {
+    let root = base_dir.join(SNAPSHOTS_DIR);
+    let stamp = chrono::Local::now().format("%Y%m%dT%H%M%S%3f").to_string();
+
+    // 同一毫秒内多次创建时追加序号
+    let mut id = SnapshotId(stamp.clone());
+    let mut suffix = 1;
+    while root.join(&id.0).exists() {
+        id = SnapshotId(format!("{}-{}", stamp, suffix));
+        suffix += 1;
+    }
+
+    let target = root.join(&id.0);
+    fs::create_dir_all(&target)
+        .map_err(|e| format!("Failed to create snapshot directory {:?}: {}", target, e))?;
+
+    let result = config_files(base_dir).and_then(|files| {
+        for relative in &files {
+            copy_relative(base_dir, &target, relative)?;
+        }
+        Ok(files.len())
+    });
+
+    match result {
+        Ok(count) => {
+            log::info!("Created config snapshot {} ({} files)", id, count);
+            Ok(id)
+        }
+        Err(e) => {
+            fs::remove_dir_all(&target).ok();
+            Err(e)
+        }
+    }
+}
+
+/// 从快照恢复整个配置目录
+///
+/// 快照中的文件会覆盖当前文件，整个恢复过程持有配置目录锁（见 [`with_config_dir_lock`]）。
+/// 快照之后新建的文件保持不变：
+/// `~/.claude` 下还有 CLI 自行维护的 `.json` 文件（如 todos），整体删除风险太大。
+pub fn restore_config_tree(base_dir: &Path, id: &SnapshotId) -> Result<(), String> {
+    let source = snapshot_dir(base_dir, id)?;
+    if !source.is_dir() {
+        return Err(format!("Snapshot {} not found", id));
+    }
+
+    // 持有目录锁，恢复期间的单文件保存等待恢复完成，不会与快照内容交错
+    let snapshot_files = with_config_dir_lock(base_dir, || {
+        let snapshot_files = config_files(&source)?;
+        for relative in &snapshot_files {
+            copy_relative(&source, base_dir, relative)?;
+        }
+        Ok(snapshot_files)
+    })?;
+
+    log::info!(
+        "Restored config snapshot {} ({} files)",
+        id,
+        snapshot_files.len()
+    );
+    Ok(())
+}
+
+/// 列出所有快照（最新的在前）
+pub fn list_snapshots(base_dir: &Path) -> Result<Vec<SnapshotId>, String> {
+    let root = base_dir.join(SNAPSHOTS_DIR);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&root).map_err(|e| format!("Failed to read {:?}: {}", root, e))?;
+    let mut ids: Vec<SnapshotId> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .map(SnapshotId)
+        .collect();
+    ids.sort();
+    ids.reverse();
+    Ok(ids)
+}
+
+/// 删除快照
+pub fn delete_snapshot(base_dir: &Path, id: &SnapshotId) -> Result<(), String> {
+    let dir = snapshot_dir(base_dir, id)?;
+    if !dir.is_dir() {
+        return Err(format!("Snapshot {} not found", id));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete snapshot {}: {}", id, e))
+}
+
+fn snapshot_dir(base_dir: &Path, id: &SnapshotId) -> Result<PathBuf, String> {
+    if id.0.is_empty() || id.0.contains(['/', '\\']) || id.0.starts_with('.') {
+        return Err(format!("Invalid snapshot id: {:?}", id.0));
+    }
+    Ok(base_dir.join(SNAPSHOTS_DIR).join(&id.0))
+}
+
+/// 递归收集目录下的 `.json` 配置文件（相对路径），跳过内部存储目录
+pub(crate) fn config_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    // 跟随符号链接，使链接到 dotfiles 仓库的配置也被收集；walkdir 会检测目录循环
+    let walker = walkdir::WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !INTERNAL_DIRS.iter().any(|name| entry.file_name() == *name)
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(|e| match e.loop_ancestor() {
+            Some(ancestor) => format!(
+                "Symlink cycle detected in {:?}: {:?} points back to {:?}",
+                dir,
+                e.path().unwrap_or(ancestor),
+                ancestor
+            ),
+            None => format!("Failed to scan {:?}: {}", dir, e),
+        })?;
+        let path = entry.path();
+        if entry.file_type().is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            if let Ok(relative) = path.strip_prefix(dir) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn copy_relative(from_root: &Path, to_root: &Path, relative: &Path) -> Result<(), String> {
+    let from = from_root.join(relative);
+    let to = to_root.join(relative);
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    }
+    copy_config_file(&from, &to)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_restore_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path();
+        fs::create_dir_all(base.join("agents")).unwrap();
+        fs::write(base.join("settings.json"), r#"{"model": "a"}"#).unwrap();
+        fs::write(base.join("agents/agent.json"), "{}").unwrap();
+
+        let id = snapshot_config_tree(base).unwrap();
+        assert_eq!(list_snapshots(base).unwrap(), vec![id.clone()]);
+
+        fs::write(base.join("settings.json"), r#"{"model": "b"}"#).unwrap();
+        fs::write(base.join("new.json"), "{}").unwrap();
+
+        restore_config_tree(base, &id).unwrap();
+        assert_eq!(
+            fs::read_to_string(base.join("settings.json")).unwrap(),
+            r#"{"model": "a"}"#
+        );
+        assert!(base.join("agents/agent.json").exists());
+        assert!(base.join("new.json").exists());
+
+        delete_snapshot(base, &id).unwrap();
+        assert!(list_snapshots(base).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_ids_are_validated() {
+        let temp = tempfile::tempdir().unwrap();
+        let bad = SnapshotId("../settings".to_string());
+        assert!(restore_config_tree(temp.path(), &bad).is_err());
+        assert!(delete_snapshot(temp.path(), &bad).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshot_follows_links_and_rejects_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join(".claude");
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(&base).unwrap();
+        fs::create_dir_all(&dotfiles).unwrap();
+        fs::write(dotfiles.join("settings.json"), "{}").unwrap();
+        symlink(dotfiles.join("settings.json"), base.join("settings.json")).unwrap();
+        assert_eq!(
+            config_files(&base).unwrap(),
+            vec![PathBuf::from("settings.json")]
+        );
+
+        symlink(&base, base.join("loop")).unwrap();
+        let err = snapshot_config_tree(&base).unwrap_err();
+        assert!(err.contains("Symlink cycle"), "{}", err);
+    }
+}
@@ -0,0 +1,114 @@
+//! 拒绝未知键的严格加载
+//!
+//! 默认的 [`load_json_config`] 会静默忽略未知键以保持向前兼容，
+//! 但拼错的键（如 `modle`）也会因此被忽略。需要发现这类错误时改用严格加载。
+
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::load_json_config;
+use super::pointer::escape_pointer_token;
+
+/// 加载配置，存在类型未声明的键时报错
+///
+/// 效果等同于为类型及其所有嵌套类型加上 `#[serde(deny_unknown_fields)]`，
+/// 但不需要修改类型定义。被 `Value` 或 `#[serde(flatten)]` 映射接收的键不算未知键。
+///
+/// # 返回值
+/// - `Ok(T)`: 没有未知键的配置（文件不存在时返回 `T::default()`）
+/// - `Err(String)`: 加载失败，或列出所有未知键的 JSON Pointer
+pub fn load_json_config_strict<T>(config_path: impl AsRef<Path>) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let path = config_path.as_ref();
+    let raw: Value = load_json_config(path)?;
+    if raw.is_null() {
+        return Ok(T::default());
+    }
+
+    let mut unknown = Vec::new();
+    let config: T = serde_ignored::deserialize(raw, |ignored| {
+        unknown.push(ignored_pointer(&ignored));
+    })
+    .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+
+    if unknown.is_empty() {
+        Ok(config)
+    } else {
+        Err(format!(
+            "Config {:?} contains unknown keys: {}",
+            path,
+            unknown.join(", ")
+        ))
+    }
+}
+
+fn ignored_pointer(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => {
+            format!("{}/{}", ignored_pointer(parent), index)
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            format!("{}/{}", ignored_pointer(parent), escape_pointer_token(key))
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_pointer(parent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct Proxy {
+        url: String,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct Settings {
+        model: Option<String>,
+        proxy: Option<Proxy>,
+        #[serde(default)]
+        env: std::collections::BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn test_load_json_config_strict() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        fs::write(
+            &path,
+            r#"{"model": "opus", "proxy": {"url": "http://a"}, "env": {"ANY": "1"}}"#,
+        )
+        .unwrap();
+        let settings: Settings = load_json_config_strict(&path).unwrap();
+        assert_eq!(settings.model.as_deref(), Some("opus"));
+
+        fs::write(
+            &path,
+            r#"{"modle": "opus", "proxy": {"url": "http://a", "prot": 1}}"#,
+        )
+        .unwrap();
+        let err = load_json_config_strict::<Settings>(&path).unwrap_err();
+        assert!(
+            err.ends_with("contains unknown keys: /modle, /proxy/prot"),
+            "{}",
+            err
+        );
+
+        let lenient: Settings = load_json_config(&path).unwrap();
+        assert_eq!(lenient.model, None);
+        assert_eq!(
+            load_json_config_strict::<Settings>(temp.path().join("missing.json")).unwrap(),
+            Settings::default()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! 提取配置子集
+//!
+//! 分享部分设置（如只导出 MCP 服务器列表）或附在问题报告中时，
+//! 只保留指定的路径，其余设置不会被带出。
+
+use serde_json::{Map, Value};
+
+use super::pointer::split_pointer;
+
+/// 提取只包含指定 JSON Pointer 的配置
+///
+/// # 参数
+/// - `value`: 完整配置
+/// - `pointers`: 需要保留的路径，如 `["/mcpServers", "/permissions/allow"]`
+///
+/// # 返回值
+/// 只包含这些路径的新值，中间的对象结构会按原样重建
+///
+/// # 特性
+/// - ✅ 不存在的路径被忽略，不会留下空的中间结构
+/// - ✅ 指向数组元素时只保留选中的元素（按原顺序排列）
+/// - ✅ 无效的指针记录警告并忽略
+pub fn extract_config_subset(value: &Value, pointers: &[&str]) -> Value {
+    let selections: Vec<Vec<String>> = pointers
+        .iter()
+        .filter_map(|pointer| match split_pointer(pointer) {
+            Ok(tokens) => Some(tokens),
+            Err(e) => {
+                log::warn!("Ignoring subset pointer: {}", e);
+                None
+            }
+        })
+        .collect();
+    let selections: Vec<&[String]> = selections.iter().map(Vec::as_slice).collect();
+
+    extract(value, &selections).unwrap_or_else(|| match value {
+        Value::Array(_) => Value::Array(Vec::new()),
+        _ => Value::Object(Map::new()),
+    })
+}
+
+/// 没有任何路径命中时返回 `None`，避免留下空的中间结构
+fn extract(value: &Value, selections: &[&[String]]) -> Option<Value> {
+    if selections.iter().any(|tokens| tokens.is_empty()) {
+        return Some(value.clone());
+    }
+
+    let children_of = |key: &str| -> Vec<&[String]> {
+        selections
+            .iter()
+            .filter(|tokens| tokens[0] == key)
+            .map(|tokens| &tokens[1..])
+            .collect()
+    };
+
+    match value {
+        Value::Object(map) => {
+            let map: Map<String, Value> = map
+                .iter()
+                .filter_map(|(key, child)| {
+                    let rest = children_of(key);
+                    extract(child, &rest).map(|child| (key.clone(), child))
+                })
+                .collect();
+            (!map.is_empty()).then_some(Value::Object(map))
+        }
+        Value::Array(items) => {
+            let items: Vec<Value> = items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, child)| extract(child, &children_of(&index.to_string())))
+                .collect();
+            (!items.is_empty()).then_some(Value::Array(items))
+        }
+        // 指针越过了标量值，视为不存在
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_config_subset() {
+        let config = json!({
+            "apiKey": "secret",
+            "mcpServers": { "fs": { "command": "fs-server" } },
+            "permissions": { "allow": ["Read"], "deny": ["Bash(rm:*)"] },
+            "hooks": [{ "name": "a" }, { "name": "b" }, { "name": "c" }]
+        });
+
+        let subset = extract_config_subset(
+            &config,
+            &[
+                "/mcpServers",
+                "/permissions/allow",
+                "/hooks/2",
+                "/hooks/0/name",
+                "/missing",
+            ],
+        );
+        assert_eq!(
+            subset,
+            json!({
+                "mcpServers": { "fs": { "command": "fs-server" } },
+                "permissions": { "allow": ["Read"] },
+                "hooks": [{ "name": "a" }, { "name": "c" }]
+            })
+        );
+
+        assert_eq!(
+            extract_config_subset(&config, &["apiKey", "/apiKey/x", "/permissions/ask"]),
+            json!({})
+        );
+    }
+}
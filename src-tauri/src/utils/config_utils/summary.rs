@@ -0,0 +1,95 @@
+//! 配置概览摘要
+//!
+//! 概览面板只展示少量关键设置。提取与格式化逻辑放在 Rust 侧，
+//! 前端无需重复理解配置结构，摘要与后端对配置的解释保持一致。
+
+use serde_json::Value;
+
+/// Claude 设置的默认摘要字段：`(标签, JSON Pointer)`
+pub const DEFAULT_SUMMARY_FIELDS: &[(&str, &str)] = &[
+    ("Model", "/model"),
+    ("Proxy", "/env/HTTPS_PROXY"),
+    ("Allowed tools", "/permissions/allow"),
+    ("Denied tools", "/permissions/deny"),
+    ("Hooks", "/hooks"),
+];
+
+/// 缺失字段的显示文本
+const NOT_SET: &str = "Not set";
+
+/// 提取配置中的关键设置并格式化为 `(标签, 值)` 对
+///
+/// # 参数
+/// - `value`: 配置值
+/// - `fields`: `(标签, JSON Pointer)` 列表，如 [`DEFAULT_SUMMARY_FIELDS`]
+///
+/// # 格式化规则
+/// - 字符串原样显示，布尔值显示为 `Enabled` / `Disabled`
+/// - 数组显示元素数量，对象显示键数量
+/// - 缺失或为 `null` 时显示 `Not set`
+pub fn summarize_config(value: &Value, fields: &[(&str, &str)]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|(label, pointer)| {
+            let display = value
+                .pointer(pointer)
+                .map_or_else(|| NOT_SET.to_string(), format_summary_value);
+            (label.to_string(), display)
+        })
+        .collect()
+}
+
+fn format_summary_value(value: &Value) -> String {
+    match value {
+        Value::Null => NOT_SET.to_string(),
+        Value::Bool(true) => "Enabled".to_string(),
+        Value::Bool(false) => "Disabled".to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(text) if text.is_empty() => NOT_SET.to_string(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => count_label(items.len(), "item"),
+        Value::Object(map) => count_label(map.len(), "entry"),
+    }
+}
+
+fn count_label(count: usize, noun: &str) -> String {
+    match (count, noun) {
+        (1, _) => format!("1 {}", noun),
+        (_, "entry") => format!("{} entries", count),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summarize_config() {
+        let value = json!({
+            "model": "opus",
+            "permissions": { "allow": ["Read", "Edit", "Bash"], "deny": ["WebFetch"] },
+            "hooks": {},
+            "autoUpdate": false
+        });
+
+        let mut fields = DEFAULT_SUMMARY_FIELDS.to_vec();
+        fields.push(("Auto update", "/autoUpdate"));
+        let summary = summarize_config(&value, &fields);
+
+        let expected = [
+            ("Model", "opus"),
+            ("Proxy", "Not set"),
+            ("Allowed tools", "3 items"),
+            ("Denied tools", "1 item"),
+            ("Hooks", "0 entries"),
+            ("Auto update", "Disabled"),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(label, value)| (label.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(summary, expected);
+    }
+}
@@ -0,0 +1,140 @@
+//! 随应用发布的起始配置模板
+//!
+//! 模板通过 `include_str!` 编译进程序，首次运行或重置时写为当前的 settings.json。
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::history::backup_config_file;
+use super::{save_json_config, save_json_config_if_absent};
+
+/// 模板写入的文件名
+const TEMPLATE_TARGET: &str = "settings.json";
+
+/// `(名称, 说明, 内容)`
+const TEMPLATES: &[(&str, &str, &str)] = &[
+    (
+        "minimal",
+        "Empty permission lists, every tool asks before running",
+        include_str!("templates/minimal.json"),
+    ),
+    (
+        "full-tools",
+        "All built-in tools allowed without prompting",
+        include_str!("templates/full-tools.json"),
+    ),
+];
+
+/// 可用的配置模板
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigTemplate {
+    pub name: String,
+    pub description: String,
+}
+
+/// 已有 settings.json 时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TemplateOverwrite {
+    /// 不覆盖，返回错误（默认）
+    #[default]
+    Never,
+    /// 直接覆盖
+    Replace,
+    /// 先备份（见 [`backup_config_file`]）再覆盖
+    BackupAndReplace,
+}
+
+/// 列出随应用发布的模板
+pub fn list_templates() -> Vec<ConfigTemplate> {
+    TEMPLATES
+        .iter()
+        .map(|(name, description, _)| ConfigTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+/// 将模板写为 `<base_dir>/settings.json`
+///
+/// # 参数
+/// - `base_dir`: 配置目录（如 `~/.claude`）
+/// - `template_name`: 模板名称（见 [`list_templates`]）
+/// - `overwrite`: 已有 settings.json 时的处理方式
+///
+/// # 返回值
+/// - `Ok(())`: 模板已写入
+/// - `Err(String)`: 模板不存在、已有配置且未允许覆盖，或写入失败
+pub fn apply_template(
+    base_dir: impl AsRef<Path>,
+    template_name: &str,
+    overwrite: TemplateOverwrite,
+) -> Result<(), String> {
+    let (_, _, content) = TEMPLATES
+        .iter()
+        .find(|(name, _, _)| *name == template_name)
+        .ok_or_else(|| format!("Unknown config template: {:?}", template_name))?;
+    let template: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid config template {:?}: {}", template_name, e))?;
+
+    let target = base_dir.as_ref().join(TEMPLATE_TARGET);
+    match overwrite {
+        TemplateOverwrite::Never => {
+            if !save_json_config_if_absent(&template, &target)? {
+                return Err(format!(
+                    "Config {:?} already exists; pass an overwrite mode to replace it",
+                    target
+                ));
+            }
+            Ok(())
+        }
+        TemplateOverwrite::Replace => save_json_config(&template, &target),
+        TemplateOverwrite::BackupAndReplace => {
+            backup_config_file(&target)?;
+            save_json_config(&template, &target)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::history::list_config_backups;
+    use super::*;
+
+    #[test]
+    fn test_bundled_templates_are_valid_json() {
+        for (name, _, content) in TEMPLATES {
+            assert!(
+                serde_json::from_str::<Value>(content).is_ok_and(|value| value.is_object()),
+                "template {} is not a JSON object",
+                name
+            );
+        }
+        assert_eq!(list_templates().len(), TEMPLATES.len());
+    }
+
+    #[test]
+    fn test_apply_template() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = temp.path().join("settings.json");
+
+        apply_template(temp.path(), "minimal", TemplateOverwrite::Never).unwrap();
+        let err = apply_template(temp.path(), "full-tools", TemplateOverwrite::Never).unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+
+        apply_template(
+            temp.path(),
+            "full-tools",
+            TemplateOverwrite::BackupAndReplace,
+        )
+        .unwrap();
+        let applied: Value = super::super::load_json_config(&settings).unwrap();
+        assert_eq!(applied["permissions"]["allow"][0], "Bash");
+        assert_eq!(list_config_backups(&settings).unwrap().len(), 1);
+
+        assert!(apply_template(temp.path(), "nope", TemplateOverwrite::Replace).is_err());
+    }
+}
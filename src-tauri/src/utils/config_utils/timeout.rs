@@ -0,0 +1,92 @@
+//! 带超时的配置加载
+//!
+//! 当 `~/.claude` 位于不可达的网络共享上时，读取可能无限期挂起并卡住 Tauri 命令。
+//! 在工作线程中读取，超时后立即返回错误，保持界面响应。
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::load_json_config;
+
+/// 在限定时间内加载配置
+///
+/// # 参数
+/// - `config_path`: 配置文件路径
+/// - `timeout`: 最长等待时间
+///
+/// # 返回值
+/// - `Ok(T)`: 加载成功（文件不存在时为 `T::default()`）
+/// - `Err(String)`: 加载失败，或超时（错误信息以 `Timed out` 开头）
+///
+/// # 注意
+/// 超时后工作线程无法被强制中止，会在读取返回后自行退出，结果被丢弃
+pub fn load_json_config_with_timeout<T>(
+    config_path: impl AsRef<Path>,
+    timeout: Duration,
+) -> Result<T, String>
+where
+    T: for<'de> Deserialize<'de> + Default + Send + 'static,
+{
+    let path = config_path.as_ref().to_path_buf();
+    let (sender, receiver) = mpsc::channel();
+
+    let worker_path = path.clone();
+    thread::Builder::new()
+        .name("config-load".to_string())
+        .spawn(move || {
+            // 调用方已超时返回时接收端已关闭，忽略发送失败
+            let _ = sender.send(load_json_config::<T>(&worker_path));
+        })
+        .map_err(|e| format!("Failed to spawn config loader for {:?}: {}", path, e))?;
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            log::warn!("Loading config {:?} timed out after {:?}", path, timeout);
+            Err(format!(
+                "Timed out after {:?} while loading config from {:?}",
+                timeout, path
+            ))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err(format!("Config loader for {:?} exited unexpectedly", path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_load_with_timeout_returns_config() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "opus"}"#).unwrap();
+
+        let value: Value = load_json_config_with_timeout(&path, Duration::from_secs(5)).unwrap();
+        assert_eq!(value, json!({ "model": "opus" }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_with_timeout_gives_up_on_hanging_read() {
+        let temp = tempfile::tempdir().unwrap();
+        let fifo = temp.path().join("settings.json");
+        // 没有写端的 FIFO 在打开时会一直阻塞，模拟挂起的网络文件系统
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let err =
+            load_json_config_with_timeout::<Value>(&fifo, Duration::from_millis(50)).unwrap_err();
+        assert!(err.starts_with("Timed out"), "{}", err);
+    }
+}
@@ -0,0 +1,100 @@
+//! 批量启用/禁用工具
+//!
+//! 工具面板的开关对应 settings.json 中的 `permissions.allow` 数组。
+//! 手工编辑数组容易重复或误删其他设置，这里在一次加锁的读写中完成修改。
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::pointer::set_pointer;
+use super::update_json_config;
+
+/// 允许的工具列表所在的 JSON Pointer
+pub const ALLOWED_TOOLS_POINTER: &str = "/permissions/allow";
+
+/// 启用或禁用一组工具
+///
+/// # 参数
+/// - `config_path`: 配置文件路径（不存在时创建）
+/// - `tools`: 工具名称（如 `Bash`、`WebFetch`）
+/// - `enabled`: `true` 时加入允许列表，`false` 时从中移除
+///
+/// # 返回值
+/// - `Ok(Vec<String>)`: 修改后的允许列表
+/// - `Err(String)`: 允许列表存在但不是数组，或读写失败
+///
+/// # 特性
+/// - ✅ 保留原有顺序，新启用的工具追加到末尾
+/// - ✅ 去除重复项
+/// - ✅ 其他设置保持不变，写入期间持有写锁（见 [`update_json_config`]）
+pub fn set_tools_enabled(
+    config_path: impl AsRef<Path>,
+    tools: &[&str],
+    enabled: bool,
+) -> Result<Vec<String>, String> {
+    update_json_config(config_path, |value| {
+        let current = match value.pointer(ALLOWED_TOOLS_POINTER) {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.clone(),
+            Some(_) => {
+                return Err(format!(
+                    "{} is not an array of tool names",
+                    ALLOWED_TOOLS_POINTER
+                ))
+            }
+        };
+
+        let mut allowed: Vec<Value> = Vec::with_capacity(current.len() + tools.len());
+        let candidates = current
+            .into_iter()
+            .chain(tools.iter().map(|tool| Value::from(*tool)));
+        for item in candidates {
+            let named = item.as_str().is_some_and(|name| tools.contains(&name));
+            if (named && !enabled) || allowed.contains(&item) {
+                continue;
+            }
+            allowed.push(item);
+        }
+
+        let names = allowed
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        set_pointer(value, ALLOWED_TOOLS_POINTER, Value::Array(allowed))?;
+        Ok(names)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_set_tools_enabled() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"model": "opus", "permissions": {"allow": ["Read", "Bash", "Read"], "deny": ["WebFetch"]}}"#,
+        )
+        .unwrap();
+
+        let allowed = set_tools_enabled(&path, &["Edit", "Bash", "Edit"], true).unwrap();
+        assert_eq!(allowed, vec!["Read", "Bash", "Edit"]);
+
+        let allowed = set_tools_enabled(&path, &["Read", "Grep"], false).unwrap();
+        assert_eq!(allowed, vec!["Bash", "Edit"]);
+
+        let saved: Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(
+            saved,
+            json!({
+                "model": "opus",
+                "permissions": { "allow": ["Bash", "Edit"], "deny": ["WebFetch"] }
+            })
+        );
+    }
+}
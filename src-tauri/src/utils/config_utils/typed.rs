@@ -0,0 +1,247 @@
+//! 类型化配置访问
+//!
+//! 持有配置文件路径和原始 `Value`，既能整体反序列化为 `T`，
+//! 也能按字段读写磁盘上的单个键而不丢失结构体未声明的键。
+//!
+//! ```rust
+//! impl TypedConfig<Settings> {
+//!     fn model(&mut self) -> ConfigField<'_, Settings, String> {
+//!         self.field("/model")
+//!     }
+//! }
+//!
+//! let mut settings = TypedConfig::<Settings>::open(&path)?;
+//! settings.model().set("claude-3".to_string())?;
+//! ```
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::pointer::{remove_pointer, set_pointer};
+use super::{load_json_config, update_json_config};
+
+/// 类型化配置句柄
+pub struct TypedConfig<T> {
+    path: PathBuf,
+    raw: Value,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TypedConfig<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    /// 打开配置文件（不存在时视为空对象）
+    pub fn open(config_path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = config_path.as_ref().to_path_buf();
+        let raw = read_raw(&path)?;
+        Ok(Self {
+            path,
+            raw,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 原始 JSON 值
+    pub fn raw(&self) -> &Value {
+        &self.raw
+    }
+
+    /// 反序列化为完整的配置结构体
+    pub fn get(&self) -> Result<T, String> {
+        serde_json::from_value(self.raw.clone())
+            .map_err(|e| format!("Failed to parse config from {:?}: {}", self.path, e))
+    }
+
+    /// 修改配置结构体并保存
+    ///
+    /// 读取、修改与写回在同一把写锁内完成（见 [`update_json_config`]）。
+    /// 结构体声明的顶层键整体替换为新值：设为 `None` 而被跳过序列化的字段、缩短的映射会从磁盘上删除；
+    /// 结构体未声明的键保留
+    pub fn update<F>(&mut self, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut T),
+    {
+        let path = self.path.clone();
+        self.raw = update_json_config(&path, |raw| {
+            let mut config: T = serde_json::from_value(raw.clone())
+                .map_err(|e| format!("Failed to parse config from {:?}: {}", path, e))?;
+            let before = serialize_object(&config)?;
+            f(&mut config);
+            let after = serialize_object(&config)?;
+
+            if let Value::Object(raw) = raw {
+                for key in before.keys().filter(|key| !after.contains_key(*key)) {
+                    raw.remove(key);
+                }
+                raw.extend(after);
+            }
+            Ok(raw.clone())
+        })?;
+        Ok(())
+    }
+
+    /// 获取单个字段的访问器
+    pub fn field<V>(&mut self, pointer: &str) -> ConfigField<'_, T, V> {
+        ConfigField {
+            config: self,
+            pointer: pointer.to_string(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 在写锁内修改磁盘上的原始值，并更新缓存的值
+    fn edit<F>(&mut self, edit: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Value) -> Result<(), String>,
+    {
+        self.raw = update_json_config(&self.path, |raw| {
+            edit(raw)?;
+            Ok(raw.clone())
+        })?;
+        Ok(())
+    }
+}
+
+/// 单个配置字段的读写器
+pub struct ConfigField<'a, T, V> {
+    config: &'a mut TypedConfig<T>,
+    pointer: String,
+    _marker: PhantomData<V>,
+}
+
+impl<T, V> ConfigField<'_, T, V>
+where
+    T: DeserializeOwned + Serialize,
+    V: DeserializeOwned + Serialize,
+{
+    /// 读取字段值（不存在时返回 `None`）
+    pub fn get(&self) -> Result<Option<V>, String> {
+        match self.config.raw.pointer(&self.pointer) {
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|e| format!("Failed to parse {}: {}", self.pointer, e)),
+            None => Ok(None),
+        }
+    }
+
+    /// 写入字段值并立即保存
+    ///
+    /// 在写锁内重新读取文件，只修改该字段，不覆盖其他键（包括外部修改）
+    pub fn set(self, value: V) -> Result<(), String> {
+        let value = serde_json::to_value(value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.pointer, e))?;
+        let pointer = self.pointer;
+        self.config.edit(|raw| set_pointer(raw, &pointer, value))
+    }
+
+    /// 删除字段并立即保存
+    pub fn remove(self) -> Result<(), String> {
+        let pointer = self.pointer;
+        self.config.edit(|raw| {
+            remove_pointer(raw, &pointer);
+            Ok(())
+        })
+    }
+}
+
+fn serialize_object<T: Serialize>(config: &T) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err("Typed config must serialize to a JSON object".to_string()),
+        Err(e) => Err(format!("Failed to serialize config: {}", e)),
+    }
+}
+
+fn read_raw(path: &Path) -> Result<Value, String> {
+    let raw: Value = load_json_config(path)?;
+    Ok(if raw.is_null() {
+        Value::Object(Map::new())
+    } else {
+        raw
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Settings {
+        #[serde(default)]
+        model: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        theme: Option<String>,
+        #[serde(default)]
+        env: std::collections::BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn test_field_set_preserves_unknown_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(&path, r#"{"model": "old", "custom": {"x": 1}}"#).unwrap();
+
+        let mut settings = TypedConfig::<Settings>::open(&path).unwrap();
+        assert_eq!(settings.path(), path);
+        settings
+            .field::<String>("/model")
+            .set("claude-3".to_string())
+            .unwrap();
+        assert_eq!(
+            settings.field::<String>("/model").get().unwrap().as_deref(),
+            Some("claude-3")
+        );
+
+        settings.update(|s| s.model.push_str("-opus")).unwrap();
+
+        let on_disk: Value = load_json_config(&path).unwrap();
+        assert_eq!(
+            on_disk,
+            json!({ "model": "claude-3-opus", "custom": { "x": 1 }, "env": {} })
+        );
+        assert_eq!(settings.get().unwrap().model, "claude-3-opus");
+
+        settings.field::<Value>("/custom").remove().unwrap();
+        assert_eq!(settings.raw().get("custom"), None);
+        let on_disk: Value = load_json_config(&path).unwrap();
+        assert_eq!(on_disk.get("custom"), None);
+    }
+
+    #[test]
+    fn test_update_replaces_declared_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        std::fs::write(
+            &path,
+            r#"{"theme": "dark", "env": {"A": "1", "B": "2"}, "custom": true}"#,
+        )
+        .unwrap();
+
+        let mut settings = TypedConfig::<Settings>::open(&path).unwrap();
+        settings
+            .update(|s| {
+                s.theme = None;
+                s.env.remove("B");
+            })
+            .unwrap();
+
+        let on_disk: Value = load_json_config(&path).unwrap();
+        assert_eq!(
+            on_disk,
+            json!({ "model": "", "env": { "A": "1" }, "custom": true })
+        );
+        assert_eq!(settings.raw(), &on_disk);
+    }
+}
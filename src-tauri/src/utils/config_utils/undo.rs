@@ -0,0 +1,117 @@
+//! 可撤销的配置修改
+//!
+//! 设置编辑器中的每次修改都应能撤销。[`apply_with_undo`] 应用局部更新时，
+//! 由修改前后的值计算逆向 JSON Patch 存入 [`UndoToken`]，无需快照整个文件；
+//! [`undo`] 应用逆向补丁并返回用于重做的令牌，界面据此维护撤销/重做栈。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::merge::deep_merge;
+use super::patch::{apply_patch_to_value, create_config_patch};
+use super::update_json_config;
+
+/// 一次修改的撤销令牌
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoToken {
+    pub path: PathBuf,
+    /// 撤销时应用的 JSON Patch（带 `test` 操作）
+    pub inverse: Value,
+    /// 重做时应用的 JSON Patch
+    pub forward: Value,
+}
+
+impl UndoToken {
+    /// 修改是否没有改变任何内容
+    pub fn is_empty(&self) -> bool {
+        self.inverse.as_array().is_none_or(Vec::is_empty)
+    }
+}
+
+/// 将局部更新深度合并到配置文件，并返回撤销令牌
+///
+/// # 参数
+/// - `config_path`: 配置文件路径（不存在时从空对象开始）
+/// - `patch`: 局部更新，按 [`deep_merge`] 的规则合并
+///
+/// # 返回值
+/// - `Ok(UndoToken)`: 已保存；令牌可传给 [`undo`]
+/// - `Err(String)`: 读取或写入失败
+pub fn apply_with_undo(config_path: impl AsRef<Path>, patch: &Value) -> Result<UndoToken, String> {
+    let path = config_path.as_ref();
+    update_json_config(path, |live| {
+        let before = live.clone();
+        deep_merge(live, patch.clone());
+        Ok(UndoToken {
+            path: path.to_path_buf(),
+            inverse: create_config_patch(live, &before),
+            forward: create_config_patch(&before, live),
+        })
+    })
+}
+
+/// 撤销一次修改
+///
+/// # 返回值
+/// - `Ok(UndoToken)`: 已撤销；返回的令牌再次传给 `undo` 即为重做
+/// - `Err(String)`: 被修改的字段之后又被改动（逆向补丁的 `test` 失败），
+///   为避免覆盖后来的修改，配置文件保持不变
+pub fn undo(token: &UndoToken) -> Result<UndoToken, String> {
+    update_json_config(&token.path, |live| {
+        apply_patch_to_value(live, &token.inverse)
+            .map_err(|e| format!("Cannot undo change to {:?}: {}", token.path, e))?;
+        Ok(UndoToken {
+            path: token.path.clone(),
+            inverse: token.forward.clone(),
+            forward: token.inverse.clone(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::load_json_config;
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_apply_with_undo_and_redo() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, r#"{"model": "sonnet", "env": {"A": "1"}}"#).unwrap();
+
+        let token = apply_with_undo(
+            &path,
+            &json!({ "model": "opus", "env": { "B": "2" }, "verbose": true }),
+        )
+        .unwrap();
+        assert!(!token.is_empty());
+        let applied: Value = load_json_config(&path).unwrap();
+
+        let redo = undo(&token).unwrap();
+        assert_eq!(
+            load_json_config::<Value>(&path).unwrap(),
+            json!({ "model": "sonnet", "env": { "A": "1" } })
+        );
+
+        undo(&redo).unwrap();
+        assert_eq!(load_json_config::<Value>(&path).unwrap(), applied);
+    }
+
+    #[test]
+    fn test_undo_refuses_after_later_edit() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+
+        let token = apply_with_undo(&path, &json!({ "model": "opus" })).unwrap();
+        fs::write(&path, r#"{"model": "haiku"}"#).unwrap();
+
+        let err = undo(&token).unwrap_err();
+        assert!(err.contains("conflict at /model"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"model": "haiku"}"#);
+    }
+}
@@ -0,0 +1,92 @@
+//! 写后校验的配置保存
+//!
+//! 某些网络文件系统上写入"成功"但数据并未真正落盘，随后的读取仍返回旧内容，
+//! 直到下次加载才会发现修改丢失。关键的保存可以在写入后重新读取文件并与预期内容比较。
+
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::atomic::atomic_write;
+use super::lock::lock_config;
+use super::{history, metrics, rate_limit, serialize_config};
+
+/// 保存配置并重新读取校验
+///
+/// # 返回值
+/// - `Ok(())`: 已写入，且读回的内容与写入的完全一致
+/// - `Err(String)`: 写入失败，或读回的内容不一致（静默的写入失败）
+///
+/// # 特性
+/// - ✅ 与 [`save_json_config`] 相同的写锁、原子写入和格式
+/// - ✅ 写入与读回都在写锁内完成，其他保存不会干扰校验
+/// - ✅ 读回时重新打开文件，网络文件系统会按"关闭后打开"的一致性重新验证缓存
+///
+/// [`save_json_config`]: super::save_json_config
+pub fn save_json_config_verified<T>(config: &T, config_path: impl AsRef<Path>) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let path = config_path.as_ref();
+    let started = Instant::now();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory {:?}: {}", parent, e))?;
+    }
+
+    let _lock = lock_config(path)?;
+    rate_limit::admit_write(path)?;
+    let content = serialize_config(config)?;
+    history::before_save(path);
+    atomic_write(path, content.as_bytes())?;
+    verify_written(path, content.as_bytes())?;
+    history::after_save(path);
+
+    metrics::observe_save(path, started.elapsed());
+    log::debug!("Config saved and verified at {:?}", path);
+    Ok(())
+}
+
+fn verify_written(path: &Path, expected: &[u8]) -> Result<(), String> {
+    let actual =
+        fs::read(path).map_err(|e| format!("Failed to re-read config {:?}: {}", path, e))?;
+    if actual == expected {
+        return Ok(());
+    }
+
+    log::error!("Config write to {:?} did not persist", path);
+    Err(format!(
+        "Config write to {:?} did not persist: wrote {} bytes but read back {} bytes{}",
+        path,
+        expected.len(),
+        actual.len(),
+        if actual.len() == expected.len() {
+            " with different content"
+        } else {
+            ""
+        }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_save_json_config_verified() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("nested").join("settings.json");
+
+        save_json_config_verified(&json!({ "model": "opus" }), &path).unwrap();
+        let saved: serde_json::Value = super::super::load_json_config(&path).unwrap();
+        assert_eq!(saved, json!({ "model": "opus" }));
+
+        // 模拟读回旧内容
+        let err = verify_written(&path, b"{\"model\": \"sonnet\"}").unwrap_err();
+        assert!(err.contains("did not persist"), "{}", err);
+    }
+}
@@ -0,0 +1,212 @@
+//! 配置文件变化监听
+//!
+//! 按固定间隔轮询文件的修改时间与大小，变化时执行回调。
+//! 轮询不依赖平台的文件通知机制，原子替换（新 inode）同样能被发现。
+//! [`watch_config_events`] 在此基础上加载并校验新内容，产生可直接推送给前端的 [`ConfigEvent`]。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::load_json_config;
+
+/// 配置变化且校验通过时的事件名
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// 配置变化但无法加载或校验失败时的事件名
+pub const CONFIG_INVALID_EVENT: &str = "config-invalid";
+
+/// 配置变化事件，序列化为事件负载（`{ path, value }` 或 `{ path, error }`）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ConfigEvent {
+    Changed { path: PathBuf, value: Value },
+    Invalid { path: PathBuf, error: String },
+}
+
+impl ConfigEvent {
+    /// 事件名（[`CONFIG_CHANGED_EVENT`] 或 [`CONFIG_INVALID_EVENT`]）
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigEvent::Changed { .. } => CONFIG_CHANGED_EVENT,
+            ConfigEvent::Invalid { .. } => CONFIG_INVALID_EVENT,
+        }
+    }
+}
+
+/// 配置监听器，drop 时停止监听线程
+pub struct ConfigWatcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// 停止监听并等待线程退出
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 监听配置文件，每次变化后执行 `on_change`
+///
+/// 启动时只记录当前状态，不触发回调
+///
+/// # 参数
+/// - `path`: 配置文件路径（可以暂不存在，创建和删除同样视为变化）
+/// - `interval`: 轮询间隔
+/// - `on_change`: 变化回调，参数为配置文件路径
+pub fn watch_config_changes<F>(
+    path: impl AsRef<Path>,
+    interval: Duration,
+    on_change: F,
+) -> ConfigWatcher
+where
+    F: FnMut(&Path) + Send + 'static,
+{
+    spawn_watcher(path.as_ref().to_path_buf(), interval, false, on_change)
+}
+
+/// 监听配置文件，每次变化后加载、校验并产生事件
+///
+/// # 参数
+/// - `path`: 配置文件路径
+/// - `interval`: 轮询间隔
+/// - `validator`: 类型化加载成功后的额外校验，返回所有校验错误
+/// - `on_event`: 事件回调，校验通过时为 [`ConfigEvent::Changed`]（`value` 为规范化后的配置），
+///   加载或校验失败时为 [`ConfigEvent::Invalid`]
+pub fn watch_config_events<T, V, F>(
+    path: impl AsRef<Path>,
+    interval: Duration,
+    validator: V,
+    mut on_event: F,
+) -> ConfigWatcher
+where
+    T: for<'de> Deserialize<'de> + Serialize + Default,
+    V: Fn(&T) -> Result<(), Vec<String>> + Send + 'static,
+    F: FnMut(ConfigEvent) + Send + 'static,
+{
+    watch_config_changes(path, interval, move |path| {
+        let path = path.to_path_buf();
+        let loaded = load_json_config::<T>(&path).and_then(|config| {
+            validator(&config).map_err(|errors| errors.join("; "))?;
+            serde_json::to_value(&config).map_err(|e| format!("Failed to serialize config: {}", e))
+        });
+        on_event(match loaded {
+            Ok(value) => ConfigEvent::Changed { path, value },
+            Err(error) => {
+                log::warn!("Config {:?} changed but is invalid: {}", path, error);
+                ConfigEvent::Invalid { path, error }
+            }
+        });
+    })
+}
+
+/// 启动轮询线程；`fire_initially` 为 `true` 时启动后立即执行一次回调
+pub(crate) fn spawn_watcher<F>(
+    path: PathBuf,
+    interval: Duration,
+    fire_initially: bool,
+    mut on_change: F,
+) -> ConfigWatcher
+where
+    F: FnMut(&Path) + Send + 'static,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+
+    let handle = thread::spawn(move || {
+        let mut last_seen = (!fire_initially).then(|| file_stamp(&path));
+        while !stop_flag.load(Ordering::SeqCst) {
+            let current = file_stamp(&path);
+            if last_seen.as_ref() != Some(&current) {
+                last_seen = Some(current);
+                on_change(&path);
+            }
+            thread::sleep(interval);
+        }
+    });
+
+    ConfigWatcher {
+        stop,
+        handle: Some(handle),
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[derive(Debug, Default, Deserialize, Serialize)]
+    struct Settings {
+        #[serde(default)]
+        model: String,
+    }
+
+    #[test]
+    fn test_watch_config_events() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("settings.json");
+        let (sender, receiver) = mpsc::channel();
+
+        let watcher = watch_config_events(
+            &path,
+            Duration::from_millis(10),
+            |settings: &Settings| {
+                if settings.model.is_empty() {
+                    Err(vec!["model is required".to_string()])
+                } else {
+                    Ok(())
+                }
+            },
+            move |event| sender.send(event).unwrap(),
+        );
+        let next = || receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        // 等待监听线程记录初始状态
+        thread::sleep(Duration::from_millis(50));
+
+        fs::write(&path, r#"{"model": "opus"}"#).unwrap();
+        let event = next();
+        assert_eq!(event.name(), CONFIG_CHANGED_EVENT);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "path": path, "value": { "model": "opus" } })
+        );
+
+        fs::write(&path, r#"{"model": ""}"#).unwrap();
+        let event = next();
+        assert_eq!(event.name(), CONFIG_INVALID_EVENT);
+        assert_eq!(
+            event,
+            ConfigEvent::Invalid {
+                path: path.clone(),
+                error: "model is required".to_string()
+            }
+        );
+        watcher.stop();
+    }
+}
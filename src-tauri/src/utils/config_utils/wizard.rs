@@ -0,0 +1,209 @@
+//! 引导式配置生成（首次运行向导）
+//!
+//! 前端根据声明式的问题列表渲染向导，收集答案后交给后端逐项校验，
+//! 并按问题的点分键组装为配置对象。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::overrides::dotted_key_to_pointer;
+use super::pointer::set_pointer;
+
+/// 答案类型及其约束
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AnswerKind {
+    /// 字符串，可选正则约束
+    String {
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+    /// 任意数字
+    Number {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// 整数
+    Integer {
+        #[serde(default)]
+        min: Option<i64>,
+        #[serde(default)]
+        max: Option<i64>,
+    },
+    Boolean,
+    /// 从给定选项中选择一个
+    Choice {
+        options: Vec<String>,
+    },
+}
+
+/// 向导中的一个问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WizardQuestion {
+    /// 答案写入的点分键（如 `proxy.url`）
+    pub key: String,
+    #[serde(flatten)]
+    pub kind: AnswerKind,
+    /// 未作答时使用的默认值
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// 未作答且无默认值时是否报错
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// 根据向导答案组装配置
+///
+/// # 参数
+/// - `spec`: 问题列表
+/// - `answers`: 问题键到答案的映射
+///
+/// # 返回值
+/// - `Ok(Value)`: 组装好的配置对象（可直接交给 `save_json_config` 保存）
+/// - `Err(Vec<String>)`: 每个无效答案一条错误，形如 `proxy.url: ...`
+pub fn build_config_from_answers(
+    spec: &[WizardQuestion],
+    mut answers: BTreeMap<String, Value>,
+) -> Result<Value, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut config = Value::Object(Map::new());
+
+    for question in spec {
+        let answer = match answers.remove(&question.key) {
+            Some(answer) => answer,
+            None => match &question.default {
+                Some(default) => default.clone(),
+                None if question.required => {
+                    errors.push(format!("{}: an answer is required", question.key));
+                    continue;
+                }
+                None => continue,
+            },
+        };
+
+        let result = validate_answer(&question.kind, &answer).and_then(|_| {
+            let pointer = dotted_key_to_pointer(&question.key)?;
+            set_pointer(&mut config, &pointer, answer)
+        });
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", question.key, e));
+        }
+    }
+
+    errors.extend(
+        answers
+            .into_keys()
+            .map(|key| format!("{}: no such question", key)),
+    );
+
+    if errors.is_empty() {
+        Ok(config)
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_answer(kind: &AnswerKind, answer: &Value) -> Result<(), String> {
+    match kind {
+        AnswerKind::String { pattern } => {
+            let text = answer.as_str().ok_or("expected a string")?;
+            if let Some(pattern) = pattern {
+                let regex = regex::Regex::new(pattern)
+                    .map_err(|e| format!("invalid pattern {:?}: {}", pattern, e))?;
+                if !regex.is_match(text) {
+                    return Err(format!("{:?} does not match {:?}", text, pattern));
+                }
+            }
+            Ok(())
+        }
+        AnswerKind::Number { min, max } => {
+            let number = answer.as_f64().ok_or("expected a number")?;
+            check_range(number, *min, *max)
+        }
+        AnswerKind::Integer { min, max } => {
+            let number = answer.as_i64().ok_or("expected an integer")?;
+            check_range(number, *min, *max)
+        }
+        AnswerKind::Boolean => answer
+            .is_boolean()
+            .then_some(())
+            .ok_or_else(|| "expected true or false".to_string()),
+        AnswerKind::Choice { options } => {
+            let choice = answer.as_str().ok_or("expected one of the options")?;
+            if options.iter().any(|option| option == choice) {
+                Ok(())
+            } else {
+                Err(format!("{:?} is not one of {:?}", choice, options))
+            }
+        }
+    }
+}
+
+fn check_range<N>(value: N, min: Option<N>, max: Option<N>) -> Result<(), String>
+where
+    N: PartialOrd + std::fmt::Display + Copy,
+{
+    if let Some(min) = min.filter(|min| value < *min) {
+        return Err(format!("{} is less than the minimum {}", value, min));
+    }
+    if let Some(max) = max.filter(|max| value > *max) {
+        return Err(format!("{} is greater than the maximum {}", value, max));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec() -> Vec<WizardQuestion> {
+        serde_json::from_value(json!([
+            { "key": "model", "type": "choice", "options": ["sonnet", "opus"], "required": true },
+            { "key": "proxy.url", "type": "string", "pattern": "^https?://" },
+            { "key": "timeoutSeconds", "type": "integer", "min": 1, "default": 30 },
+            { "key": "telemetry", "type": "boolean", "default": false }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_config_from_answers() {
+        let answers = BTreeMap::from([
+            ("model".to_string(), json!("opus")),
+            ("proxy.url".to_string(), json!("http://localhost:8080")),
+        ]);
+
+        let config = build_config_from_answers(&spec(), answers).unwrap();
+        assert_eq!(
+            config,
+            json!({
+                "model": "opus",
+                "proxy": { "url": "http://localhost:8080" },
+                "timeoutSeconds": 30,
+                "telemetry": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_config_reports_every_invalid_answer() {
+        let answers = BTreeMap::from([
+            ("proxy.url".to_string(), json!("localhost")),
+            ("timeoutSeconds".to_string(), json!(0)),
+            ("unknown".to_string(), json!(1)),
+        ]);
+
+        let errors = build_config_from_answers(&spec(), answers).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("model: an answer is required"));
+        assert!(errors[1].starts_with("proxy.url:"));
+        assert!(errors[2].starts_with("timeoutSeconds: 0 is less than"));
+        assert_eq!(errors[3], "unknown: no such question");
+    }
+}
@@ -991,6 +991,48 @@ export const api = {
     }
   },
 
+  /**
+   * Get the JSON Schema of the Claude execution configuration
+   * @returns Promise resolving to a JSON Schema used to render the settings form
+   */
+  async getClaudeExecutionConfigSchema(): Promise<Record<string, any>> {
+    try {
+      return await invoke<Record<string, any>>("get_claude_execution_config_schema");
+    } catch (error) {
+      console.error("Failed to get Claude execution config schema:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Start watching a config file for external changes
+   * @param path - The absolute path to the config file
+   * @returns Promise resolving when watching starts (changes are pushed via the
+   * "config-changed" and "config-invalid" events)
+   */
+  async watchConfigFile(path: string): Promise<void> {
+    try {
+      return await invoke<void>("watch_config_file", { path });
+    } catch (error) {
+      console.error("Failed to watch config file:", error);
+      throw error;
+    }
+  },
+
+  /**
+   * Stop watching a config file
+   * @param path - The absolute path to the config file
+   * @returns Promise resolving to whether the file was being watched
+   */
+  async unwatchConfigFile(path: string): Promise<boolean> {
+    try {
+      return await invoke<boolean>("unwatch_config_file", { path });
+    } catch (error) {
+      console.error("Failed to unwatch config file:", error);
+      throw error;
+    }
+  },
+
   /**
    * Update Claude execution configuration
    * @param config - The new execution configuration